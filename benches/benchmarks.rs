@@ -18,6 +18,9 @@ criterion::criterion_group! {
         computing_optimal_transport_variation,
         computing_optimal_transport_heuristic,
         computing_optimal_transport_sinkhorns,
+        building_preflop_tree,
+        visiting_preflop_line,
+        computing_river_regret_vectors,
 }
 
 fn sampling_river_evaluation(c: &mut criterion::Criterion) {
@@ -101,17 +104,138 @@ fn computing_optimal_transport_sinkhorns(c: &mut criterion::Criterion) {
      */
 }
 
+fn preflop_encoder() -> Encoder {
+    Encoder::from(
+        IsomorphismIterator::from(Street::Pref)
+            .enumerate()
+            .map(|(i, iso)| (iso, Abstraction::from((Street::Pref, i))))
+            .collect::<BTreeMap<_, _>>(),
+    )
+}
+
+fn building_preflop_tree(c: &mut criterion::Criterion) {
+    let ref encoder = preflop_encoder();
+    c.bench_function("build every preflop betting Branch eagerly", |b| {
+        b.iter(|| {
+            let game = Game::root();
+            let mut tree = Tree::empty(Player::default());
+            let root = tree.plant(Data::from((game, encoder.abstraction(&game))));
+            let mut todo = encoder.branches(&root);
+            while let Some(branch) = todo.pop() {
+                let ref node = tree.fork(branch);
+                if node.player() != Player::chance() {
+                    todo.extend(encoder.branches(node));
+                }
+            }
+            tree
+        })
+    });
+}
+
+fn visiting_preflop_line(c: &mut criterion::Criterion) {
+    let ref encoder = preflop_encoder();
+    c.bench_function("visit one preflop betting line lazily", |b| {
+        b.iter(|| {
+            let game = Game::root();
+            let mut tree = Tree::empty(Player::default());
+            let mut head = tree
+                .plant(Data::from((game, encoder.abstraction(&game))))
+                .index();
+            while let Some(edge) = Some(tree.at(head))
+                .filter(|node| node.player() != Player::chance())
+                .and_then(|node| node.edges().last().copied())
+            {
+                head = tree.visit(head, &edge, encoder).index();
+            }
+            tree
+        })
+    });
+}
+
+fn computing_river_regret_vectors(c: &mut criterion::Criterion) {
+    let abstraction = Abstraction::from((Street::Rive, 0));
+    let branches = |tree: &Tree, index| {
+        tree.at(index)
+            .branches()
+            .into_iter()
+            .map(|(e, g)| Branch(Data::from((g, abstraction)), e, index))
+            .collect::<Vec<Branch>>()
+    };
+    let mut profile = Profile::default();
+    let mut tree = Tree::empty(Player::default());
+    let mut head = tree.plant(Data::from((Game::root(), abstraction))).index();
+    while tree.at(head).data().game().street() != Street::Rive
+        || tree.at(head).player() == Player::chance()
+    {
+        let branches = branches(&tree, head);
+        if tree.at(head).player() != Player::chance() {
+            profile.witness(&tree.at(head), &branches);
+        }
+        let branch = branches
+            .into_iter()
+            .find(|b| matches!(b.edge(), Edge::Check | Edge::Call | Edge::Draw))
+            .unwrap();
+        head = tree.fork(branch).index();
+    }
+    let mut todo = vec![head];
+    while let Some(index) = todo.pop() {
+        let branches = branches(&tree, index);
+        if !branches.is_empty() {
+            profile.witness(&tree.at(index), &branches);
+        }
+        for branch in branches {
+            todo.push(tree.fork(branch).index());
+        }
+    }
+    let infos = Vec::<Info>::from(Partition::from(tree))
+        .into_iter()
+        .filter(|info| info.node().data().game().street() == Street::Rive)
+        .collect::<Vec<Info>>();
+    c.bench_function("compute regret vectors of a River subgame", |b| {
+        b.iter(|| infos.iter().map(|info| profile.regret_vector(info)).count())
+    });
+    c.bench_function(
+        "compute counterfactuals of a River subgame in serial",
+        |b| {
+            b.iter(|| {
+                infos
+                    .iter()
+                    .cloned()
+                    .map(|info| profile.counterfactual(info))
+                    .count()
+            })
+        },
+    );
+    c.bench_function(
+        "compute counterfactuals of a River subgame in parallel",
+        |b| b.iter(|| profile.counterfactuals(infos.clone()).len()),
+    );
+}
+
 use robopoker::cards::evaluator::Evaluator;
 use robopoker::cards::hand::Hand;
 use robopoker::cards::isomorphism::Isomorphism;
+use robopoker::cards::isomorphisms::IsomorphismIterator;
 use robopoker::cards::observation::Observation;
 use robopoker::cards::observations::ObservationIterator;
 use robopoker::cards::street::Street;
 use robopoker::cards::strength::Strength;
+use robopoker::clustering::abstraction::Abstraction;
 use robopoker::clustering::emd::EMD;
 use robopoker::clustering::equity::Equity;
 use robopoker::clustering::heuristic::Heuristic;
 use robopoker::clustering::histogram::Histogram;
 use robopoker::clustering::sinkhorn::Sinkhorn;
+use robopoker::gameplay::game::Game;
+use robopoker::mccfr::data::Data;
+use robopoker::mccfr::edge::Edge;
+use robopoker::mccfr::encoder::Encoder;
+use robopoker::mccfr::info::Info;
+use robopoker::mccfr::partition::Partition;
+use robopoker::mccfr::player::Player;
+use robopoker::mccfr::profile::Profile;
+use robopoker::mccfr::tree::Branch;
+use robopoker::mccfr::tree::Tree;
 use robopoker::transport::coupling::Coupling;
 use robopoker::Arbitrary;
+use std::collections::BTreeMap;
@@ -9,7 +9,7 @@ use crate::clustering::pair::Pair;
 use crate::clustering::sinkhorn::Sinkhorn;
 use crate::transport::coupling::Coupling;
 use crate::Energy;
-use crate::Probability;
+use crate::Equity;
 use std::collections::BTreeMap;
 use std::collections::HashSet;
 use std::sync::Arc;
@@ -29,6 +29,28 @@ impl API {
         Self(crate::db().await)
     }
 
+    /// which streets have abstractions in the database,
+    /// and whether any blueprint rows have been uploaded
+    pub async fn available(&self) -> Result<(Vec<Street>, bool), E> {
+        const STREETS: &'static str = r#"
+            SELECT DISTINCT street
+            FROM abstraction
+        "#;
+        const BLUEPRINT: &'static str = r#"
+            SELECT EXISTS (SELECT 1 FROM blueprint)
+        "#;
+        let streets = self
+            .0
+            .query(STREETS, &[])
+            .await?
+            .iter()
+            .map(|row| row.get::<_, i16>(0))
+            .map(|street| Street::from(street as isize))
+            .collect();
+        let blueprint = self.0.query_one(BLUEPRINT, &[]).await?.get::<_, bool>(0);
+        Ok((streets, blueprint))
+    }
+
     // global lookups
     pub async fn obs_to_abs(&self, obs: Observation) -> Result<Abstraction, E> {
         let iso = i64::from(Isomorphism::from(obs));
@@ -45,7 +67,6 @@ impl API {
             .into())
     }
     pub async fn metric(&self, street: Street) -> Result<Metric, E> {
-        let street = street as i16;
         const SQL: &'static str = r#"
             SELECT
                 a1.abs # a2.abs AS xor,
@@ -59,15 +80,15 @@ impl API {
                 a1.street   = $1 AND
                 a1.abs     != a2.abs;
         "#;
-        Ok(self
+        let rows = self
             .0
-            .query(SQL, &[&street])
+            .query(SQL, &[&(street as i16)])
             .await?
             .iter()
             .map(|row| (row.get::<_, i64>(0), row.get::<_, Energy>(1)))
             .map(|(xor, distance)| (Pair::from(xor), distance))
-            .collect::<BTreeMap<Pair, Energy>>()
-            .into())
+            .collect::<BTreeMap<Pair, Energy>>();
+        Ok(Metric::from((street, rows)))
     }
     pub async fn basis(&self, street: Street) -> Result<Vec<Abstraction>, E> {
         let street = street as i16;
@@ -88,7 +109,7 @@ impl API {
     }
 
     // equity calculations
-    pub async fn abs_equity(&self, abs: Abstraction) -> Result<Probability, E> {
+    pub async fn abs_equity(&self, abs: Abstraction) -> Result<Equity, E> {
         let iso = i64::from(abs);
         const SQL: &'static str = r#"
             SELECT equity
@@ -102,7 +123,7 @@ impl API {
             .get::<_, f32>(0)
             .into())
     }
-    pub async fn obs_equity(&self, obs: Observation) -> Result<Probability, E> {
+    pub async fn obs_equity(&self, obs: Observation) -> Result<Equity, E> {
         let iso = i64::from(Isomorphism::from(obs));
         let sql = if obs.street() == Street::Rive {
             r#"
@@ -177,7 +198,7 @@ impl API {
     }
 
     // centrality (mean distance) lookups
-    pub async fn abs_centrality(&self, abs: Abstraction) -> Result<Probability, E> {
+    pub async fn abs_centrality(&self, abs: Abstraction) -> Result<Energy, E> {
         let abs = i64::from(abs);
         const SQL: &'static str = r#"
             SELECT centrality
@@ -191,7 +212,7 @@ impl API {
             .get::<_, f32>(0)
             .into())
     }
-    pub async fn obs_centrality(&self, obs: Observation) -> Result<Probability, E> {
+    pub async fn obs_centrality(&self, obs: Observation) -> Result<Energy, E> {
         let iso = i64::from(Isomorphism::from(obs));
         const SQL: &'static str = r#"
             SELECT centrality
@@ -704,7 +725,7 @@ impl API {
                 (
                     Observation::from(row.get::<_, i64>(0)),
                     Abstraction::from(row.get::<_, i64>(1)),
-                    Probability::from(row.get::<_, f32>(2)),
+                    row.get::<_, f32>(2),
                 )
             })
             .map(|(obs, abs, equity)| (obs, (abs, equity)))
@@ -721,7 +742,7 @@ impl API {
                 obs: obs.equivalent(),
                 abs: abs.to_string(),
                 equity: eqy.clone(),
-                density: pop as Probability / n as Probability,
+                density: pop as f32 / n as f32,
                 distance: 0.,
             })
             .collect::<Vec<_>>();
@@ -800,6 +821,12 @@ use crate::mccfr::recall::Recall;
 
 // blueprint lookups
 impl API {
+    /// the Bucket a Recall is played from, abstracted through the database
+    pub async fn bucket(&self, recall: &Recall) -> Result<Bucket, E> {
+        let observation = recall.head().sweat();
+        let abstraction = self.obs_to_abs(observation).await?;
+        Ok(recall.bucket(abstraction))
+    }
     pub async fn policy(&self, recall: Recall) -> Result<Vec<Decision>, E> {
         const SQL: &'static str = r#"
         -- policy is indexed by present, past, future
@@ -812,10 +839,7 @@ impl API {
             AND   present = $2
             AND   future  = $3
         "#;
-        let game = recall.head();
-        let observation = game.sweat();
-        let abstraction = self.obs_to_abs(observation).await?;
-        let Bucket(history, present, choices) = recall.bucket(abstraction);
+        let Bucket(history, present, choices) = self.bucket(&recall).await?;
         let ref history = i64::from(history);
         let ref present = i64::from(present);
         let ref choices = i64::from(choices);
@@ -2,8 +2,11 @@ use super::api::API;
 use super::query::Query;
 use crate::cards::hand::Hand;
 use crate::cards::observation::Observation;
+use crate::cards::street::Street;
 use crate::cards::strength::Strength;
 use crate::clustering::abstraction::Abstraction;
+use crate::clustering::lookup::Lookup;
+use crate::save::upload::Table;
 use clap::Parser;
 use std::io::Write;
 
@@ -152,7 +155,7 @@ impl CLI {
                         .0
                         .obs_histogram(obs)
                         .await?
-                        .distribution()
+                        .ranked()
                         .iter()
                         .enumerate()
                         .map(|(i, (abs, dist))| format!("{:>2}. {} ({:.4})", i + 1, abs, dist))
@@ -165,7 +168,7 @@ impl CLI {
                         .0
                         .abs_histogram(abs)
                         .await?
-                        .distribution()
+                        .ranked()
                         .iter()
                         .enumerate()
                         .map(|(i, (abs, dist))| format!("{:>2}. {} ({:.4})", i + 1, abs, dist))
@@ -175,6 +178,14 @@ impl CLI {
                 }
                 Err("invalid histogram target".into())
             }
+
+            Query::Summary { street } => {
+                let street = Street::try_from(street.as_str())?;
+                if !Lookup::done(street) {
+                    return Err(format!("no {} lookup on disk", street).into());
+                }
+                Ok(println!("{}", Lookup::load(street)?.summary()))
+            }
         }
     }
 }
@@ -0,0 +1,137 @@
+use super::readiness::Readiness;
+use super::response::Decision;
+use crate::mccfr::bucket::Bucket;
+use crate::mccfr::profile::Profile;
+use crate::mccfr::strategy::StrategyKind;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::RwLock;
+use std::sync::TryLockError;
+
+/// the blueprint currently being served.
+/// handlers clone the inner Arc and query it without holding the lock,
+/// so a reload only waits for other clones to be taken, never for
+/// in-flight queries to finish. those keep answering from the old Profile
+/// until they drop their Arc. reloads are serialized by their own
+/// lock, so at most one is ever validating a blueprint.
+#[derive(Default)]
+pub struct Live {
+    served: RwLock<Arc<Profile>>,
+    reloading: Mutex<()>,
+}
+
+/// why a blueprint was not swapped in
+#[derive(Debug, PartialEq)]
+pub enum ReloadError {
+    /// another reload is still in flight
+    Busy,
+    /// the blueprint on disk failed validation
+    Rejected(String),
+}
+
+impl std::fmt::Display for ReloadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Busy => write!(f, "a reload is already in progress"),
+            Self::Rejected(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<Profile> for Live {
+    fn from(profile: Profile) -> Self {
+        Self {
+            served: RwLock::new(Arc::new(profile)),
+            reloading: Mutex::default(),
+        }
+    }
+}
+
+impl Live {
+    /// snapshot of the served blueprint
+    pub fn current(&self) -> Arc<Profile> {
+        self.served.read().expect("live blueprint lock").clone()
+    }
+    /// averaged strategy of the served blueprint at a Bucket,
+    /// or nothing if it was never trained there
+    pub fn decisions(&self, bucket: &Bucket) -> Vec<Decision> {
+        let profile = self.current();
+        match profile.is_witnessed(bucket) {
+            false => vec![],
+            true => profile
+                .distribution(StrategyKind::Average, bucket)
+                .into_iter()
+                .map(Decision::from)
+                .collect(),
+        }
+    }
+    /// validate the blueprint at path and swap it in.
+    /// on failure the previous blueprint keeps serving.
+    /// if another reload is in flight, we back off rather than queue.
+    /// returns the number of Buckets in the new blueprint.
+    pub fn reload(&self, path: &str, readiness: &Readiness) -> Result<usize, ReloadError> {
+        let _guard = match self.reloading.try_lock() {
+            Ok(guard) => guard,
+            Err(TryLockError::Poisoned(poisoned)) => poisoned.into_inner(),
+            Err(TryLockError::WouldBlock) => return Err(ReloadError::Busy),
+        };
+        log::info!("{:<32}{:<32}", "reloading   blueprint", path);
+        readiness.begin_reload();
+        let result = Profile::validated(path).map(|profile| {
+            let size = profile.size();
+            *self.served.write().expect("live blueprint lock") = Arc::new(profile);
+            readiness.blueprint(true);
+            size
+        });
+        readiness.finish_reload();
+        if let Err(ref e) = result {
+            log::warn!("rejected blueprint reload: {}", e);
+        }
+        result.map_err(ReloadError::Rejected)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards::street::Street;
+    use crate::mccfr::edge::Edge;
+    use crate::mccfr::memory::Memory;
+    use crate::mccfr::shard::Shard;
+    use crate::Arbitrary;
+
+    #[test]
+    fn reload_swaps_or_keeps() {
+        let ref dir = std::env::temp_dir().join("robopoker-live");
+        std::fs::create_dir_all(dir).unwrap();
+        let ref good = dir.join("good").to_string_lossy().to_string();
+        let ref bad = dir.join("bad").to_string_lossy().to_string();
+        let mut records = (0..8)
+            .map(|_| (Bucket::random(), Edge::random(), Memory::from((1., 1.))))
+            .collect::<Vec<_>>();
+        records.sort_by_key(|(b, e, _)| (*b, *e));
+        records.dedup_by_key(|(b, e, _)| (*b, *e));
+        Shard::write(good, records.clone().into_iter());
+        std::fs::write(bad, b"PGCOPY\n\xFF\r\n\0 not a blueprint").unwrap();
+        let readiness = Readiness::default();
+        readiness.streets(Street::all().iter().copied());
+        let live = Live::default();
+        let before = live.current();
+        let ref served = records[0].0;
+        assert!(before.size() == 0);
+        assert!(live.decisions(served).is_empty());
+        let size = live.reload(good, &readiness).unwrap();
+        assert!(size > 0);
+        assert!(live.current().size() == size);
+        assert!(!live.decisions(served).is_empty());
+        assert!(before.size() == 0);
+        assert!(live.reload(bad, &readiness).is_err());
+        assert!(live.current().size() == size);
+        assert!(readiness.is_ready());
+        let held = live.reloading.lock().unwrap();
+        assert!(live.reload(good, &readiness) == Err(ReloadError::Busy));
+        drop(held);
+        assert!(live.reload(good, &readiness) == Ok(size));
+        std::fs::remove_dir_all(dir).ok();
+    }
+}
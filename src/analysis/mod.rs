@@ -1,6 +1,11 @@
 pub mod api;
 pub mod cli;
+pub mod live;
 pub mod query;
+pub mod readiness;
 pub mod request;
 pub mod response;
 pub mod server;
+pub mod service;
+
+pub use service::neighbors;
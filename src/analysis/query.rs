@@ -76,4 +76,13 @@ pub enum Query {
         #[arg(required = true)]
         target: String,
     },
+
+    #[command(
+        about = "Summarize the cluster sizes of a street's abstraction lookup",
+        alias = "sum"
+    )]
+    Summary {
+        #[arg(required = true)]
+        street: String,
+    },
 }
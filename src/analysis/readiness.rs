@@ -0,0 +1,71 @@
+use crate::cards::street::Street;
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::RwLock;
+
+/// which artifacts the server can currently answer queries from.
+/// shared between route handlers and whatever loads, or reloads,
+/// the abstraction and blueprint tables behind them.
+#[derive(Debug, Default)]
+pub struct Readiness {
+    streets: RwLock<BTreeSet<Street>>,
+    blueprint: AtomicBool,
+    reloading: AtomicUsize,
+}
+
+/// body of GET /ready
+#[derive(Debug, Serialize)]
+pub struct Report {
+    pub ready: bool,
+    pub streets: Vec<String>,
+    pub blueprint: bool,
+    pub reloading: bool,
+}
+
+impl Readiness {
+    /// record the streets whose abstractions are loaded
+    pub fn streets(&self, streets: impl IntoIterator<Item = Street>) {
+        self.streets
+            .write()
+            .expect("readiness lock")
+            .extend(streets);
+    }
+    /// record whether a blueprint is loaded
+    pub fn blueprint(&self, loaded: bool) {
+        self.blueprint.store(loaded, Ordering::Release);
+    }
+    /// queries stop being ready until every begun reload has finished
+    pub fn begin_reload(&self) {
+        self.reloading.fetch_add(1, Ordering::AcqRel);
+    }
+    pub fn finish_reload(&self) {
+        self.reloading.fetch_sub(1, Ordering::AcqRel);
+    }
+    fn is_reloading(&self) -> bool {
+        self.reloading.load(Ordering::Acquire) > 0
+    }
+    /// every street and the blueprint are loaded, and no swap is in flight
+    pub fn is_ready(&self) -> bool {
+        let streets = self.streets.read().expect("readiness lock");
+        Street::all().iter().all(|s| streets.contains(s))
+            && self.blueprint.load(Ordering::Acquire)
+            && !self.is_reloading()
+    }
+    pub fn report(&self) -> Report {
+        Report {
+            ready: self.is_ready(),
+            streets: self
+                .streets
+                .read()
+                .expect("readiness lock")
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+            blueprint: self.blueprint.load(Ordering::Acquire),
+            reloading: self.is_reloading(),
+        }
+    }
+}
@@ -13,10 +13,24 @@ pub struct Sample {
     pub distance: f32,
 }
 
+#[derive(Serialize)]
+pub struct Classified {
+    pub obs: String,
+    pub abs: String,
+    pub equity: f32,
+}
+
+#[derive(Serialize)]
+pub struct Neighbor {
+    pub obs: String,
+    pub abs: String,
+    pub distance: f32,
+}
+
 #[derive(Serialize)]
 pub struct Decision {
     pub edge: String,
-    pub prob: Probability,
+    pub prob: f32,
 }
 
 impl From<tokio_postgres::Row> for Sample {
@@ -31,11 +45,20 @@ impl From<tokio_postgres::Row> for Sample {
     }
 }
 
+impl From<(Edge, Probability)> for Decision {
+    fn from((edge, prob): (Edge, Probability)) -> Self {
+        Self {
+            edge: edge.to_string(),
+            prob: f32::from(prob),
+        }
+    }
+}
+
 impl From<tokio_postgres::Row> for Decision {
     fn from(row: tokio_postgres::Row) -> Self {
         Self {
             edge: Edge::from(row.get::<_, i64>("edge") as u64).to_string(),
-            prob: Probability::from(row.get::<_, f32>("policy")),
+            prob: row.get::<_, f32>("policy"),
         }
     }
 }
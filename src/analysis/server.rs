@@ -1,4 +1,7 @@
 use super::api::API;
+use super::live::Live;
+use super::live::ReloadError;
+use super::readiness::Readiness;
 use super::request::AbsHist;
 use super::request::GetPolicy;
 use super::request::ObsHist;
@@ -14,7 +17,9 @@ use crate::cards::street::Street;
 use crate::clustering::abstraction::Abstraction;
 use crate::gameplay::action::Action;
 use crate::gameplay::ply::Turn;
+use crate::mccfr::profile::Profile;
 use crate::mccfr::recall::Recall;
+use crate::save::upload::Table;
 use actix_cors::Cors;
 use actix_web::middleware::Logger;
 use actix_web::web;
@@ -28,6 +33,19 @@ pub struct Server;
 impl Server {
     pub async fn run() -> Result<(), std::io::Error> {
         let api = web::Data::new(API::from(crate::db().await));
+        let readiness = web::Data::new(Readiness::default());
+        let live = web::Data::new(Live::default());
+        match api.available().await {
+            Ok((streets, _)) => readiness.streets(streets),
+            Err(e) => log::warn!("artifacts unavailable: {}", e),
+        }
+        match Profile::done(Street::Pref) {
+            true => live
+                .reload(&Profile::path(Street::Pref), &readiness)
+                .map(|_| ())
+                .unwrap_or_else(|e| log::warn!("serving without a blueprint: {}", e)),
+            false => log::warn!("serving without a blueprint"),
+        }
         log::info!("starting HTTP server");
         HttpServer::new(move || {
             App::new()
@@ -39,6 +57,11 @@ impl Server {
                         .allow_any_header(),
                 )
                 .app_data(api.clone())
+                .app_data(readiness.clone())
+                .app_data(live.clone())
+                .route("/health", web::get().to(health))
+                .route("/ready", web::get().to(ready))
+                .route("/reload", web::post().to(reload))
                 .route("/replace-obs", web::post().to(replace_obs))
                 .route("/nbr-any-abs", web::post().to(nbr_any_wrt_abs))
                 .route("/nbr-obs-abs", web::post().to(nbr_obs_wrt_abs))
@@ -62,6 +85,28 @@ impl Server {
 
 // Route handlers
 
+async fn health() -> impl Responder {
+    HttpResponse::Ok().body("ok")
+}
+
+async fn ready(readiness: web::Data<Readiness>) -> impl Responder {
+    let report = readiness.report();
+    match report.ready {
+        true => HttpResponse::Ok().json(report),
+        false => HttpResponse::ServiceUnavailable().json(report),
+    }
+}
+
+async fn reload(live: web::Data<Live>, readiness: web::Data<Readiness>) -> impl Responder {
+    let path = Profile::path(Street::Pref);
+    match web::block(move || live.reload(&path, &readiness)).await {
+        Err(e) => HttpResponse::InternalServerError().body(e.to_string()),
+        Ok(Err(ReloadError::Busy)) => HttpResponse::Conflict().body(ReloadError::Busy.to_string()),
+        Ok(Err(e)) => HttpResponse::UnprocessableEntity().body(e.to_string()),
+        Ok(Ok(size)) => HttpResponse::Ok().body(format!("reloaded {} buckets", size)),
+    }
+}
+
 async fn replace_obs(api: web::Data<API>, req: web::Json<ReplaceObs>) -> impl Responder {
     match Observation::try_from(req.obs.as_str()) {
         Err(_) => HttpResponse::BadRequest().body("invalid observation format"),
@@ -196,7 +241,11 @@ async fn hst_wrt_obs(api: web::Data<API>, req: web::Json<ObsHist>) -> impl Respo
     }
 }
 
-async fn lookup_policy(api: web::Data<API>, req: web::Json<GetPolicy>) -> impl Responder {
+async fn lookup_policy(
+    api: web::Data<API>,
+    live: web::Data<Live>,
+    req: web::Json<GetPolicy>,
+) -> impl Responder {
     let hero = Turn::try_from(req.hero.as_str());
     let seen = Observation::try_from(req.seen.as_str());
     let path = req
@@ -206,11 +255,51 @@ async fn lookup_policy(api: web::Data<API>, req: web::Json<GetPolicy>) -> impl R
         .collect::<Result<Vec<_>, _>>();
     match (hero, seen, path) {
         (Ok(hero), Ok(seen), Ok(path)) => {
-            match api.policy(Recall::from((hero, seen, path))).await {
+            match api.bucket(&Recall::from((hero, seen, path))).await {
                 Err(e) => HttpResponse::InternalServerError().body(e.to_string()),
-                Ok(rows) => HttpResponse::Ok().json(rows),
+                Ok(bucket) => HttpResponse::Ok().json(live.decisions(&bucket)),
             }
         }
         _ => HttpResponse::BadRequest().body("invalid recall format"),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::http::StatusCode;
+    use actix_web::test;
+
+    #[actix_web::test]
+    async fn ready_after_load() {
+        let readiness = web::Data::new(Readiness::default());
+        let app = test::init_service(
+            App::new()
+                .app_data(readiness.clone())
+                .route("/health", web::get().to(health))
+                .route("/ready", web::get().to(ready)),
+        )
+        .await;
+        let get = |uri: &str| test::TestRequest::get().uri(uri).to_request();
+        let health = test::call_service(&app, get("/health")).await;
+        assert!(health.status() == StatusCode::OK);
+        let before = test::call_service(&app, get("/ready")).await;
+        assert!(before.status() == StatusCode::SERVICE_UNAVAILABLE);
+        readiness.streets(Street::all().iter().copied());
+        readiness.blueprint(true);
+        let after = test::call_service(&app, get("/ready")).await;
+        assert!(after.status() == StatusCode::OK);
+        readiness.begin_reload();
+        let during = test::call_service(&app, get("/ready")).await;
+        assert!(during.status() == StatusCode::SERVICE_UNAVAILABLE);
+        readiness.begin_reload();
+        readiness.finish_reload();
+        let overlapping = test::call_service(&app, get("/ready")).await;
+        assert!(overlapping.status() == StatusCode::SERVICE_UNAVAILABLE);
+        readiness.finish_reload();
+        let report = test::call_and_read_body(&app, get("/ready")).await;
+        let report = String::from_utf8(report.to_vec()).unwrap();
+        assert!(report.contains(r#""ready":true"#));
+        assert!(report.contains(r#""streets":["preflop","flop","turn","river"]"#));
+    }
+}
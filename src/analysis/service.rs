@@ -0,0 +1,132 @@
+use super::request::ReplaceAbs;
+use super::request::ReplaceObs;
+use super::request::ReplaceRow;
+use super::request::SetStreets;
+use super::response::Classified;
+use super::response::Neighbor;
+use crate::cards::observation::Observation;
+use crate::cards::street::Street;
+use crate::clustering::abstraction::Abstraction;
+use crate::clustering::abstractor::Abstractor;
+use crate::clustering::equity::Equity;
+use crate::clustering::metric::Metric;
+use crate::save::upload::LoadError;
+use crate::Energy;
+
+// answers to the analysis requests from the learned artifacts
+// on disk, rather than from the database behind API. each takes
+// the parsed request body and returns a response to serialize,
+// or why the request could not be answered.
+
+/// a random Observation on the requested Street
+pub fn set_streets(abstractor: &Abstractor, req: SetStreets) -> Result<Classified, String> {
+    let street = Street::try_from(req.street.as_str()).map_err(|e| e.to_string())?;
+    classified(abstractor, Observation::from(street))
+}
+
+/// the Abstraction of the requested Observation
+pub fn replace_obs(abstractor: &Abstractor, req: ReplaceObs) -> Result<Classified, String> {
+    let obs = Observation::try_from(req.obs.as_str())?;
+    classified(abstractor, obs)
+}
+
+/// an Observation that belongs to the requested Abstraction
+pub fn replace_abs(abstractor: &Abstractor, req: ReplaceAbs) -> Result<Classified, String> {
+    let abs = Abstraction::try_from(req.wrt.as_str()).map_err(|e| e.to_string())?;
+    classified(abstractor, abstractor.member(&abs)?)
+}
+
+/// the requested Observation, and how far its
+/// Abstraction lies from the one it is compared to
+pub fn replace_row(abstractor: &Abstractor, req: ReplaceRow) -> Result<Neighbor, String> {
+    let wrt = Abstraction::try_from(req.wrt.as_str()).map_err(|e| e.to_string())?;
+    let obs = Observation::try_from(req.obs.as_str())?;
+    let abs = abstractor.classify(&obs)?;
+    Ok(Neighbor {
+        obs: obs.to_string(),
+        abs: abs.to_string(),
+        distance: abstractor.between(&wrt, &abs)?,
+    })
+}
+
+/// the k Abstractions nearest to abs by its Street's
+/// Metric on disk, nearest first, and leaving abs out
+pub fn neighbors(abs: Abstraction, k: usize) -> Result<Vec<(Abstraction, Energy)>, LoadError> {
+    use crate::save::upload::Table;
+    Ok(Metric::load(abs.street())?.neighbors(&abs, k))
+}
+
+fn classified(abstractor: &Abstractor, obs: Observation) -> Result<Classified, String> {
+    Ok(Classified {
+        obs: obs.to_string(),
+        abs: abstractor.classify(&obs)?.to_string(),
+        equity: f32::from(Equity::of(&obs)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards::isomorphism::Isomorphism;
+    use crate::clustering::lookup::Lookup;
+    use crate::clustering::pair::Pair;
+    use std::collections::BTreeMap;
+
+    const FLOPS: [&str; 2] = ["AsAh~Kd7c2h", "7c2d~Kd8s3h"];
+
+    /// two Flop clusters, each holding one of FLOPS
+    fn abstractor() -> Abstractor {
+        let a = |i: usize| Abstraction::from((Street::Flop, i));
+        let lookup = Lookup::from(
+            FLOPS
+                .iter()
+                .map(|s| Observation::try_from(*s).unwrap())
+                .enumerate()
+                .map(|(i, o)| (Isomorphism::from(o), a(i)))
+                .collect::<BTreeMap<Isomorphism, Abstraction>>(),
+        );
+        let metric = Metric::from((
+            Street::Flop,
+            BTreeMap::from([(Pair::from((&a(0), &a(1))), 1.0)]),
+        ));
+        Abstractor::default().with_street(Street::Flop, lookup, metric)
+    }
+
+    #[test]
+    fn replace_obs_classifies_the_observation() {
+        let ref abstractor = abstractor();
+        let req = serde_json::from_str::<ReplaceObs>(r#"{"obs":"AsAh~Kd7c2h"}"#).unwrap();
+        let res = replace_obs(abstractor, req).unwrap();
+        assert!(res.abs == Abstraction::from((Street::Flop, 0)).to_string());
+        assert!(res.equity > 0.8);
+        let json = serde_json::to_string(&res).unwrap();
+        assert!(json.contains(&format!(r#""abs":"{}""#, res.abs)));
+    }
+
+    #[test]
+    fn replace_row_measures_from_wrt() {
+        let ref abstractor = abstractor();
+        let wrt = Abstraction::from((Street::Flop, 1));
+        let body = format!(r#"{{"wrt":"{}","obs":"{}"}}"#, wrt, FLOPS[0]);
+        let req = serde_json::from_str::<ReplaceRow>(&body).unwrap();
+        let res = replace_row(abstractor, req).unwrap();
+        assert!(res.distance == 1.0);
+        let body = format!(r#"{{"wrt":"{}","obs":"{}"}}"#, wrt, FLOPS[1]);
+        let req = serde_json::from_str::<ReplaceRow>(&body).unwrap();
+        assert!(replace_row(abstractor, req).unwrap().distance == 0.);
+    }
+
+    #[test]
+    fn replace_abs_finds_a_member() {
+        let ref abstractor = abstractor();
+        let wrt = Abstraction::from((Street::Flop, 1));
+        let body = format!(r#"{{"wrt":"{}"}}"#, wrt);
+        let req = serde_json::from_str::<ReplaceAbs>(&body).unwrap();
+        let res = replace_abs(abstractor, req).unwrap();
+        assert!(res.abs == wrt.to_string());
+        let req = serde_json::from_str::<SetStreets>(r#"{"street":"turn"}"#).unwrap();
+        assert!(set_streets(abstractor, req).is_err());
+        let req = serde_json::from_str::<ReplaceObs>(r#"{"obs":"nonsense"}"#).unwrap();
+        assert!(replace_obs(abstractor, req).is_err());
+    }
+}
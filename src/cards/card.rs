@@ -7,6 +7,10 @@ use super::suit::Suit;
 pub struct Card(u8);
 
 impl Card {
+    /// every Card in sorted deck order, 2c through As
+    pub fn all() -> [Card; 52] {
+        std::array::from_fn(|i| Card::from(i as u8))
+    }
     pub fn rank(&self) -> Rank {
         Rank::from(self.0 / 4)
     }
@@ -102,6 +106,15 @@ mod tests {
         assert!(card == Card::from((rank, suit)));
     }
 
+    #[test]
+    fn all_ranks_and_suits() {
+        let cards = Rank::all()
+            .into_iter()
+            .flat_map(|r| Suit::all().map(|s| Card::from((r, s))))
+            .collect::<Vec<Card>>();
+        assert!(cards == Card::all());
+    }
+
     #[test]
     fn bijective_u8() {
         let card = Deck::new().draw();
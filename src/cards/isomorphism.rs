@@ -21,9 +21,27 @@ use crate::Arbitrary;
 /// see [`crate::cards::street::Street::n_isomorphisms`] for a sense of how much.
 /// but it's approx 4 (* 5) times smaller, as youd expect for without-replacement
 /// sampling on the last two Streets.
-#[derive(Copy, Clone, Hash, Eq, PartialEq, Debug, PartialOrd, Ord)]
+///
+/// Isomorphisms are ordered by their i64 encoding, board cards first
+/// and then pocket cards, one byte each. that encoding is what gets
+/// written to disk and to Postgres, so pinning the order to it keeps
+/// serialized Lookups byte-identical across versions, whatever the
+/// field layout of Observation happens to be.
+#[derive(Copy, Clone, Hash, Eq, PartialEq, Debug)]
 pub struct Isomorphism(pub Observation);
 
+impl Ord for Isomorphism {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        i64::from(*self).cmp(&i64::from(*other))
+    }
+}
+
+impl PartialOrd for Isomorphism {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 impl From<Observation> for Isomorphism {
     fn from(ref observation: Observation) -> Self {
         let isomorphism = Permutation::from(observation);
@@ -214,4 +232,24 @@ mod tests {
         )));
         assert!(a == b);
     }
+
+    #[test]
+    fn order_follows_encoding() {
+        for _ in 0..256 {
+            let a = Isomorphism::random();
+            let b = Isomorphism::random();
+            assert!(a.cmp(&b) == i64::from(a).cmp(&i64::from(b)));
+        }
+        // board cards outrank pocket cards in the encoding
+        let a = Isomorphism::from(Observation::from((
+            Hand::try_from("As Ah").unwrap(),
+            Hand::try_from("2c 2d 3c").unwrap(),
+        )));
+        let b = Isomorphism::from(Observation::from((
+            Hand::try_from("2c 2d").unwrap(),
+            Hand::try_from("2h 2s 3c").unwrap(),
+        )));
+        assert!(i64::from(a) < i64::from(b));
+        assert!(a < b);
+    }
 }
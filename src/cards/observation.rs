@@ -22,6 +22,20 @@ pub struct Observation {
 }
 
 impl Observation {
+    /// build from typed cards, rejecting duplicates
+    /// and boards that are not preflop, flop, turn, or river
+    pub fn new(hole: [Card; 2], board: &[Card]) -> Result<Self, String> {
+        let pocket = Hand::from(hole.to_vec());
+        let public = Hand::from(board.to_vec());
+        let cards = Hand::from(hole.iter().chain(board).copied().collect::<Vec<Card>>());
+        if cards.size() != hole.len() + board.len() {
+            return Err(format!("duplicate cards: {} {}", pocket, public));
+        }
+        match board.len() {
+            0 | 3 | 4 | 5 => Ok(Self::from((pocket, public))),
+            n => Err(format!("invalid board size: {}", n)),
+        }
+    }
     pub fn children<'a>(&'a self) -> impl Iterator<Item = Self> + 'a {
         let n = self.street().n_revealed();
         let removed = Hand::from(*self);
@@ -48,8 +62,11 @@ impl Observation {
             _ => won as Equity / sum as Equity,
         }
     }
+    /// equity on any street, as Equity::of estimates it from
+    /// EQUITY_SAMPLES showdowns. seeded by the Observation
+    /// itself, so repeated estimates agree
     pub fn estimate(&self) -> Equity {
-        todo!()
+        f32::from(crate::clustering::equity::Equity::of(self))
     }
     pub fn street(&self) -> Street {
         Street::from(self.public.size())
@@ -83,6 +100,23 @@ impl Observation {
             .collect::<Vec<String>>()
             .join(Self::SEPARATOR)
     }
+    /// every card in a whitespace separated list of
+    /// tokens, each of one or more two character cards
+    fn cards(s: &str) -> Result<Vec<Card>, String> {
+        s.split_whitespace()
+            .flat_map(|token| {
+                token
+                    .chars()
+                    .collect::<Vec<char>>()
+                    .chunks(2)
+                    .map(|chunk| chunk.iter().collect::<String>())
+                    .collect::<Vec<String>>()
+            })
+            .map(|card| {
+                Card::try_from(card.as_str()).map_err(|e| format!("bad card {}: {}", card, e))
+            })
+            .collect()
+    }
 
     const SEPARATOR: &'static str = "~";
 }
@@ -159,19 +193,26 @@ impl From<Observation> for Hand {
     }
 }
 
+/// hole cards, then the board after a "/" or "~", as in
+/// "As Kh / 2c 3d 4s" or "AsKh~2c3d4s". cards may be spaced
+/// or run together, and the board may be left off preflop.
+impl std::str::FromStr for Observation {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (hole, board) = s.split_once(['/', '~']).unwrap_or((s, ""));
+        let hole = Self::cards(hole)?;
+        let board = Self::cards(board)?;
+        match <[Card; 2]>::try_from(hole.as_slice()) {
+            Ok(hole) => Self::new(hole, &board),
+            Err(_) => Err(format!("expected 2 hole cards, found {}", hole.len())),
+        }
+    }
+}
+
 impl TryFrom<&str> for Observation {
     type Error = String;
     fn try_from(s: &str) -> Result<Self, Self::Error> {
-        let (pocket, public) = s
-            .trim()
-            .split_once(Self::SEPARATOR)
-            .unwrap_or((s.trim(), ""));
-        let pocket = Hand::try_from(pocket)?;
-        let public = Hand::try_from(public)?;
-        match (pocket.size(), public.size()) {
-            (2, 0) | (2, 3) | (2, 4) | (2, 5) => Ok(Self::from((pocket, public))),
-            _ => Err(format!("invalid card counts: {} {}", pocket, public)),
-        }
+        s.parse()
     }
 }
 
@@ -205,4 +246,60 @@ mod tests {
         let swappy = Observation::try_from(random.equivalent().as_str()).unwrap();
         assert!(Isomorphism::from(random) == Isomorphism::from(swappy));
     }
+
+    #[test]
+    fn typed_cards_match_parsed() {
+        use crate::cards::rank::Rank;
+        use crate::cards::suit::Suit;
+        let hole = [
+            Card::from((Rank::Ace, Suit::S)),
+            Card::from((Rank::King, Suit::H)),
+        ];
+        let board = [
+            Card::from((Rank::Ten, Suit::D)),
+            Card::from((Rank::Two, Suit::C)),
+            Card::from((Rank::Seven, Suit::S)),
+        ];
+        let typed = Observation::new(hole, &board).unwrap();
+        let parsed = Observation::try_from("AsKh~Td2c7s").unwrap();
+        assert!(typed == parsed);
+        assert!(Observation::new(hole, &[]).unwrap().street() == Street::Pref);
+        assert!(Observation::new(hole, &board[..2]).is_err());
+        assert!(Observation::new(hole, &[board[0], hole[0], board[1]]).is_err());
+        assert!(Observation::new([hole[0], hole[0]], &board).is_err());
+    }
+
+    #[test]
+    fn parses_standard_notation() {
+        let pref = "As Kh".parse::<Observation>().unwrap();
+        let flop = "As Kh / 2c 3d 4s".parse::<Observation>().unwrap();
+        let turn = "AsKh/2c3d4s 5h".parse::<Observation>().unwrap();
+        let rive = " As Kh / 2c 3d 4s 5h 6h ".parse::<Observation>().unwrap();
+        assert!(pref.street() == Street::Pref);
+        assert!(flop.street() == Street::Flop);
+        assert!(turn.street() == Street::Turn);
+        assert!(rive.street() == Street::Rive);
+        assert!(flop == Observation::try_from("AsKh~2c3d4s").unwrap());
+        assert!(rive == rive.to_string().parse().unwrap());
+    }
+
+    #[test]
+    fn rejects_malformed_notation() {
+        let err = |s: &str| s.parse::<Observation>().unwrap_err();
+        assert!(err("As As / 2c 3d 4s").contains("duplicate"));
+        assert!(err("As Kh / 2c 3d As").contains("duplicate"));
+        assert!(err("As Kh / 2c 3d").contains("board size"));
+        assert!(err("As / 2c 3d 4s").contains("hole cards"));
+        assert!(err("As Kh / 2c 3d Xx").contains("Xx"));
+        assert!(err("As Kh / 2c 3d 4").contains("4"));
+    }
+
+    #[test]
+    fn estimate_is_repeatable() {
+        let flop = Observation::from(Street::Flop);
+        let rive = Observation::from(Street::Rive);
+        assert!(flop.estimate() == flop.estimate());
+        assert!((0. ..=1.).contains(&flop.estimate()));
+        assert!(rive.estimate() == rive.equity());
+    }
 }
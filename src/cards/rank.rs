@@ -17,6 +17,23 @@ pub enum Rank {
 }
 
 impl Rank {
+    pub const fn all() -> [Rank; 13] {
+        [
+            Rank::Two,
+            Rank::Three,
+            Rank::Four,
+            Rank::Five,
+            Rank::Six,
+            Rank::Seven,
+            Rank::Eight,
+            Rank::Nine,
+            Rank::Ten,
+            Rank::Jack,
+            Rank::Queen,
+            Rank::King,
+            Rank::Ace,
+        ]
+    }
     const fn mask() -> u16 {
         0b1111111111111
     }
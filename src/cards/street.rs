@@ -1,6 +1,8 @@
 use crate::clustering::abstraction::Abstraction;
+use serde::Deserialize;
+use serde::Serialize;
 
-#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum Street {
     Pref = 0isize,
     Flop = 1isize,
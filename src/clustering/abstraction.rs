@@ -1,7 +1,7 @@
 use crate::cards::street::Street;
 use crate::transport::support::Support;
 use crate::Arbitrary;
-use crate::Probability;
+use crate::Equity;
 use std::hash::Hash;
 use std::u64;
 
@@ -68,11 +68,11 @@ impl Abstraction {
         let bits = M & bits;
         bits as usize
     }
-    fn quantize(p: Probability) -> usize {
-        (p * Self::N as Probability).round() as usize
+    fn quantize(p: Equity) -> usize {
+        (p * Self::N as Equity).round() as usize
     }
-    fn floatize(q: usize) -> Probability {
-        q as Probability / Self::N as Probability
+    fn floatize(q: usize) -> Equity {
+        q as Equity / Self::N as Equity
     }
 }
 
@@ -96,14 +96,14 @@ impl From<(Street, usize)> for Abstraction {
 /// for river, we use a u8 to represent the equity bucket,
 /// i.e. Equity(0) is the 0% equity bucket,
 /// and Equity(N) is the 100% equity bucket.
-impl From<Probability> for Abstraction {
-    fn from(p: Probability) -> Self {
+impl From<Equity> for Abstraction {
+    fn from(p: Equity) -> Self {
         assert!(p >= 0.);
         assert!(p <= 1.);
         Self::from((Street::Rive, Self::quantize(p)))
     }
 }
-impl From<Abstraction> for Probability {
+impl From<Abstraction> for Equity {
     fn from(abstraction: Abstraction) -> Self {
         match abstraction {
             Abstraction::Percent(_) => Abstraction::floatize(abstraction.index()),
@@ -198,10 +198,10 @@ mod tests {
 
     #[test]
     fn is_quantize_inverse_floatize() {
-        for p in (0..=100).map(|x| x as Probability / 100.) {
+        for p in (0..=100).map(|x| x as Equity / 100.) {
             let q = Abstraction::quantize(p);
             let f = Abstraction::floatize(q);
-            assert!((p - f).abs() < 1. / Abstraction::N as Probability);
+            assert!((p - f).abs() < 1. / Abstraction::N as Equity);
         }
     }
     #[test]
@@ -0,0 +1,176 @@
+use super::abstraction::Abstraction;
+use super::lookup::Lookup;
+use super::metric::Metric;
+use crate::cards::isomorphism::Isomorphism;
+use crate::cards::observation::Observation;
+use crate::cards::street::Street;
+use crate::transport::measure::Measure;
+use crate::Energy;
+use std::collections::BTreeMap;
+
+/// the learned Lookup and Metric of every loaded Street, so that
+/// analysis can ask how alike two Observations are without
+/// composing the classification and the distance by hand.
+#[derive(Default)]
+pub struct Abstractor {
+    lookups: BTreeMap<Street, Lookup>,
+    metrics: BTreeMap<Street, Metric>,
+}
+
+impl Abstractor {
+    /// abstract this Street with these learned artifacts.
+    /// the River Metric is Metric::default()
+    pub fn with_street(mut self, street: Street, lookup: Lookup, metric: Metric) -> Self {
+        self.lookups.insert(street, lookup);
+        self.metrics.insert(street, metric);
+        self
+    }
+    /// the learned Abstraction of an Observation
+    pub fn classify(&self, obs: &Observation) -> Result<Abstraction, String> {
+        self.lookups
+            .get(&obs.street())
+            .ok_or_else(|| format!("no lookup loaded for {}", obs.street()))?
+            .get(obs)
+            .ok_or_else(|| format!("no abstraction for {}", obs))
+    }
+    /// learned distance between the Abstractions of two Observations
+    /// of the same Street. Observations that share an Abstraction are
+    /// told apart by the finer EMD between their own histograms over
+    /// the next Street, when that Street is loaded. preflop has no
+    /// learned distance, so it always takes the finer route.
+    pub fn distance(&self, a: &Observation, b: &Observation) -> Result<Energy, String> {
+        if a.street() != b.street() {
+            return Err(format!(
+                "no distance between {} and {} observations",
+                a.street(),
+                b.street()
+            ));
+        }
+        let x = self.classify(a)?;
+        let y = self.classify(b)?;
+        match (x == y, a.street()) {
+            (true, _) | (_, Street::Pref) => self.finer(a, b),
+            (false, _) => self.between(&x, &y),
+        }
+    }
+    /// learned distance between two Abstractions of the same Street.
+    /// an Abstraction is no distance from itself
+    pub fn between(&self, x: &Abstraction, y: &Abstraction) -> Result<Energy, String> {
+        if x.street() != y.street() {
+            return Err(format!("no distance between {} and {}", x, y));
+        }
+        match x == y {
+            true => Ok(0.),
+            false => self
+                .metrics
+                .get(&x.street())
+                .map(|metric| metric.distance(x, y))
+                .ok_or_else(|| format!("no metric loaded for {}", x.street())),
+        }
+    }
+    /// some Observation that the learned Lookup assigns to an Abstraction
+    pub fn member(&self, abs: &Abstraction) -> Result<Observation, String> {
+        self.lookups
+            .get(&abs.street())
+            .ok_or_else(|| format!("no lookup loaded for {}", abs.street()))?
+            .members_of(abs)
+            .next()
+            .map(|iso| iso.0)
+            .ok_or_else(|| format!("no observation abstracts to {}", abs))
+    }
+    /// EMD between the next-Street histograms of two Observations,
+    /// or exact equity difference on the River. zero if the next
+    /// Street is not loaded, since then nothing finer is known.
+    fn finer(&self, a: &Observation, b: &Observation) -> Result<Energy, String> {
+        if a.street() == Street::Rive {
+            return Ok((a.equity() - b.equity()).abs());
+        }
+        let next = a.street().next();
+        match (self.lookups.get(&next), self.metrics.get(&next)) {
+            (Some(lookup), Some(metric)) => {
+                let ref x = lookup.future(&Isomorphism::from(*a));
+                let ref y = lookup.future(&Isomorphism::from(*b));
+                Ok(metric.emd(x, y))
+            }
+            _ => Ok(0.),
+        }
+    }
+}
+
+#[cfg(feature = "native")]
+impl Abstractor {
+    /// every Street whose Lookup and Metric are both in dir,
+    /// or the first of them that can't be read
+    pub fn load_from(dir: &std::path::Path) -> Result<Self, crate::save::upload::LoadError> {
+        use crate::save::upload::Table;
+        Street::all()
+            .iter()
+            .copied()
+            .filter(|&s| Lookup::done_in(dir, s) && Metric::done_in(dir, s))
+            .try_fold(Self::default(), |abstractor, s| {
+                Ok(abstractor.with_street(
+                    s,
+                    Lookup::load_from(dir, s)?,
+                    Metric::load_from(dir, s)?,
+                ))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clustering::pair::Pair;
+
+    /// a toy Flop abstraction of three clusters, where
+    /// the first two are close and the third is far away
+    fn abstractor(flops: &[Observation]) -> Abstractor {
+        let street = Street::Flop;
+        let a = |i: usize| Abstraction::from((street, i));
+        let lookup = Lookup::from(
+            flops
+                .iter()
+                .enumerate()
+                .map(|(i, o)| (Isomorphism::from(*o), a(i % 3)))
+                .collect::<BTreeMap<Isomorphism, Abstraction>>(),
+        );
+        let metric = Metric::from((
+            street,
+            BTreeMap::from([
+                (Pair::from((&a(0), &a(1))), 0.1),
+                (Pair::from((&a(0), &a(2))), 1.0),
+                (Pair::from((&a(1), &a(2))), 0.9),
+            ]),
+        ));
+        Abstractor::default().with_street(street, lookup, metric)
+    }
+
+    #[test]
+    fn similar_flops_are_near() {
+        let flops = (0..16)
+            .map(|_| Observation::from(Street::Flop))
+            .map(Isomorphism::from)
+            .collect::<std::collections::BTreeSet<Isomorphism>>()
+            .into_iter()
+            .take(3)
+            .map(|iso| iso.0)
+            .collect::<Vec<Observation>>();
+        let abstractor = abstractor(&flops);
+        let near = abstractor.distance(&flops[0], &flops[1]).unwrap();
+        let far = abstractor.distance(&flops[0], &flops[2]).unwrap();
+        assert!(near < far);
+        assert!(near == 0.1);
+        assert!(far == 1.0);
+        assert!(abstractor.distance(&flops[2], &flops[0]) == Ok(far));
+        assert!(abstractor.distance(&flops[0], &flops[0]) == Ok(0.));
+    }
+
+    #[test]
+    fn streets_must_match() {
+        let flop = Observation::from(Street::Flop);
+        let turn = Observation::from(Street::Turn);
+        let abstractor = abstractor(&[flop]);
+        assert!(abstractor.distance(&flop, &turn).is_err());
+        assert!(abstractor.distance(&turn, &turn).is_err());
+    }
+}
@@ -0,0 +1,158 @@
+use super::histogram::Histogram;
+use crate::Energy;
+use std::collections::BTreeMap;
+use std::hash::Hash;
+use std::hash::Hasher;
+
+/// memoized EMD between pairs of centroid Histograms.
+/// keys are content hashes, so any change to either
+/// centroid produces a miss rather than a stale distance.
+///
+/// keys use a fixed hash, so the cache can be written to
+/// disk and read back by a later build of the same street.
+#[derive(Default)]
+pub struct Cache {
+    energies: BTreeMap<(u64, u64), Energy>,
+    hits: usize,
+}
+
+impl Cache {
+    /// return the cached distance or compute and remember it
+    pub fn energy<F>(&mut self, x: &Histogram, y: &Histogram, emd: F) -> Energy
+    where
+        F: FnOnce() -> Energy,
+    {
+        match self.get(x, y) {
+            Some(energy) => energy,
+            None => {
+                let energy = emd();
+                self.insert(x, y, energy);
+                energy
+            }
+        }
+    }
+    /// the memoized distance, if any, counted as a hit
+    pub fn get(&mut self, x: &Histogram, y: &Histogram) -> Option<Energy> {
+        let energy = self.energies.get(&(Self::hash(x), Self::hash(y))).copied();
+        self.hits += energy.is_some() as usize;
+        energy
+    }
+    /// remember a distance computed elsewhere
+    pub fn insert(&mut self, x: &Histogram, y: &Histogram, energy: Energy) {
+        self.energies.insert((Self::hash(x), Self::hash(y)), energy);
+    }
+    /// number of lookups served without recomputation
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+    /// number of distinct pairs memoized
+    pub fn len(&self) -> usize {
+        self.energies.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.energies.is_empty()
+    }
+    fn hash(h: &Histogram) -> u64 {
+        let ref mut hasher = Digest::default();
+        h.hash(hasher);
+        hasher.finish()
+    }
+}
+
+#[cfg(feature = "native")]
+impl Cache {
+    /// where a street's cache lives, next to its Metric
+    pub fn path(dir: &std::path::Path, street: crate::cards::street::Street) -> String {
+        format!("{}/cache.{}", dir.to_string_lossy(), street)
+    }
+    /// read a cache written by save, or start empty
+    pub fn load(path: &str) -> Self {
+        use byteorder::ReadBytesExt;
+        use byteorder::BE;
+        use std::io::BufReader;
+        let Ok(file) = std::fs::File::open(path) else {
+            return Self::default();
+        };
+        log::info!("{:<32}{:<32}", "loading     emd cache", path);
+        let ref mut reader = BufReader::new(file);
+        let mut energies = BTreeMap::new();
+        while let Ok(x) = reader.read_u64::<BE>() {
+            let y = reader.read_u64::<BE>().expect("cache row");
+            let energy = reader.read_f32::<BE>().expect("cache row");
+            energies.insert((x, y), energy);
+        }
+        Self { energies, hits: 0 }
+    }
+    /// write every memoized pair. the file is written beside path
+    /// and renamed over it, so an interrupted save keeps the last one
+    pub fn save(&self, path: &str) {
+        use byteorder::WriteBytesExt;
+        use byteorder::BE;
+        use std::io::BufWriter;
+        use std::io::Write;
+        log::info!("{:<32}{:<32}", "saving      emd cache", path);
+        let ref temp = format!("{}.tmp", path);
+        let file = std::fs::File::create(temp).expect(&format!("touch {}", temp));
+        let ref mut writer = BufWriter::new(file);
+        for (&(x, y), &energy) in self.energies.iter() {
+            writer.write_u64::<BE>(x).expect("write cache");
+            writer.write_u64::<BE>(y).expect("write cache");
+            writer.write_f32::<BE>(energy).expect("write cache");
+        }
+        writer.flush().expect("flush cache");
+        std::fs::rename(temp, path).expect("rename cache");
+    }
+}
+
+/// 64-bit FNV-1a. unlike DefaultHasher, its output is fixed
+/// across Rust releases, so it is safe to persist.
+pub struct Digest(u64);
+
+impl Default for Digest {
+    fn default() -> Self {
+        Self(0xcbf29ce484222325)
+    }
+}
+
+impl Hasher for Digest {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x100000001b3);
+        }
+    }
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+#[cfg(feature = "native")]
+impl Digest {
+    /// content hash of a file, if it can be read
+    pub fn file(path: &str) -> Option<u64> {
+        use std::io::Read;
+        let file = std::fs::File::open(path).ok()?;
+        let mut reader = std::io::BufReader::new(file);
+        let ref mut hasher = Self::default();
+        let ref mut buffer = [0u8; 1 << 16];
+        loop {
+            match reader.read(buffer).ok()? {
+                0 => break,
+                n => hasher.write(&buffer[..n]),
+            }
+        }
+        Some(hasher.finish())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn digest_is_fixed() {
+        let ref mut hasher = Digest::default();
+        hasher.write(b"a");
+        assert!(hasher.finish() == 0xaf63dc4c8601ec8c);
+    }
+}
@@ -0,0 +1,154 @@
+use super::sinkhorn::SinkhornConfig;
+use crate::cards::isomorphism::Isomorphism;
+use crate::cards::isomorphisms::IsomorphismIterator;
+use crate::cards::street::Street;
+use crate::Energy;
+use serde::Deserialize;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+
+/// per-street kmeans hyperparameters.
+/// anything left unset falls back to Street::k() and Street::t(),
+/// and an unset seed falls back to hashing the Street alone.
+/// River equity is exact unless a number of samples is set, and
+/// the Turn clusters on equity distribution alone unless a
+/// potential weight is set. Sinkhorn runs with its defaults
+/// unless a SinkhornConfig is set. every isomorphism is built
+/// unless a limit is set.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClusterConfig {
+    k: BTreeMap<Street, usize>,
+    t: BTreeMap<Street, usize>,
+    seed: Option<u64>,
+    samples: Option<usize>,
+    potential: Option<Energy>,
+    sinkhorn: Option<SinkhornConfig>,
+    limit: Option<usize>,
+}
+
+impl ClusterConfig {
+    /// number of kmeans centroids
+    pub fn k(&self, street: Street) -> usize {
+        self.k.get(&street).copied().unwrap_or(street.k())
+    }
+    /// number of kmeans iterations
+    pub fn t(&self, street: Street) -> usize {
+        self.t.get(&street).copied().unwrap_or(street.t())
+    }
+    /// optional seed mixed into the kmeans++ initialization
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+    /// villain holdings sampled per River equity,
+    /// or None to enumerate all of them exactly
+    pub fn samples(&self) -> Option<usize> {
+        self.samples
+    }
+    /// share of Turn distances that comes from equity potential,
+    /// or None to cluster the Turn on equity distribution alone.
+    /// cached centroid distances are not keyed by this weight,
+    /// so changing it wants a fresh output directory.
+    pub fn potential(&self) -> Option<Energy> {
+        self.potential
+    }
+    /// hyperparameters of the Sinkhorn EMD between Learned histograms.
+    /// like the potential weight, these are not part of any cache key.
+    pub fn sinkhorn(&self) -> SinkhornConfig {
+        self.sinkhorn.unwrap_or_default()
+    }
+    /// distinct children kept per isomorphism of the street before,
+    /// or None to build every isomorphism. a limited build covers
+    /// only a corner of the game tree, so it suits smoke tests,
+    /// not training.
+    pub fn limit(&self) -> Option<usize> {
+        self.limit
+    }
+    pub fn with_k(mut self, street: Street, k: usize) -> Self {
+        assert!(street != Street::Pref, "preflop is not clustered");
+        assert!(street != Street::Rive, "river is not clustered");
+        self.k.insert(street, k);
+        self
+    }
+    pub fn with_t(mut self, street: Street, t: usize) -> Self {
+        self.t.insert(street, t);
+        self
+    }
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+    pub fn with_samples(mut self, samples: usize) -> Self {
+        assert!(samples > 0, "samples must be positive");
+        self.samples = Some(samples);
+        self
+    }
+    pub fn with_potential(mut self, weight: Energy) -> Self {
+        assert!((0. ..=1.).contains(&weight), "potential weight in [0, 1]");
+        self.potential = Some(weight);
+        self
+    }
+    pub fn with_sinkhorn(mut self, config: SinkhornConfig) -> Self {
+        assert!(config.epsilon > 0., "sinkhorn epsilon must be positive");
+        assert!(config.max_iters > 0, "sinkhorn max_iters must be positive");
+        self.sinkhorn = Some(config);
+        self
+    }
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        assert!(limit > 0, "limit must be positive");
+        self.limit = Some(limit);
+        self
+    }
+}
+
+#[cfg(feature = "native")]
+impl ClusterConfig {
+    /// where the config that built a directory's artifacts is kept
+    pub fn path(dir: &std::path::Path) -> String {
+        format!("{}/cluster.json", dir.to_string_lossy())
+    }
+    /// the config recorded in dir, if any
+    pub fn load_from(dir: &std::path::Path) -> Option<Self> {
+        let ref path = Self::path(dir);
+        let json = std::fs::read_to_string(path).ok()?;
+        log::info!("{:<32}{:<32}", "loading     cluster config", path);
+        serde_json::from_str(&json)
+            .map_err(|e| log::warn!("unreadable cluster config {}: {}", path, e))
+            .ok()
+    }
+    /// record this config as the one that built dir's artifacts
+    pub fn save_to(&self, dir: &std::path::Path) {
+        let ref path = Self::path(dir);
+        log::info!("{:<32}{:<32}", "saving      cluster config", path);
+        let json = serde_json::to_string_pretty(self).expect("serialize cluster config");
+        std::fs::write(path, json).expect(&format!("write {}", path));
+    }
+    /// isomorphisms of street that a build covers, in the order its
+    /// lookup is written. unlimited, that is all of them. limited,
+    /// the Preflop is whole and each later street keeps the first
+    /// few distinct children of every isomorphism kept before it.
+    pub fn isomorphisms(&self, street: Street) -> Box<dyn Iterator<Item = Isomorphism>> {
+        match (self.limit, street) {
+            (None, _) | (_, Street::Pref) => Box::new(IsomorphismIterator::from(street)),
+            (Some(_), _) => Box::new(
+                self.isomorphisms(street.prev())
+                    .flat_map(|parent| self.children(parent))
+                    .collect::<BTreeSet<Isomorphism>>()
+                    .into_iter(),
+            ),
+        }
+    }
+    /// distinct child isomorphisms of parent that a build keeps,
+    /// the first few of them if limited
+    pub fn children(&self, parent: Isomorphism) -> BTreeSet<Isomorphism> {
+        let n = self.limit.unwrap_or(usize::MAX);
+        let mut children = BTreeSet::new();
+        for child in parent.0.children().map(Isomorphism::from) {
+            if children.len() == n {
+                break;
+            }
+            children.insert(child);
+        }
+        children
+    }
+}
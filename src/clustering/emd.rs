@@ -1,3 +1,4 @@
+use super::exact::ExactTransport;
 use super::heuristic::Heuristic;
 use super::histogram::Histogram;
 use super::metric::Metric;
@@ -18,6 +19,9 @@ impl EMD {
     pub fn sinkhorn(&self) -> Sinkhorn {
         Sinkhorn::from((&self.1, &self.2, &self.0)).minimize()
     }
+    pub fn exact(&self) -> ExactTransport<'_> {
+        ExactTransport::from((&self.1, &self.2, &self.0)).minimize()
+    }
     pub fn heuristic(&self) -> Heuristic {
         Heuristic::from((&self.1, &self.2, &self.0)).minimize()
     }
@@ -34,7 +38,8 @@ impl Arbitrary for EMD {
         let p = Histogram::random();
         let q = Histogram::random();
         let r = Histogram::random();
-        let m = Metric::from(
+        let m = Metric::from((
+            p.peek().street(),
             std::iter::empty()
                 .chain(p.support())
                 .chain(q.support())
@@ -50,7 +55,7 @@ impl Arbitrary for EMD {
                 .map(|(x, y)| Pair::from((x, y)))
                 .map(|paired| (paired, rng.gen::<f32>()))
                 .collect::<BTreeMap<_, _>>(),
-        );
+        ));
         Self(m, p, q, r)
     }
 }
@@ -60,7 +65,10 @@ mod tests {
     use super::*;
     use crate::cards::observation::Observation;
     use crate::cards::street::Street;
+    use crate::clustering::abstraction::Abstraction;
     use crate::clustering::histogram::Histogram;
+    use crate::transport::measure::Measure;
+    use std::collections::BTreeSet;
 
     /// equity implementation should be
     /// 1. symmetric
@@ -167,4 +175,51 @@ mod tests {
         assert!(d11 == 0.);
         assert!(d22 == 0.);
     }
+
+    /// exact implementation should
+    /// 1. match the optimal assignment when every bin has equal mass
+    /// 2. be exactly self-annihilating
+
+    #[test]
+    fn is_exact_emd_optimal_assignment() {
+        use rand::Rng;
+        let ref mut rng = rand::thread_rng();
+        let a = (0..8)
+            .map(|i| Abstraction::from((Street::Flop, i)))
+            .collect::<Vec<Abstraction>>();
+        for _ in 0..64 {
+            let metric = Metric::from((
+                Street::Flop,
+                a.iter()
+                    .flat_map(|x| a.iter().map(move |y| (x, y)))
+                    .filter(|(x, y)| x > y)
+                    .map(|(x, y)| (Pair::from((x, y)), rng.gen::<f32>()))
+                    .collect::<BTreeMap<_, _>>(),
+            ));
+            let ref source = Histogram::from(a[..4].to_vec());
+            let ref target = Histogram::from(a[4..].to_vec());
+            let exact = ExactTransport::from((source, target, &metric))
+                .minimize()
+                .cost();
+            let brute = (0..256usize)
+                .map(|n| [n & 3, n >> 2 & 3, n >> 4 & 3, n >> 6 & 3])
+                .filter(|perm| perm.iter().collect::<BTreeSet<_>>().len() == 4)
+                .map(|perm| {
+                    (0..4)
+                        .map(|i| metric.distance(&a[i], &a[4 + perm[i]]))
+                        .sum::<f32>()
+                        / 4.
+                })
+                .fold(f32::INFINITY, f32::min);
+            assert!((exact - brute).abs() < 1e-5, "{} != {}", exact, brute);
+        }
+    }
+    #[test]
+    fn is_exact_emd_zero() {
+        let EMD(metric, h1, h2, _) = EMD::random();
+        let d11 = ExactTransport::from((&h1, &h1, &metric)).minimize().cost();
+        let d22 = ExactTransport::from((&h2, &h2, &metric)).minimize().cost();
+        assert!(d11 == 0.);
+        assert!(d22 == 0.);
+    }
 }
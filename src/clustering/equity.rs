@@ -1,7 +1,15 @@
 use super::abstraction::Abstraction;
 use super::histogram::Histogram;
+use crate::cards::card::Card;
+use crate::cards::hand::Hand;
+use crate::cards::hands::HandIterator;
+use crate::cards::observation::Observation;
+use crate::cards::street::Street;
+use crate::cards::strength::Strength;
 use crate::transport::measure::Measure;
-use crate::{Energy, Probability};
+use crate::Energy;
+use crate::Probability;
+use std::cmp::Ordering;
 
 /// useful struct for grouping methods that help in calculating
 /// optimal transport between two Equity Histograms.
@@ -18,7 +26,7 @@ impl Measure for Equity {
     type X = Abstraction; //::Equity(i8) variant
     type Y = Abstraction; //::Equity(i8) variant
     fn distance(&self, x: &Self::X, y: &Self::Y) -> f32 {
-        (Probability::from(*x) - Probability::from(*y)).abs()
+        (crate::Equity::from(*x) - crate::Equity::from(*y)).abs()
     }
 }
 
@@ -39,6 +47,32 @@ impl Equity {
             .sum::<Energy>()
             / Abstraction::size() as Energy
     }
+    /// cost of the monotone transport plan, split by the source bin
+    /// the mass was shipped from and weighted by how far it went.
+    /// in one dimension this plan is optimal, so the entries sum
+    /// to variation() exactly.
+    pub fn breakdown(x: &Histogram, y: &Histogram) -> Vec<(Abstraction, Energy)> {
+        let bins = Abstraction::range().collect::<Vec<Abstraction>>();
+        let mut supply = bins.iter().map(|b| y.density(b)).collect::<Vec<Energy>>();
+        let mut j = 0;
+        bins.iter()
+            .enumerate()
+            .map(|(i, bin)| {
+                let mut mass = x.density(bin);
+                let mut cost = 0.;
+                while mass > 0. && j < bins.len() {
+                    let moved = mass.min(supply[j]);
+                    cost += moved * i.abs_diff(j) as Energy;
+                    mass -= moved;
+                    supply[j] -= moved;
+                    if supply[j] <= 0. {
+                        j += 1;
+                    }
+                }
+                (*bin, cost / Abstraction::size() as Energy)
+            })
+            .collect()
+    }
     pub fn euclidean(x: &Histogram, y: &Histogram) -> Energy {
         Abstraction::range()
             .map(|abstraction| x.density(&abstraction) - y.density(&abstraction))
@@ -58,4 +92,174 @@ impl Equity {
             .map(|(x, y)| (x - y).abs())
             .sum::<Energy>()
     }
+    /// Monte-Carlo River equity against samples villain holdings,
+    /// each dealt from the cards the Observation leaves live, so
+    /// blockers are respected. like Observation::equity, draws are
+    /// dropped and a sample of nothing but draws is a coin flip.
+    pub fn approximate(obs: &Observation, samples: usize, rng: &mut impl rand::Rng) -> Probability {
+        assert!(obs.street() == Street::Rive);
+        Self::sampled(obs, samples, rng)
+    }
+    /// Monte-Carlo equity on any Street from samples showdowns, each
+    /// completing the board and a villain holding at random. samples
+    /// trade speed for accuracy, until they would cover every
+    /// showdown there is, when we enumerate them exactly instead.
+    pub fn estimate(obs: &Observation, samples: usize, rng: &mut impl rand::Rng) -> Probability {
+        match samples >= Self::showdowns(obs) {
+            true => Self::exact(obs),
+            false => Self::sampled(obs, samples, rng),
+        }
+    }
+    /// win probability of an Observation against a random holding,
+    /// with the rest of the board dealt at random. the River is
+    /// enumerated exactly, as the River Lookup is, and earlier Streets
+    /// are estimated, seeded by the Observation so calls agree.
+    /// Observation::estimate defers here, so EQUITY_SAMPLES is
+    /// the one knob for how closely either one estimates.
+    pub fn of(obs: &Observation) -> Probability {
+        use rand::rngs::SmallRng;
+        use rand::SeedableRng;
+        use std::hash::Hash;
+        use std::hash::Hasher;
+        let ref mut hasher = std::collections::hash_map::DefaultHasher::new();
+        obs.hash(hasher);
+        let ref mut rng = SmallRng::seed_from_u64(hasher.finish());
+        Self::estimate(obs, crate::EQUITY_SAMPLES, rng)
+    }
+    /// every runout of the board against every villain holding
+    fn exact(obs: &Observation) -> Probability {
+        let dead = Hand::from(*obs);
+        let runouts = match Self::unseen(obs) {
+            0 => vec![Hand::empty()],
+            n => HandIterator::from((n, dead)).collect(),
+        };
+        Self::tally(runouts.into_iter().flat_map(|runout| {
+            HandIterator::from((2, Hand::add(dead, runout)))
+                .map(move |villain| Self::showdown(obs, runout, villain))
+        }))
+    }
+    /// samples showdowns, dealing the runout and the villain
+    /// holding from the live cards, so blockers are respected
+    fn sampled(obs: &Observation, samples: usize, rng: &mut impl rand::Rng) -> Probability {
+        use rand::seq::SliceRandom;
+        let live = Vec::<Card>::from(Hand::from(*obs).complement());
+        let unseen = Self::unseen(obs);
+        Self::tally((0..samples).map(|_| {
+            let dealt = live
+                .choose_multiple(rng, 2 + unseen)
+                .copied()
+                .collect::<Vec<Card>>();
+            let runout = Hand::from(dealt[2..].to_vec());
+            let villain = Hand::from(dealt[..2].to_vec());
+            Self::showdown(obs, runout, villain)
+        }))
+    }
+    /// hero against villain, once the runout completes the board
+    fn showdown(obs: &Observation, runout: Hand, villain: Hand) -> Ordering {
+        let board = Hand::add(*obs.public(), runout);
+        let hero = Strength::from(Hand::add(*obs.pocket(), board));
+        hero.cmp(&Strength::from(Hand::add(villain, board)))
+    }
+    /// how many board cards are still to come
+    fn unseen(obs: &Observation) -> usize {
+        Street::Rive.n_observed() - obs.public().size()
+    }
+    /// how many (runout, villain) showdowns exact enumerates
+    fn showdowns(obs: &Observation) -> usize {
+        let dead = Hand::from(*obs);
+        let unseen = Self::unseen(obs);
+        let live = 52 - dead.size() - unseen;
+        HandIterator::from((unseen, dead)).combinations() * live * (live - 1) / 2
+    }
+    /// share of decided showdowns won. draws are
+    /// dropped, and nothing but draws is a coin flip
+    fn tally(showdowns: impl Iterator<Item = Ordering>) -> Probability {
+        let (won, sum) = showdowns.fold((0u32, 0u32), |(wins, total), ord| match ord {
+            Ordering::Greater => (wins + 1, total + 1),
+            Ordering::Less => (wins, total + 1),
+            Ordering::Equal => (wins, total),
+        });
+        match sum {
+            0 => Probability::from(0.5),
+            _ => Probability::from(won as f32 / sum as f32),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::SmallRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn approximate_converges_to_exact() {
+        let ref mut rng = SmallRng::seed_from_u64(0);
+        let obs = Observation::from(Street::Rive);
+        let exact = obs.equity();
+        let error = |samples: usize, rng: &mut SmallRng| {
+            (0..16)
+                .map(|_| f32::from(Equity::approximate(&obs, samples, rng)) - exact)
+                .map(|delta| delta.abs())
+                .sum::<f32>()
+                / 16.
+        };
+        let coarse = error(16, rng);
+        let fine = error(4096, rng);
+        assert!(fine < 0.02, "{} from {}", fine, exact);
+        assert!(fine <= coarse, "{} !<= {}", fine, coarse);
+    }
+
+    #[test]
+    /// Hand::add panics on overlap, so dealing
+    /// a dead card to villain would fail here
+    fn approximate_respects_blockers() {
+        let ref mut rng = SmallRng::seed_from_u64(0);
+        for _ in 0..64 {
+            let obs = Observation::from(Street::Rive);
+            let p = Equity::approximate(&obs, 64, rng);
+            assert!(p >= 0. && p <= 1.);
+        }
+    }
+
+    /// the Turn has few enough showdowns to enumerate,
+    /// so we can watch the estimate close in on them
+    #[test]
+    fn estimate_converges_to_exact() {
+        let ref mut rng = SmallRng::seed_from_u64(0);
+        let obs = Observation::from(Street::Turn);
+        let exact = f32::from(Equity::exact(&obs));
+        let error = |samples: usize, rng: &mut SmallRng| {
+            (0..16)
+                .map(|_| f32::from(Equity::estimate(&obs, samples, rng)) - exact)
+                .map(|delta| delta.abs())
+                .sum::<f32>()
+                / 16.
+        };
+        let coarse = error(16, rng);
+        let fine = error(4096, rng);
+        assert!(fine < 0.02, "{} from {}", fine, exact);
+        assert!(fine <= coarse, "{} !<= {}", fine, coarse);
+        assert!(f32::from(Equity::estimate(&obs, usize::MAX, rng)) == exact);
+    }
+
+    #[test]
+    fn exact_river_matches_observation() {
+        let ref mut rng = SmallRng::seed_from_u64(0);
+        let obs = Observation::from(Street::Rive);
+        assert!(Equity::showdowns(&obs) == 990);
+        assert!(f32::from(Equity::estimate(&obs, 990, rng)) == obs.equity());
+    }
+
+    /// well-known preflop equities against a random hand, with
+    /// draws dropped, a made royal flush, and the exact River
+    #[test]
+    fn of_matches_canonical_spots() {
+        let of = |s: &str| f32::from(Equity::of(&Observation::try_from(s).unwrap()));
+        assert!((of("AsAh") - 0.853).abs() < 0.02, "{}", of("AsAh"));
+        assert!((of("7c2d") - 0.337).abs() < 0.02, "{}", of("7c2d"));
+        assert!(of("AsKs~QsJsTs") == 1.);
+        let river = Observation::from(Street::Rive);
+        assert!(f32::from(Equity::of(&river)) == river.equity());
+    }
 }
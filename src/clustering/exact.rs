@@ -0,0 +1,237 @@
+use super::abstraction::Abstraction;
+use super::histogram::Histogram;
+use super::metric::Metric;
+use super::potential::Potential;
+use crate::transport::coupling::Coupling;
+use crate::transport::measure::Measure;
+use crate::Energy;
+use std::collections::btree_map::Entry;
+use std::collections::BTreeMap;
+use std::collections::VecDeque;
+
+/// exact optimal transport by the transportation simplex.
+///
+/// masses are scaled to integers, source counts by the target's total
+/// and target counts by the source's total, so both sides ship exactly
+/// the same amount and pivots never accumulate rounding error.
+///
+/// we start from the northwest corner plan, then repeatedly price every
+/// unused route against the dual potentials of the current basis, and
+/// push as much mass as we can around the cycle the cheapest route
+/// closes. once no route is cheaper than its potentials, the plan is
+/// optimal and cost() is the true 1-Wasserstein distance.
+///
+/// each pivot is O(N * M), and we may need many of them, so this only
+/// makes sense for histograms with small support. Sinkhorn handles the rest.
+pub struct ExactTransport<'a> {
+    metric: &'a Metric,
+    source: &'a Histogram,
+    target: &'a Histogram,
+    plan: BTreeMap<(Abstraction, Abstraction), Energy>,
+}
+
+impl ExactTransport<'_> {
+    /// solve the transportation problem over the two supports
+    fn simplex(&mut self) {
+        let xs = self.source.support().copied().collect::<Vec<_>>();
+        let ys = self.target.support().copied().collect::<Vec<_>>();
+        let (n, m) = (xs.len(), ys.len());
+        let cost = xs
+            .iter()
+            .map(|x| ys.iter().map(|y| self.metric.distance(x, y) as f64))
+            .map(|row| row.collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        let mut supply = xs
+            .iter()
+            .map(|x| self.source.count(x) as u64 * self.target.mass() as u64)
+            .collect::<Vec<_>>();
+        let mut demand = ys
+            .iter()
+            .map(|y| self.target.count(y) as u64 * self.source.mass() as u64)
+            .collect::<Vec<_>>();
+        let mut flow = vec![vec![0u64; m]; n];
+        let mut basis = vec![vec![false; m]; n];
+        // northwest corner. every step fixes one row or one column,
+        // so the basis ends up with exactly n + m - 1 cells
+        let (mut i, mut j) = (0, 0);
+        loop {
+            let mass = supply[i].min(demand[j]);
+            flow[i][j] = mass;
+            basis[i][j] = true;
+            supply[i] -= mass;
+            demand[j] -= mass;
+            match (supply[i] == 0 && i + 1 < n, j + 1 < m) {
+                (true, _) => i += 1,
+                (false, true) => j += 1,
+                (false, false) => break,
+            }
+        }
+        for _ in 0..self.pivots() {
+            let (u, v) = Self::duals(&cost, &basis);
+            let entering = (0..n)
+                .flat_map(|i| (0..m).map(move |j| (i, j)))
+                .filter(|&(i, j)| !basis[i][j])
+                .map(|(i, j)| ((i, j), cost[i][j] - u[i] - v[j]))
+                .min_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap());
+            match entering {
+                Some(((p, q), reduced)) if reduced < -1e-9 => {
+                    let cycle = Self::cycle(&basis, p, q);
+                    let (leaving, theta) = cycle
+                        .iter()
+                        .skip(1)
+                        .step_by(2)
+                        .map(|&(i, j)| ((i, j), flow[i][j]))
+                        .min_by_key(|&(_, f)| f)
+                        .expect("cycle has a donor cell");
+                    for (k, &(i, j)) in cycle.iter().enumerate() {
+                        match k % 2 {
+                            0 => flow[i][j] += theta,
+                            _ => flow[i][j] -= theta,
+                        }
+                    }
+                    basis[leaving.0][leaving.1] = false;
+                    basis[p][q] = true;
+                }
+                _ => break,
+            }
+        }
+        let total = self.source.mass() as f64 * self.target.mass() as f64;
+        self.plan = (0..n)
+            .flat_map(|i| (0..m).map(move |j| (i, j)))
+            .filter(|&(i, j)| flow[i][j] > 0)
+            .map(|(i, j)| ((xs[i], ys[j]), (flow[i][j] as f64 / total) as Energy))
+            .collect();
+    }
+    /// potentials u, v with u[i] + v[j] == cost[i][j] on every basic cell.
+    /// the basis is a spanning tree over rows and columns, so we
+    /// can fix u[0] = 0 and propagate outward.
+    fn duals(cost: &[Vec<f64>], basis: &[Vec<bool>]) -> (Vec<f64>, Vec<f64>) {
+        let (n, m) = (basis.len(), basis[0].len());
+        let mut u = vec![None; n];
+        let mut v = vec![None; m];
+        let mut queue = VecDeque::from([Line::Row(0)]);
+        u[0] = Some(0.);
+        while let Some(line) = queue.pop_front() {
+            match line {
+                Line::Row(i) => {
+                    for j in (0..m).filter(|&j| basis[i][j]) {
+                        if v[j].is_none() {
+                            v[j] = u[i].map(|ui| cost[i][j] - ui);
+                            queue.push_back(Line::Col(j));
+                        }
+                    }
+                }
+                Line::Col(j) => {
+                    for i in (0..n).filter(|&i| basis[i][j]) {
+                        if u[i].is_none() {
+                            u[i] = v[j].map(|vj| cost[i][j] - vj);
+                            queue.push_back(Line::Row(i));
+                        }
+                    }
+                }
+            }
+        }
+        (
+            u.into_iter()
+                .map(|x| x.expect("basis spans rows"))
+                .collect(),
+            v.into_iter()
+                .map(|x| x.expect("basis spans cols"))
+                .collect(),
+        )
+    }
+    /// the cycle that the entering cell (p, q) closes in the basis tree.
+    /// starts at the entering cell, and alternates between cells that
+    /// receive mass and cells that donate it.
+    fn cycle(basis: &[Vec<bool>], p: usize, q: usize) -> Vec<(usize, usize)> {
+        let (n, m) = (basis.len(), basis[0].len());
+        let mut parent = BTreeMap::<Line, Line>::new();
+        let mut queue = VecDeque::from([Line::Row(p)]);
+        parent.insert(Line::Row(p), Line::Row(p));
+        while let Some(line) = queue.pop_front() {
+            let neighbors = match line {
+                Line::Row(i) => (0..m)
+                    .filter(|&j| basis[i][j])
+                    .map(Line::Col)
+                    .collect::<Vec<_>>(),
+                Line::Col(j) => (0..n)
+                    .filter(|&i| basis[i][j])
+                    .map(Line::Row)
+                    .collect::<Vec<_>>(),
+            };
+            for next in neighbors {
+                if let Entry::Vacant(entry) = parent.entry(next) {
+                    entry.insert(line);
+                    queue.push_back(next);
+                }
+            }
+        }
+        let mut cycle = vec![(p, q)];
+        let mut line = Line::Col(q);
+        while line != Line::Row(p) {
+            let prev = parent[&line];
+            cycle.push(match (line, prev) {
+                (Line::Col(j), Line::Row(i)) | (Line::Row(i), Line::Col(j)) => (i, j),
+                _ => unreachable!("basis is bipartite"),
+            });
+            line = prev;
+        }
+        cycle
+    }
+    /// guard against cycling on degenerate bases
+    fn pivots(&self) -> usize {
+        let size = self.source.n() + self.target.n();
+        16 * size * size
+    }
+}
+
+/// a row or column of the transportation tableau
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Line {
+    Row(usize),
+    Col(usize),
+}
+
+impl Coupling for ExactTransport<'_> {
+    type X = Abstraction;
+    type Y = Abstraction;
+    type P = Potential;
+    type Q = Potential;
+    type M = Metric;
+
+    fn minimize(mut self) -> Self {
+        self.simplex();
+        self
+    }
+    fn flow(&self, x: &Self::X, y: &Self::Y) -> Energy {
+        self.plan.get(&(*x, *y)).copied().unwrap_or(0.) * self.metric.distance(x, y)
+    }
+    fn cost(&self) -> Energy {
+        self.plan
+            .keys()
+            .map(|(x, y)| self.flow(x, y))
+            .sum::<Energy>()
+    }
+}
+
+impl ExactTransport<'_> {
+    /// cost of the transport plan, split by the source bin
+    /// the mass was shipped from. sums to cost().
+    pub fn breakdown(&self) -> Vec<(Abstraction, Energy)> {
+        self.source
+            .support()
+            .map(|x| (*x, self.target.support().map(|y| self.flow(x, y)).sum()))
+            .collect()
+    }
+}
+
+impl<'a> From<(&'a Histogram, &'a Histogram, &'a Metric)> for ExactTransport<'a> {
+    fn from((source, target, metric): (&'a Histogram, &'a Histogram, &'a Metric)) -> Self {
+        Self {
+            metric,
+            source,
+            target,
+            plan: BTreeMap::default(),
+        }
+    }
+}
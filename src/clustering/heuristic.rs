@@ -5,7 +5,6 @@ use super::pair::Pair;
 use super::potential::Potential;
 use crate::transport::coupling::Coupling;
 use crate::transport::measure::Measure;
-use crate::Probability;
 use std::collections::BTreeMap;
 
 /// greedy algorithm for optimimal transport.
@@ -27,7 +26,7 @@ use std::collections::BTreeMap;
 /// also, it turns out this algorithm sucks in worst case. like it's just not at all
 /// a reasonable heuristic, even in pathological 1D trivial cases.
 pub struct Heuristic<'a> {
-    plan: BTreeMap<Pair, Probability>,
+    plan: BTreeMap<Pair, f32>,
     metric: &'a Metric,
     source: &'a Histogram,
     target: &'a Histogram,
@@ -40,10 +39,10 @@ impl Coupling for Heuristic<'_> {
     type Q = Potential;
     type M = Metric;
 
-    fn cost(&self) -> Probability {
+    fn cost(&self) -> f32 {
         self.plan.values().sum()
     }
-    fn flow(&self, x: &Self::X, y: &Self::Y) -> Probability {
+    fn flow(&self, x: &Self::X, y: &Self::Y) -> f32 {
         let ref index = Pair::from((x, y));
         self.plan
             .get(index)
@@ -69,7 +68,7 @@ impl Coupling for Heuristic<'_> {
                 {
                     None => break 'cost,
                     Some(((y, dy), distance)) => {
-                        let mass = Probability::min(*dx, *dy);
+                        let mass = f32::min(*dx, *dy);
                         let pair = Pair::from((&x, &y));
                         *dx -= mass;
                         *dy -= mass;
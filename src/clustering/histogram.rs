@@ -1,4 +1,5 @@
 use crate::cards::observation::Observation;
+use crate::cards::street::Street;
 use crate::clustering::abstraction::Abstraction;
 use crate::transport::density::Density;
 use crate::Arbitrary;
@@ -11,25 +12,41 @@ use std::ops::AddAssign;
 ///
 /// The sum of the weights is the total number of samples.
 /// The weight of an abstraction is the number of times it was sampled.
-#[derive(Debug, Default, Clone)]
+///
+/// The backing map is always sparse. An Observation's support can't
+/// exceed its number of children (47 turns, 46 rivers), well short of
+/// the abstractions of the next street, so a dense Vec would only add
+/// zeros. dense() converts at the boundary for anything that wants one.
+#[derive(Debug, Default, Clone, Hash, PartialEq, Eq)]
 pub struct Histogram {
     mass: usize,
     counts: BTreeMap<Abstraction, usize>,
 }
 
 impl Histogram {
+    /// the mass that normalized() rescales to
+    pub const NORM: usize = 0x10000;
+
     pub fn set(&mut self, abs: Abstraction, count: usize) {
         self.counts.insert(abs, count);
         self.mass += count;
     }
     /// the weight of a given Abstraction. returns 0 if the Abstraction was never witnessed.
-    pub fn density(&self, x: &Abstraction) -> Probability {
+    pub fn density(&self, x: &Abstraction) -> f32 {
         self.counts.get(x).copied().unwrap_or(0usize) as f32 / self.mass as f32
     }
+    /// the raw count of a given Abstraction. 0 if it was never witnessed.
+    pub fn count(&self, x: &Abstraction) -> usize {
+        self.counts.get(x).copied().unwrap_or(0usize)
+    }
     /// all witnessed Abstractions in the support
     pub fn support(&self) -> impl Iterator<Item = &Abstraction> {
         self.counts.keys()
     }
+    /// total number of samples
+    pub fn mass(&self) -> usize {
+        self.mass
+    }
     /// size of the support
     pub fn n(&self) -> usize {
         self.counts.len()
@@ -53,6 +70,80 @@ impl Histogram {
             self.counts.entry(*key).or_insert(0usize).add_assign(*count);
         }
     }
+    /// a new Histogram with both sets of counts. each side
+    /// weighs in by its mass, so normalize both first to
+    /// mix them as equally weighted distributions.
+    pub fn merge(&self, other: &Self) -> Self {
+        let mut merged = self.clone();
+        merged.absorb(other);
+        merged
+    }
+    /// a new Histogram with every count multiplied by factor,
+    /// rounded to the nearest sample. densities are unchanged,
+    /// only the weight in a merge, and integer factors are exact.
+    /// bins that round to zero leave the support.
+    pub fn scale(&self, factor: f32) -> Self {
+        assert!(factor >= 0.);
+        self.counts
+            .iter()
+            .map(|(abs, count)| (*abs, (*count as f32 * factor).round() as usize))
+            .filter(|(_, count)| *count > 0)
+            .fold(Self::default(), |mut hist, (abs, count)| {
+                hist.set(abs, count);
+                hist
+            })
+    }
+    /// a new Histogram with the same densities, up to rounding,
+    /// scaled to a mass of about Self::NORM samples
+    pub fn normalized(&self) -> Self {
+        self.scale(Self::NORM as f32 / self.mass as f32)
+    }
+    /// collapse bins into coarser ones. each group of fine
+    /// Abstractions becomes the single bin (street, i), where i is
+    /// the group's position. groups must partition the support:
+    /// no Abstraction in two groups, every witnessed one in some group.
+    pub fn merge_bins(&self, groups: &[Vec<Abstraction>]) -> Self {
+        let street = self.peek().street();
+        let mut coarse = BTreeMap::new();
+        for (i, group) in groups.iter().enumerate() {
+            for fine in group {
+                assert!(
+                    coarse
+                        .insert(*fine, Abstraction::from((street, i)))
+                        .is_none(),
+                    "{} appears in more than one group",
+                    fine
+                );
+            }
+        }
+        self.counts
+            .iter()
+            .map(|(fine, count)| {
+                let bin = coarse
+                    .get(fine)
+                    .expect(&format!("{} appears in no group", fine));
+                (*bin, *count)
+            })
+            .fold(Self::default(), |mut hist, (bin, count)| {
+                hist.mass += count;
+                hist.counts.entry(bin).or_insert(0).add_assign(count);
+                hist
+            })
+    }
+
+    /// dense counts indexed by Abstraction::index over the whole street,
+    /// zeros included. Histogram itself stays sparse, since most
+    /// River and Preflop bins are empty. a street clustered with a
+    /// larger K than the default is widened to its highest index.
+    pub fn dense(&self) -> Vec<usize> {
+        let street = self.peek().street();
+        let width = self.support().map(|abs| abs.index() + 1).max();
+        let mut counts = vec![0; street.n_abstractions().max(width.unwrap_or(0))];
+        for (abs, count) in self.counts.iter() {
+            counts[abs.index()] = *count;
+        }
+        counts
+    }
 
     /// it is useful in EMD calculation
     /// to know if we're dealing with ::Equity or ::Random
@@ -68,6 +159,17 @@ impl Histogram {
         assert!(matches!(self.peek(), Abstraction::Percent(_)));
         self.pdf().iter().map(|(x, y)| x * y).sum()
     }
+    /// spread of the River equity distribution, i.e. how far the
+    /// next card can swing this hand. a draw and a made hand of
+    /// the same equity() are told apart here.
+    pub fn potential(&self) -> Equity {
+        let mean = self.equity();
+        self.pdf()
+            .iter()
+            .map(|(x, p)| p * (x - mean) * (x - mean))
+            .sum::<Equity>()
+            .sqrt()
+    }
     /// this yields the posterior equity distribution
     /// at Street::Turn.
     /// this is the only street we explicitly can calculate
@@ -75,31 +177,37 @@ impl Histogram {
     ///     Probability -> Probability
     /// vs  Probability -> Abstraction
     /// hence a distribution over showdown equities.
-    pub fn pdf(&self) -> Vec<(Equity, Probability)> {
+    pub fn pdf(&self) -> Vec<(Equity, f32)> {
         assert!(matches!(self.peek(), Abstraction::Percent(_)));
         self.counts
             .iter()
             .map(|(&key, &value)| (key, value as f32 / self.mass as f32))
-            .map(|(k, v)| (Equity::from(k), Probability::from(v)))
+            .map(|(k, v)| (Equity::from(k), v))
             .collect()
     }
 
+    /// every Abstraction in the support with its normalized
+    /// mass, in Abstraction order. these sum to one.
+    pub fn distribution(&self) -> impl Iterator<Item = (&Abstraction, Probability)> {
+        self.counts
+            .iter()
+            .map(|(abs, count)| (abs, Probability::from(*count as f32 / self.mass as f32)))
+    }
     /// owned vector of Abstractions and their densities
     /// sorted by density in descending order (most likely first)
-    pub fn distribution(&self) -> Vec<(Abstraction, Probability)> {
-        let mut distribution = self
-            .support()
-            .copied()
-            .map(|abs| (abs, self.density(&abs)))
+    pub fn ranked(&self) -> Vec<(Abstraction, f32)> {
+        let mut ranked = self
+            .distribution()
+            .map(|(abs, p)| (*abs, f32::from(p)))
             .collect::<Vec<_>>();
-        distribution.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
-        distribution
+        ranked.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+        ranked
     }
 }
 
 impl From<Observation> for Histogram {
     fn from(ref turn: Observation) -> Self {
-        assert!(turn.street() == crate::cards::street::Street::Turn);
+        assert!(turn.street() == Street::Turn);
         turn.children()
             .map(|river| Abstraction::from(river.equity()))
             .fold(Self::default(), |hist, abs| hist.increment(abs))
@@ -113,6 +221,23 @@ impl From<Vec<Abstraction>> for Histogram {
     }
 }
 
+/// from dense per-street counts. empty bins are dropped rather than
+/// stored as zeros, since a zero-density bin in the support would
+/// blow up the log-potentials in Sinkhorn.
+impl From<(Street, &[usize])> for Histogram {
+    fn from((street, counts): (Street, &[usize])) -> Self {
+        counts
+            .iter()
+            .enumerate()
+            .filter(|(_, count)| **count > 0)
+            .map(|(i, count)| (Abstraction::from((street, i)), *count))
+            .fold(Self::default(), |mut hist, (abs, count)| {
+                hist.set(abs, count);
+                hist
+            })
+    }
+}
+
 impl Density for Histogram {
     type S = Abstraction;
     fn density(&self, x: &Self::S) -> f32 {
@@ -182,3 +307,102 @@ impl std::fmt::Display for Histogram {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merge_into_one_bin() {
+        let fine = (0..8)
+            .map(|i| Abstraction::from((Street::Turn, i)))
+            .collect::<Vec<Abstraction>>();
+        let histogram = Histogram::from(
+            fine.iter()
+                .cycle()
+                .take(20)
+                .copied()
+                .collect::<Vec<Abstraction>>(),
+        );
+        let merged = histogram.merge_bins(&[fine]);
+        assert!(merged.n() == 1);
+        assert!(merged.mass() == histogram.mass());
+        assert!(merged.density(merged.peek()) == 1.);
+    }
+
+    #[test]
+    fn dense_round_trip_preserves_emd() {
+        use crate::clustering::metric::Metric;
+        let ref metric = Metric::default();
+        let x = Histogram::from(Observation::from(Street::Turn));
+        let y = Histogram::from(Observation::from(Street::Turn));
+        let dense = |h: &Histogram| Histogram::from((Street::Rive, h.dense().as_slice()));
+        assert!(dense(&x).dense() == x.dense());
+        assert!(metric.emd(&x, &y) == metric.emd(&dense(&x), &dense(&y)));
+    }
+
+    #[test]
+    fn support_is_sparse() {
+        let turn = Histogram::from(Observation::from(Street::Turn));
+        assert!(turn.n() <= Street::Turn.n_children());
+        assert!(turn.n() < turn.dense().len());
+    }
+
+    #[test]
+    fn dense_widens_past_default_k() {
+        let k = Street::Turn.k() + 8;
+        let beyond = Abstraction::from((Street::Turn, k - 1));
+        let h = Histogram::from(vec![Abstraction::from((Street::Turn, 0)), beyond]);
+        assert!(h.dense().len() == k);
+        assert!(h.dense()[k - 1] == 1);
+    }
+
+    #[test]
+    fn merge_is_commutative() {
+        let x = Histogram::random();
+        let y = Histogram::random();
+        assert!(x.merge(&y) == y.merge(&x));
+        assert!(x.merge(&y).mass() == x.mass() + y.mass());
+        assert!(x.merge(&Histogram::default()) == x);
+    }
+
+    #[test]
+    fn scale_is_linear() {
+        let h = Histogram::random();
+        assert!(h.scale(2.).merge(&h.scale(3.)) == h.scale(5.));
+        assert!(h.scale(2.).scale(3.) == h.scale(6.));
+        assert!(h.scale(1.) == h);
+        assert!(h.scale(0.).mass() == 0);
+        for abs in h.support() {
+            assert!(h.scale(4.).density(abs) == h.density(abs));
+        }
+    }
+
+    #[test]
+    fn normalized_mixes_equally() {
+        let x = Histogram::from(vec![Abstraction::from((Street::Turn, 0)); 3]);
+        let y = Histogram::from(vec![Abstraction::from((Street::Turn, 1)); 97]);
+        let mixed = x.normalized().merge(&y.normalized());
+        assert!(x.normalized().mass() == Histogram::NORM);
+        assert!(mixed.density(x.peek()) == 0.5);
+        assert!(mixed.density(y.peek()) == 0.5);
+        assert!(x.merge(&y).density(x.peek()) == 0.03);
+    }
+
+    #[test]
+    fn distribution_sums_to_one() {
+        let h = Histogram::random();
+        let total = h.distribution().map(|(_, p)| f32::from(p)).sum::<f32>();
+        assert!((total - 1.).abs() < 1e-5);
+        assert!(h.distribution().count() == h.n());
+        assert!(h.ranked().windows(2).all(|w| w[0].1 >= w[1].1));
+    }
+
+    #[test]
+    #[should_panic]
+    fn merge_rejects_overlap() {
+        let a = Abstraction::from((Street::Turn, 0));
+        let b = Abstraction::from((Street::Turn, 1));
+        Histogram::from(vec![a, b]).merge_bins(&[vec![a, b], vec![b]]);
+    }
+}
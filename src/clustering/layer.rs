@@ -1,24 +1,39 @@
 use super::abstraction::Abstraction;
+use super::cache::Cache;
+use super::config::ClusterConfig;
 use super::histogram::Histogram;
 use super::lookup::Lookup;
 use super::metric::Metric;
 use super::pair::Pair;
+use super::stopwatch::Laps;
+use super::stopwatch::Stopwatch;
 use super::transitions::Decomp;
 use crate::cards::isomorphism::Isomorphism;
-use crate::cards::isomorphisms::IsomorphismIterator;
+use crate::cards::observation::Observation;
 use crate::cards::street::Street;
 use crate::Energy;
+use crate::Hook;
 use rand::distributions::Distribution;
 use rand::distributions::WeightedIndex;
 use std::collections::BTreeMap;
+use std::sync::Mutex;
 
 type Neighbor = (usize, f32);
 
+/// the one kmeans implementation, shared by every street and
+/// by both the CLI and Pipeline entry points. given the same
+/// points and ClusterConfig it reproduces the same centroids,
+/// so a rerun with a fixed seed yields the same abstraction.
 pub struct Layer {
     street: Street,
     metric: Metric,
     points: Vec<Histogram>, // positioned by Isomorphism
     kmeans: Vec<Histogram>, // positioned by K-means abstraction
+    cache: Mutex<Cache>,    // centroid pair distances across metric() calls
+    classified: Mutex<BTreeMap<Isomorphism, Abstraction>>, // memoized classify() results
+    hook: Option<Hook>,     // progress events of init and cluster
+    laps: Laps,             // per-phase elapsed times
+    config: ClusterConfig,  // kmeans hyperparameters
 }
 
 impl Layer {
@@ -35,6 +50,92 @@ impl Layer {
             .count();
     }
 
+    #[cfg(feature = "native")]
+    /// build and cluster a Layer with explicit hyperparameters
+    pub fn make(street: Street, config: &ClusterConfig) -> Self {
+        Self::make_in(&crate::save::upload::directory(), street, config, None)
+    }
+
+    #[cfg(feature = "native")]
+    /// build and cluster a Layer from the next street's artifacts in dir,
+    /// reporting clustering progress to hook
+    pub fn make_in(
+        dir: &std::path::Path,
+        street: Street,
+        config: &ClusterConfig,
+        hook: Option<Hook>,
+    ) -> Self {
+        use crate::save::upload::Table;
+        let layer = match street {
+            Street::Rive => Self {
+                street,
+                kmeans: Vec::default(),
+                points: Vec::default(),
+                metric: Metric::default(),
+                cache: Mutex::new(Cache::load(&Cache::path(dir, street))),
+                classified: Mutex::default(),
+                hook: hook.clone(),
+                laps: Laps::default(),
+                config: config.clone(),
+            },
+            _ => Self {
+                street,
+                kmeans: Vec::default(),
+                // clustering can't start without the next street,
+                // so a missing or corrupt artifact is fatal here
+                points: {
+                    let lookup = Lookup::load_from(dir, street.next()).unwrap_or_else(|e| {
+                        panic!("{}: {}", Lookup::path_in(dir, street.next()), e)
+                    });
+                    match config.limit() {
+                        None => lookup.projections(),
+                        Some(_) => config
+                            .isomorphisms(street)
+                            .map(|iso| config.children(iso))
+                            .map(|kept| kept.iter().map(|c| lookup.lookup(&c.0)).collect())
+                            .map(|children: Vec<Abstraction>| Histogram::from(children))
+                            .collect(),
+                    }
+                },
+                metric: Metric::load_from(dir, street.next())
+                    .unwrap_or_else(|e| panic!("{}: {}", Metric::path_in(dir, street.next()), e)),
+                cache: Mutex::new(Cache::load(&Cache::path(dir, street))),
+                classified: Mutex::default(),
+                hook: hook.clone(),
+                laps: Laps::default(),
+                config: config.clone(),
+            },
+        };
+        layer.cluster()
+    }
+
+    #[cfg(feature = "native")]
+    /// compute and write metric, lookup, and transitions into dir
+    pub fn save_to(&self, dir: &std::path::Path) {
+        let street = self.street();
+        let metric = {
+            let _timer = Stopwatch::from((&self.laps, street, "metric"));
+            self.metric(Some(&Cache::path(dir, street)))
+        };
+        let lookup = {
+            let _timer = Stopwatch::from((&self.laps, street, "lookup"));
+            self.lookup()
+        };
+        let decomp = {
+            let _timer = Stopwatch::from((&self.laps, street, "decomp"));
+            self.decomp()
+        };
+        {
+            let _timer = Stopwatch::from((&self.laps, street, "save"));
+            metric.save_to(dir);
+            lookup.save_to(dir);
+            decomp.save_to(dir);
+            let ref cache = self.cache.lock().expect("metric cache lock");
+            cache.save(&Cache::path(dir, street));
+        }
+        log::info!("timing\n{}", super::stopwatch::summary(&self.laps, street));
+    }
+
     /// reference to the all points up to isomorphism
     fn points(&self) -> &Vec<Histogram> /* N */ {
         &self.points
@@ -48,20 +149,34 @@ impl Layer {
     /// primary clustering algorithm loop
     fn cluster(mut self) -> Self {
         log::info!("{:<32}{:<32}", "initialize  kmeans", self.street());
-        let ref mut init = self.init();
-        let ref mut last = self.kmeans;
-        std::mem::swap(init, last);
-        log::info!("{:<32}{:<32}", "clustering  kmeans", self.street());
-        let t = self.street().t();
-        let progress = crate::progress(t);
-        for _ in 0..t {
-            let ref mut next = self.next();
+        {
+            let _timer = Stopwatch::from((&self.laps, self.street, "init"));
+            let ref mut init = self.init();
             let ref mut last = self.kmeans;
-            std::mem::swap(next, last);
-            progress.inc(1);
+            std::mem::swap(init, last);
+        }
+        log::info!("{:<32}{:<32}", "clustering  kmeans", self.street());
+        {
+            let _timer = Stopwatch::from((&self.laps, self.street, "cluster"));
+            let t = self.config.t(self.street());
+            let progress = crate::progress(t)
+                .with_phase("kmeans")
+                .with_hook(self.hook.clone());
+            for iteration in 0..t {
+                let (ref mut next, rms) = self.next();
+                let ref mut last = self.kmeans;
+                std::mem::swap(next, last);
+                log::debug!(
+                    "street={} phase=kmeans iteration={} rms={}",
+                    self.street(),
+                    iteration,
+                    rms
+                );
+                progress.inc(1);
+            }
+            progress.finish();
+            println!();
         }
-        progress.finish();
-        println!();
         self
     }
 
@@ -69,7 +184,10 @@ impl Layer {
     /// initializes the centroids for k-means clustering using the k-means++ algorithm
     /// 1. choose 1st centroid randomly from the dataset
     /// 2. choose nth centroid with probability proportional to squared distance of nearest neighbors
-    /// 3. collect histograms and label with arbitrary (random) `Abstraction`s
+    /// 3. collect histograms, later labeled by index as in Layer::abstraction
+    ///
+    /// the draws are seeded by the street and the configured seed, if any,
+    /// so the same points always yield the same initial centroids.
     fn init(&self) -> Vec<Histogram> /* K */ {
         use rand::rngs::SmallRng;
         use rand::SeedableRng;
@@ -79,7 +197,7 @@ impl Layer {
         use std::hash::Hash;
         use std::hash::Hasher;
         // don't do any abstraction on preflop
-        let k = self.street().k();
+        let k = self.config.k(self.street());
         let n = self.points().len();
         if self.street() == Street::Pref {
             assert!(n == k);
@@ -87,10 +205,15 @@ impl Layer {
         }
         // deterministic pseudo-random clustering
         let ref mut hasher = DefaultHasher::default();
+        if let Some(seed) = self.config.seed() {
+            seed.hash(hasher);
+        }
         self.street().hash(hasher);
         let ref mut rng = SmallRng::seed_from_u64(hasher.finish());
         // kmeans++ initialization
-        let progress = crate::progress(k * n);
+        let progress = crate::progress(k * n)
+            .with_phase("kmeans++")
+            .with_hook(self.hook.clone());
         let mut potentials = vec![1.; n];
         let mut histograms = Vec::new();
         while histograms.len() < k {
@@ -123,11 +246,12 @@ impl Layer {
     #[cfg(feature = "native")]
     /// calculates the next step of the kmeans iteration by
     /// determining K * N optimal transport calculations and
-    /// taking the nearest neighbor
-    fn next(&self) -> Vec<Histogram> /* K */ {
+    /// taking the nearest neighbor. also returns the RMS
+    /// distance of points to their assigned centroids
+    fn next(&self) -> (Vec<Histogram> /* K */, f32) {
         use rayon::iter::IntoParallelRefIterator;
         use rayon::iter::ParallelIterator;
-        let k = self.street().k();
+        let k = self.config.k(self.street());
         let mut loss = 0f32;
         let mut centroids = vec![Histogram::default(); k];
         // assign points to nearest neighbors
@@ -144,23 +268,64 @@ impl Layer {
                 .expect("index from neighbor calculation")
                 .absorb(point);
         }
-        log::debug!(
-            "{:<32}{:<32}",
-            "abstraction cluster RMS error",
-            (loss / self.points().len() as f32).sqrt()
-        );
-        centroids
+        (centroids, (loss / self.points().len() as f32).sqrt())
     }
 
-    /// wrawpper for distance metric calculations
+    /// abstract an arbitrary Observation of this street, which need not
+    /// have been among our training points. the outer Lookup is the
+    /// next street's table that our points were projected from.
+    /// assignments are memoized by Isomorphism, since centroids
+    /// no longer move once the Layer has been clustered.
+    pub fn classify(&self, outer: &Lookup, obs: &Observation) -> Abstraction {
+        let iso = Isomorphism::from(*obs);
+        let ref mut classified = self.classified.lock().expect("classify cache lock");
+        *classified.entry(iso).or_insert_with(|| {
+            let ref histogram = outer.future(&iso);
+            let (k, _) = self.neighborhood(histogram);
+            self.abstraction(k)
+        })
+    }
+
+    /// wrawpper for distance metric calculations.
+    /// the Turn may also weigh in equity potential
     fn emd(&self, x: &Histogram, y: &Histogram) -> Energy {
-        self.metric.emd(x, y)
+        match (self.street(), self.config.potential()) {
+            (Street::Turn, Some(weight)) => self.metric.combined(x, y, weight),
+            _ => self.metric.emd_with(x, y, self.config.sinkhorn()),
+        }
     }
     /// because we have fixed-order Abstractions that are determined by
     /// street and K-index, we should encapsulate the self.street depenency
     fn abstraction(&self, i: usize) -> Abstraction {
         Abstraction::from((self.street(), i))
     }
+    #[cfg(feature = "native")]
+    /// every (i, j) with i > j among k centroids, in the order that
+    /// Layer::metric measures them. each centroid is labeled by its
+    /// index, so labels are distinct by construction, but a Pair only
+    /// keeps the XOR of two labels, so we check that no two pairs of
+    /// centroids would share an entry in the Metric.
+    fn triangle(street: Street, k: usize) -> Vec<(usize, usize)> {
+        let label = |i: usize| Abstraction::from((street, i));
+        let mut seen = BTreeMap::<Pair, (usize, usize)>::new();
+        let pairs = (0..k)
+            .flat_map(|i| (0..i).map(move |j| (i, j)))
+            .collect::<Vec<(usize, usize)>>();
+        for &(i, j) in pairs.iter() {
+            if let Some((x, y)) = seen.insert(Pair::from((&label(i), &label(j))), (i, j)) {
+                log::error!(
+                    "{} centroids ({}, {}) and ({}, {}) collide",
+                    street,
+                    i,
+                    j,
+                    x,
+                    y
+                );
+                panic!("abstraction pair collision on {}", street);
+            }
+        }
+        pairs
+    }
     /// calculates nearest neighbor and separation distance for a Histogram
     fn neighborhood(&self, x: &Histogram) -> Neighbor {
         self.kmeans()
@@ -176,24 +341,60 @@ impl Layer {
     fn street(&self) -> Street {
         self.street
     }
+    #[cfg(feature = "native")]
     /// take outer triangular product of current learned kmeans
-    /// Histograms, using whatever is stored as the future metric
-    fn metric(&self) -> Metric {
+    /// Histograms, using whatever is stored as the future metric.
+    /// pairs already in the cache are reused and the rest are solved
+    /// in parallel batches. given a checkpoint path, the cache is
+    /// written after every batch, so an interrupted build resumes
+    /// by recomputing only the pairs it had not reached.
+    fn metric(&self, checkpoint: Option<&str>) -> Metric {
         log::info!("{:<32}{:<32}", "calculating metric", self.street());
+        use rayon::iter::IntoParallelRefIterator;
+        use rayon::iter::ParallelIterator;
+        let k = self.kmeans.len();
+        let pairs = Self::triangle(self.street(), k);
+        let progress = crate::progress(pairs.len())
+            .with_phase("metric")
+            .with_hook(self.hook.clone());
+        let pair = |i: usize, j: usize| Pair::from((&self.abstraction(i), &self.abstraction(j)));
         let mut metric = BTreeMap::new();
-        for (i, x) in self.kmeans.iter().enumerate() {
-            for (j, y) in self.kmeans.iter().enumerate() {
-                if i > j {
-                    let ref a = self.abstraction(i);
-                    let ref b = self.abstraction(j);
-                    let index = Pair::from((a, b));
-                    let distance = self.metric.emd(x, y) + self.metric.emd(y, x);
-                    let distance = distance / 2.;
-                    metric.insert(index, distance);
+        let mut missing = Vec::new();
+        {
+            let ref mut cache = self.cache.lock().expect("metric cache lock");
+            for (i, j) in pairs {
+                match cache.get(&self.kmeans[i], &self.kmeans[j]) {
+                    Some(distance) => {
+                        metric.insert(pair(i, j), distance);
+                        progress.inc(1);
+                    }
+                    None => missing.push((i, j)),
                 }
             }
         }
-        Metric::from(metric)
+        for batch in missing.chunks(crate::KMEANS_METRIC_BATCH) {
+            let solved = batch
+                .par_iter()
+                .map(|&(i, j)| (i, j, &self.kmeans[i], &self.kmeans[j]))
+                .map(|(i, j, x, y)| (i, j, (self.emd(x, y) + self.emd(y, x)) / 2.))
+                .inspect(|_| progress.inc(1))
+                .collect::<Vec<(usize, usize, Energy)>>();
+            let ref mut cache = self.cache.lock().expect("metric cache lock");
+            for (i, j, distance) in solved {
+                cache.insert(&self.kmeans[i], &self.kmeans[j], distance);
+                metric.insert(pair(i, j), distance);
+            }
+            if let Some(path) = checkpoint {
+                cache.save(path);
+            }
+        }
+        progress.finish();
+        let metric = Metric::from((self.street(), metric));
+        assert!(
+            metric.n() == k * k.saturating_sub(1) / 2,
+            "incorrect metric entries"
+        );
+        metric
     }
     /// in ObsIterator order, get a mapping of
     /// Isomorphism -> Abstraction
@@ -204,8 +405,12 @@ impl Layer {
         use rayon::iter::IntoParallelRefIterator;
         use rayon::iter::ParallelIterator;
         let street = self.street();
-        match street {
-            Street::Pref | Street::Rive => Lookup::grow(street),
+        let lookup = match street {
+            Street::Rive => match self.config.samples() {
+                Some(samples) => Lookup::approximate(self.config.isomorphisms(street), samples),
+                None => Lookup::river(self.config.isomorphisms(street)),
+            },
+            Street::Pref => Lookup::grow(street),
             Street::Flop | Street::Turn => self
                 .points()
                 .par_iter()
@@ -213,11 +418,22 @@ impl Layer {
                 .collect::<Vec<Neighbor>>()
                 .into_iter()
                 .map(|(k, _)| self.abstraction(k))
-                .zip(IsomorphismIterator::from(street))
+                .zip(self.config.isomorphisms(street))
                 .map(|(abs, iso)| (iso, abs))
                 .collect::<BTreeMap<Isomorphism, Abstraction>>()
                 .into(),
+        };
+        // enumerating every isomorphism again is as slow as building
+        // the River lookup, so only debug builds pay for the check.
+        // a limited build is partial by design
+        if self.config.limit().is_none() {
+            debug_assert_eq!(
+                lookup.validate(street),
+                Ok(()),
+                "lookup for {street} is not the full set of isomorphisms"
+            );
         }
+        lookup
     }
     /// in AbsIterator order, get a mapping of
     /// Abstraction -> Histogram
@@ -236,30 +452,14 @@ impl Layer {
 
 #[cfg(feature = "native")]
 impl crate::save::upload::Table for Layer {
-    fn done(street: Street) -> bool {
-        Lookup::done(street) && Decomp::done(street) && Metric::done(street)
+    fn done_in(dir: &std::path::Path, street: Street) -> bool {
+        Lookup::done_in(dir, street) && Decomp::done_in(dir, street) && Metric::done_in(dir, street)
     }
     fn save(&self) {
-        self.metric().save();
-        self.lookup().save();
-        self.decomp().save();
+        self.save_to(&crate::save::upload::directory())
     }
     fn grow(street: Street) -> Self {
-        let layer = match street {
-            Street::Rive => Self {
-                street,
-                kmeans: Vec::default(),
-                points: Vec::default(),
-                metric: Metric::default(),
-            },
-            _ => Self {
-                street,
-                kmeans: Vec::default(),
-                points: Lookup::load(street.next()).projections(),
-                metric: Metric::load(street.next()),
-            },
-        };
-        layer.cluster()
+        Self::make(street, &ClusterConfig::default())
     }
 
     fn name() -> String {
@@ -268,7 +468,7 @@ impl crate::save::upload::Table for Layer {
     fn copy() -> String {
         unimplemented!()
     }
-    fn load(_: Street) -> Self {
+    fn load(_: Street) -> Result<Self, crate::save::upload::LoadError> {
         unimplemented!()
     }
     fn creates() -> String {
@@ -284,3 +484,323 @@ impl crate::save::upload::Table for Layer {
         unimplemented!()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::measure::Measure;
+
+    /// a Layer over kmeans and points, with nothing cached or classified
+    fn fixture(
+        street: Street,
+        kmeans: Vec<Histogram>,
+        points: Vec<Histogram>,
+        config: ClusterConfig,
+    ) -> Layer {
+        Layer {
+            street,
+            kmeans,
+            points,
+            metric: Metric::default(),
+            cache: Mutex::default(),
+            classified: Mutex::default(),
+            hook: None,
+            laps: Laps::default(),
+            config,
+        }
+    }
+
+    #[test]
+    fn classify_finds_nearest_centroid() {
+        use crate::clustering::equity::Equity;
+        let street = Street::Turn;
+        let obs = (0..9)
+            .map(|_| Observation::from(street))
+            .collect::<Vec<Observation>>();
+        let outer = Lookup::from(
+            obs.iter()
+                .map(|o| Isomorphism::from(*o).0)
+                .flat_map(|o| o.children().collect::<Vec<Observation>>())
+                .map(|o| (Isomorphism::from(o), Abstraction::from(o.equity())))
+                .collect::<BTreeMap<Isomorphism, Abstraction>>(),
+        );
+        // centroids are the first few points, projected through the Lookup
+        let (train, fresh) = obs.split_at(3);
+        let kmeans = train
+            .iter()
+            .map(|o| outer.future(&Isomorphism::from(*o)))
+            .collect::<Vec<Histogram>>();
+        let layer = fixture(
+            street,
+            kmeans.clone(),
+            kmeans.clone(),
+            ClusterConfig::default(),
+        );
+        // a centroid's own observation is nearest to itself
+        for (k, o) in train.iter().enumerate() {
+            assert!(layer.classify(&outer, o) == layer.abstraction(k));
+        }
+        // fresh observations, histogrammed straight from their own
+        // (non-canonical) runouts, against brute force over centroids
+        for o in fresh {
+            let ref histogram = Histogram::from(
+                o.children()
+                    .map(|child| Abstraction::from(child.equity()))
+                    .collect::<Vec<Abstraction>>(),
+            );
+            let nearest = kmeans
+                .iter()
+                .map(|centroid| Equity::variation(histogram, centroid))
+                .enumerate()
+                .min_by(|(_, x), (_, y)| x.partial_cmp(y).unwrap())
+                .map(|(k, _)| layer.abstraction(k))
+                .unwrap();
+            assert!(layer.classify(&outer, o) == nearest);
+        }
+        assert!(layer.classified.lock().unwrap().len() == obs.len());
+        assert!(layer.classify(&outer, &fresh[0]) == layer.classify(&outer, &fresh[0]));
+        assert!(layer.classified.lock().unwrap().len() == obs.len());
+    }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn clustering_is_reproducible() {
+        let street = Street::Turn;
+        let config = ClusterConfig::default()
+            .with_seed(7)
+            .with_k(street, 3)
+            .with_t(street, 4);
+        let points = (0..12)
+            .map(|_| Observation::from(street))
+            .map(Histogram::from)
+            .collect::<Vec<Histogram>>();
+        let layer = |config: &ClusterConfig| {
+            fixture(street, Vec::default(), points.clone(), config.clone()).cluster()
+        };
+        let a = layer(&config);
+        let b = layer(&config);
+        let assign = |layer: &Layer| {
+            layer
+                .points()
+                .iter()
+                .map(|h| layer.neighborhood(h))
+                .collect::<Vec<Neighbor>>()
+        };
+        assert!(assign(&a) == assign(&b));
+        let (ma, mb) = (a.metric(None), b.metric(None));
+        for i in 0..3 {
+            for j in 0..i {
+                let ref x = a.abstraction(i);
+                let ref y = a.abstraction(j);
+                assert!(ma.distance(x, y) == mb.distance(x, y));
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn potential_separates_draws_from_made_hands() {
+        let street = Street::Turn;
+        let p = |e: crate::Equity| Abstraction::from(e);
+        // every hand has equity one half, give or take
+        let made = |e| Histogram::from(vec![p(e); 4]);
+        let draw = |lo, hi| Histogram::from(vec![p(lo), p(lo), p(hi), p(hi)]);
+        let points = vec![
+            made(0.48),
+            made(0.50),
+            made(0.52),
+            draw(0.10, 0.90),
+            draw(0.12, 0.88),
+            draw(0.08, 0.92),
+        ];
+        assert!(points.iter().all(|h| (h.equity() - 0.5).abs() < 0.05));
+        let config = ClusterConfig::default()
+            .with_seed(7)
+            .with_k(street, 2)
+            .with_t(street, 8)
+            .with_potential(0.5);
+        let layer = fixture(street, Vec::default(), points.clone(), config).cluster();
+        let (k, _) = layer.neighborhood(&points[1]);
+        let (j, _) = layer.neighborhood(&points[4]);
+        assert!(k != j);
+        assert!(points[..3].iter().all(|h| layer.neighborhood(h).0 == k));
+        assert!(points[3..].iter().all(|h| layer.neighborhood(h).0 == j));
+    }
+
+    #[test]
+    fn init_is_seeded() {
+        let street = Street::Turn;
+        let points = (0..16)
+            .map(|_| Observation::from(street))
+            .map(Histogram::from)
+            .collect::<Vec<Histogram>>();
+        let centroids = |config: ClusterConfig| {
+            fixture(street, Vec::default(), points.clone(), config)
+                .init()
+                .iter()
+                .map(Histogram::dense)
+                .collect::<Vec<Vec<usize>>>()
+        };
+        let unseeded = ClusterConfig::default().with_k(street, 4);
+        let seeded = unseeded.clone().with_seed(7);
+        assert!(centroids(seeded.clone()) == centroids(seeded));
+        assert!(centroids(unseeded.clone()) == centroids(unseeded));
+    }
+
+    #[test]
+    fn k_comes_from_config() {
+        let street = Street::Turn;
+        let points = (0..16)
+            .map(|_| Observation::from(street))
+            .map(Histogram::from)
+            .collect::<Vec<Histogram>>();
+        let layer = |k: usize| {
+            fixture(
+                street,
+                Vec::default(),
+                points.clone(),
+                ClusterConfig::default()
+                    .with_seed(7)
+                    .with_k(street, k)
+                    .with_t(street, 2),
+            )
+            .cluster()
+        };
+        assert!(layer(2).kmeans.len() == 2);
+        assert!(layer(5).kmeans.len() == 5);
+        assert!(layer(5).metric(None).n() == 5 * 4 / 2);
+    }
+
+    #[test]
+    fn centroid_pairs_never_collide() {
+        for street in [Street::Flop, Street::Turn] {
+            for k in [street.k(), 400] {
+                assert!(Layer::triangle(street, k).len() == k * (k - 1) / 2);
+            }
+        }
+    }
+
+    #[test]
+    fn metric_has_every_pair() {
+        let street = Street::Turn;
+        let k = 8;
+        let layer = fixture(
+            street,
+            (0..k)
+                .map(|_| Observation::from(street))
+                .map(Histogram::from)
+                .collect::<Vec<Histogram>>(),
+            Vec::default(),
+            ClusterConfig::default(),
+        );
+        let metric = layer.metric(None);
+        assert!(metric.n() == k * (k - 1) / 2);
+        for i in 0..k {
+            for j in 0..i {
+                let ref a = layer.abstraction(i);
+                let ref b = layer.abstraction(j);
+                assert!(metric.distance(a, b) == metric.distance(b, a));
+            }
+        }
+    }
+
+    #[test]
+    fn metric_cache_hits() {
+        let street = Street::Turn;
+        let kmeans = (0..4)
+            .map(|_| Observation::from(street))
+            .map(Histogram::from)
+            .collect::<Vec<Histogram>>();
+        let mut layer = fixture(street, kmeans, Vec::default(), ClusterConfig::default());
+        let n = 4 * 3 / 2;
+        let first = layer.metric(None);
+        assert!(layer.cache.lock().unwrap().hits() == 0);
+        let again = layer.metric(None);
+        assert!(layer.cache.lock().unwrap().hits() == n);
+        for i in 0..4 {
+            for j in 0..i {
+                let ref a = layer.abstraction(i);
+                let ref b = layer.abstraction(j);
+                assert!(first.distance(a, b) == again.distance(a, b));
+            }
+        }
+        layer.kmeans[0] = Histogram::from(Observation::from(street));
+        layer.metric(None);
+        assert!(layer.cache.lock().unwrap().len() == n + 3);
+    }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn metric_cache_survives_rebuild() {
+        let street = Street::Turn;
+        let ref path = std::env::temp_dir()
+            .join("robopoker-emd-cache")
+            .to_string_lossy()
+            .to_string();
+        let kmeans = (0..4)
+            .map(|_| Observation::from(street))
+            .map(Histogram::from)
+            .collect::<Vec<Histogram>>();
+        let layer = |cache: Cache| Layer {
+            cache: Mutex::new(cache),
+            ..fixture(
+                street,
+                kmeans.clone(),
+                Vec::default(),
+                ClusterConfig::default(),
+            )
+        };
+        let first = layer(Cache::default());
+        let before = first.metric(None);
+        first.cache.lock().unwrap().save(path);
+        let again = layer(Cache::load(path));
+        let after = again.metric(None);
+        assert!(again.cache.lock().unwrap().hits() == 4 * 3 / 2);
+        for i in 0..4 {
+            for j in 0..i {
+                let ref a = again.abstraction(i);
+                let ref b = again.abstraction(j);
+                assert!(before.distance(a, b) == after.distance(a, b));
+            }
+        }
+        std::fs::remove_file(path).ok();
+    }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn interrupted_metric_resumes() {
+        let street = Street::Turn;
+        let ref path = std::env::temp_dir()
+            .join("robopoker-emd-resume")
+            .to_string_lossy()
+            .to_string();
+        let kmeans = (0..5)
+            .map(|_| Observation::from(street))
+            .map(Histogram::from)
+            .collect::<Vec<Histogram>>();
+        let layer = |kmeans: &[Histogram], cache: Cache| Layer {
+            cache: Mutex::new(cache),
+            ..fixture(
+                street,
+                kmeans.to_vec(),
+                Vec::default(),
+                ClusterConfig::default(),
+            )
+        };
+        // a build cut short after the pairs among three centroids
+        layer(&kmeans[..3], Cache::default()).metric(Some(path));
+        let resumed = layer(&kmeans, Cache::load(path));
+        let metric = resumed.metric(Some(path));
+        let fresh = layer(&kmeans, Cache::default()).metric(None);
+        assert!(resumed.cache.lock().unwrap().hits() == 3 * 2 / 2);
+        assert!(Cache::load(path).len() == 5 * 4 / 2);
+        for i in 0..5 {
+            for j in 0..i {
+                let ref a = resumed.abstraction(i);
+                let ref b = resumed.abstraction(j);
+                assert!(metric.distance(a, b) == fresh.distance(a, b));
+            }
+        }
+        std::fs::remove_file(path).ok();
+    }
+}
@@ -31,6 +31,23 @@ impl Lookup {
             .cloned()
             .expect(&format!("precomputed abstraction missing for {obs}"))
     }
+    /// the abstraction of an observation, if it was precomputed
+    pub fn get(&self, obs: &Observation) -> Option<Abstraction> {
+        self.0.get(&Isomorphism::from(*obs)).copied()
+    }
+    /// confirm every canonical isomorphism of the street is present,
+    /// and nothing else is. Err holds the number of missing entries
+    pub fn validate(&self, street: Street) -> Result<(), usize> {
+        let missing = IsomorphismIterator::from(street)
+            .filter(|iso| !self.0.contains_key(iso))
+            .count();
+        let expected = street.n_isomorphisms();
+        match (missing, self.0.len()) {
+            (0, n) if n == expected => Ok(()),
+            (0, n) => Err(n.abs_diff(expected)),
+            (m, _) => Err(m),
+        }
+    }
     #[cfg(feature = "native")]
     /// generate the entire space of inner layers
     pub fn projections(&self) -> Vec<Histogram> {
@@ -42,25 +59,217 @@ impl Lookup {
             .map(|inner| self.future(&inner))
             .collect::<Vec<Histogram>>()
     }
+    #[cfg(feature = "native")]
+    /// equity abstractions for River isomorphisms, enumerated in parallel.
+    /// the expensive showdown evaluation is embarrassingly parallel,
+    /// so rayon collects straight into the table with no coordination
+    pub fn river(isomorphisms: impl Iterator<Item = Isomorphism>) -> Self {
+        use rayon::iter::IntoParallelIterator;
+        use rayon::iter::ParallelIterator;
+        isomorphisms
+            .collect::<Vec<Isomorphism>>()
+            .into_par_iter()
+            .map(|iso| (iso, Abstraction::from(iso.0.equity())))
+            .collect::<BTreeMap<_, _>>()
+            .into()
+    }
+    #[cfg(feature = "native")]
+    /// Monte-Carlo equity abstractions for River isomorphisms, each
+    /// from samples villain holdings. the sampler is seeded by the
+    /// isomorphism, so rebuilding with the same samples agrees
+    pub fn approximate(isomorphisms: impl Iterator<Item = Isomorphism>, samples: usize) -> Self {
+        use super::equity::Equity;
+        use rand::rngs::SmallRng;
+        use rand::SeedableRng;
+        use rayon::iter::IntoParallelIterator;
+        use rayon::iter::ParallelIterator;
+        isomorphisms
+            .collect::<Vec<Isomorphism>>()
+            .into_par_iter()
+            .map(|iso| (iso, SmallRng::seed_from_u64(i64::from(iso) as u64)))
+            .map(|(iso, ref mut rng)| (iso, Equity::approximate(&iso.0, samples, rng)))
+            .map(|(iso, p)| (iso, Abstraction::from(f32::from(p))))
+            .collect::<BTreeMap<_, _>>()
+            .into()
+    }
     /// distribution over potential next states. this "layer locality" is what
     /// makes imperfect recall hierarchical kmeans nice
-    fn future(&self, iso: &Isomorphism) -> Histogram {
+    pub fn future(&self, iso: &Isomorphism) -> Histogram {
         assert!(iso.0.street() != Street::Rive);
-        iso.0
-            .children()
-            .map(|o| self.lookup(&o))
-            .collect::<Vec<Abstraction>>()
-            .into()
+        let histogram = Histogram::from(
+            iso.0
+                .children()
+                .map(|o| self.lookup(&o))
+                .collect::<Vec<Abstraction>>(),
+        );
+        debug_assert!(histogram.mass() == iso.0.street().n_children());
+        debug_assert!(histogram.n() <= iso.0.street().next().n_abstractions());
+        histogram
+    }
+    /// invert the table: every Isomorphism assigned to each Abstraction
+    pub fn members(&self) -> BTreeMap<Abstraction, Vec<Isomorphism>> {
+        self.0
+            .iter()
+            .fold(BTreeMap::new(), |mut members, (iso, abs)| {
+                members.entry(*abs).or_insert_with(Vec::new).push(*iso);
+                members
+            })
+    }
+    /// every Isomorphism assigned to one Abstraction
+    pub fn members_of<'a>(&'a self, abs: &'a Abstraction) -> impl Iterator<Item = &'a Isomorphism> {
+        self.0
+            .iter()
+            .filter(move |(_, a)| *a == abs)
+            .map(|(iso, _)| iso)
+    }
+    /// textual health check of a learned street. cluster count,
+    /// size statistics and quantiles, and a few example Observations
+    /// per cluster. River clusters are just equity bins, but the
+    /// same summary applies. an empty Lookup has nothing to report.
+    pub fn summary(&self) -> String {
+        const EXAMPLES: usize = 3;
+        if self.0.is_empty() {
+            return String::new();
+        }
+        let street = self.street();
+        let members = self.members();
+        let mut sizes = members.values().map(Vec::len).collect::<Vec<usize>>();
+        sizes.sort();
+        let n = sizes.len();
+        let quantile = |q: f32| sizes[((n - 1) as f32 * q).round() as usize];
+        let mean = sizes.iter().sum::<usize>() as f32 / n as f32;
+        let mut lines = vec![
+            format!("street     {}", street),
+            format!("clusters   {} of {}", n, street.n_abstractions()),
+            format!("empty      {}", street.n_abstractions().saturating_sub(n)),
+            format!("min        {}", sizes[0]),
+            format!("max        {}", sizes[n - 1]),
+            format!("mean       {:.2}", mean),
+            format!(
+                "quantiles  p10 {} p50 {} p90 {}",
+                quantile(0.1),
+                quantile(0.5),
+                quantile(0.9)
+            ),
+        ];
+        lines.extend(members.iter().map(|(abs, isos)| {
+            let examples = isos
+                .iter()
+                .take(EXAMPLES)
+                .map(|iso| iso.0.to_string())
+                .collect::<Vec<String>>()
+                .join(", ");
+            format!("{} {:>8}  {}", abs, isos.len(), examples)
+        }));
+        lines.join("\n")
     }
     fn street(&self) -> Street {
         self.0.keys().next().expect("non empty").0.street()
     }
+    #[cfg(feature = "native")]
+    /// write a PGCOPY lookup file
+    fn write(&self, path: &str) {
+        const N_FIELDS: u16 = 2;
+        use crate::save::upload::Table;
+        use byteorder::WriteBytesExt;
+        use byteorder::BE;
+        use std::io::Write;
+        let ref mut file = crate::save::upload::writer(path).expect(&format!("touch {}", path));
+        file.write_all(Self::header()).expect("header");
+        for (Isomorphism(obs), abs) in self.0.iter() {
+            file.write_u16::<BE>(N_FIELDS).unwrap();
+            file.write_u32::<BE>(size_of::<i64>() as u32).unwrap();
+            file.write_i64::<BE>(i64::from(*obs)).unwrap();
+            file.write_u32::<BE>(size_of::<i64>() as u32).unwrap();
+            file.write_i64::<BE>(i64::from(*abs)).unwrap();
+        }
+        crate::save::upload::trailer(file).expect("trailer");
+    }
+    #[cfg(feature = "native")]
+    /// parse a PGCOPY lookup file. canonicalization never maps two
+    /// rows to the same Isomorphism, so any repeated key means the
+    /// file is corrupt, and Err holds every one seen more than once.
+    fn read(path: &str) -> Result<Self, crate::save::upload::LoadError> {
+        use crate::save::upload::LoadError;
+        let ref mut reader = crate::save::upload::open(path)?;
+        let mut lookup = BTreeMap::new();
+        let mut duplicates = Vec::new();
+        crate::save::upload::header(reader)?;
+        while let Some((observation, abstraction)) = Self::record(reader)? {
+            if lookup.insert(observation, abstraction).is_some() {
+                duplicates.push(i64::from(observation));
+            }
+        }
+        match duplicates.len() {
+            0 => Ok(Self(lookup)),
+            _ => Err(LoadError::Duplicates(duplicates)),
+        }
+    }
+    #[cfg(feature = "native")]
+    /// lazily parse the rows of a PGCOPY lookup file, in file order.
+    /// only the reader's buffer is ever held in memory
+    fn rows(path: &str) -> impl Iterator<Item = (Isomorphism, Abstraction)> {
+        let mut reader = crate::save::upload::open(path)
+            .and_then(|mut reader| crate::save::upload::header(&mut reader).map(|_| reader))
+            .unwrap_or_else(|e| panic!("{}: {}", path, e));
+        let path = path.to_string();
+        std::iter::from_fn(move || {
+            Self::record(&mut reader).unwrap_or_else(|e| panic!("{}: {}", path, e))
+        })
+    }
+    #[cfg(feature = "native")]
+    /// parse the next row, or None at the trailer
+    fn record(
+        reader: &mut crate::save::upload::Checked<impl std::io::Read>,
+    ) -> Result<Option<(Isomorphism, Abstraction)>, crate::save::upload::LoadError> {
+        use crate::save::upload::row;
+        use crate::save::upload::width;
+        use byteorder::ReadBytesExt;
+        use byteorder::BE;
+        match row(reader, 2)? {
+            false => Ok(None),
+            true => {
+                width(reader, 8)?;
+                let iso = reader.read_i64::<BE>()?;
+                width(reader, 8)?;
+                let abs = reader.read_i64::<BE>()?;
+                Ok(Some((Isomorphism::from(iso), Abstraction::from(abs))))
+            }
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    proptest::proptest! {
+        #[test]
+        fn pgcopy_round_trip(
+            rows in proptest::collection::vec(
+                (proptest::sample::subsequence((0..52u8).collect::<Vec<u8>>(), 5), 0..Street::Flop.k()),
+                0..64,
+            )
+        ) {
+            use crate::cards::card::Card;
+            use crate::cards::hand::Hand;
+            let ref path = std::env::temp_dir()
+                .join("robopoker-proptest.lookup")
+                .to_string_lossy()
+                .to_string();
+            let hand = |cards: &[u8]| Hand::from(cards.iter().copied().map(Card::from).collect::<Vec<Card>>());
+            let save = Lookup(
+                rows.iter()
+                    .map(|(cards, k)| (Observation::from((hand(&cards[..2]), hand(&cards[2..]))), *k))
+                    .map(|(obs, k)| (Isomorphism::from(obs), Abstraction::from((Street::Flop, k))))
+                    .collect(),
+            );
+            save.write(path);
+            let load = Lookup::read(path).expect("no duplicates");
+            proptest::prop_assert!(save.0 == load.0);
+        }
+    }
+
     #[ignore]
     #[test]
     fn persistence() {
@@ -68,12 +277,188 @@ mod tests {
         let street = Street::Pref;
         let lookup = Lookup::grow(street);
         lookup.save();
-        let loaded = Lookup::load(street);
+        let loaded = Lookup::load_or_panic(street);
         std::iter::empty()
             .chain(lookup.0.iter().zip(loaded.0.iter()))
             .chain(loaded.0.iter().zip(lookup.0.iter()))
             .all(|((s1, l1), (s2, l2))| s1 == s2 && l1 == l2);
     }
+
+    /// bins of a projected Histogram are abstractions of the next street,
+    /// and there is one sample per child Observation
+    fn assert_binned(inner: Street, assign: impl Fn(&Observation) -> Abstraction) {
+        let iso = Isomorphism::from(Observation::from(inner));
+        let outer = inner.next();
+        let lookup = Lookup::from(
+            iso.0
+                .children()
+                .map(|o| (Isomorphism::from(o), assign(&o)))
+                .collect::<BTreeMap<Isomorphism, Abstraction>>(),
+        );
+        let histogram = lookup.future(&iso);
+        assert!(histogram.mass() == inner.n_children());
+        assert!(histogram.n() <= outer.n_abstractions());
+        assert!(histogram.support().all(|a| a.street() == outer));
+    }
+
+    #[test]
+    fn binning_turn_into_river_equity() {
+        assert_binned(Street::Turn, |o| Abstraction::from(o.equity()));
+    }
+
+    #[test]
+    fn binning_flop_into_turn_clusters() {
+        let k = Street::Turn.k();
+        assert_binned(Street::Flop, |o| {
+            Abstraction::from((Street::Turn, i64::from(Isomorphism::from(*o)) as usize % k))
+        });
+    }
+
+    #[test]
+    fn river_matches_sequential() {
+        let isomorphisms = (0..64)
+            .map(|_| Observation::from(Street::Rive))
+            .map(Isomorphism::from)
+            .collect::<Vec<Isomorphism>>();
+        let parallel = Lookup::river(isomorphisms.iter().copied());
+        let sequential = isomorphisms
+            .iter()
+            .map(|iso| (*iso, Abstraction::from(iso.0.equity())))
+            .collect::<BTreeMap<Isomorphism, Abstraction>>();
+        assert!(parallel.0 == sequential);
+    }
+
+    #[test]
+    fn approximate_tracks_river() {
+        let isomorphisms = (0..16)
+            .map(|_| Observation::from(Street::Rive))
+            .map(Isomorphism::from)
+            .collect::<Vec<Isomorphism>>();
+        let exact = Lookup::river(isomorphisms.iter().copied());
+        let approx = Lookup::approximate(isomorphisms.iter().copied(), 4096);
+        assert!(approx.0 == Lookup::approximate(isomorphisms.iter().copied(), 4096).0);
+        for iso in isomorphisms.iter() {
+            let x = crate::Equity::from(exact.lookup(&iso.0));
+            let y = crate::Equity::from(approx.lookup(&iso.0));
+            assert!((x - y).abs() < 0.05, "{} !~ {}", x, y);
+        }
+    }
+
+    #[test]
+    fn independent_builds_serialize_identically() {
+        use crate::save::upload::Table;
+        use rand::seq::SliceRandom;
+        let ref mut rng = rand::thread_rng();
+        let path = |name: &str| {
+            std::env::temp_dir()
+                .join(format!("robopoker-lookup-{}", name))
+                .to_string_lossy()
+                .to_string()
+        };
+        let (ref a, ref b) = (path("stable-a"), path("stable-b"));
+        let mut pairs = Lookup::grow(Street::Pref).0.into_iter().collect::<Vec<_>>();
+        pairs.shuffle(rng);
+        Lookup::from(pairs.iter().copied().collect::<BTreeMap<_, _>>()).write(a);
+        pairs.shuffle(rng);
+        Lookup::from(pairs.iter().copied().collect::<BTreeMap<_, _>>()).write(b);
+        assert!(std::fs::read(a).unwrap() == std::fs::read(b).unwrap());
+        // rows on disk ascend by the i64 encoding of their Isomorphism,
+        // between the header and the trailer
+        let bytes = std::fs::read(a).unwrap();
+        let keys = bytes[Lookup::header().len()..bytes.len() - 2]
+            .chunks(Lookup::row())
+            .map(|row| i64::from_be_bytes(row[6..14].try_into().unwrap()))
+            .collect::<Vec<i64>>();
+        assert!(keys.len() == pairs.len());
+        assert!(keys.windows(2).all(|w| w[0] < w[1]));
+        std::fs::remove_file(a).ok();
+        std::fs::remove_file(b).ok();
+    }
+
+    #[test]
+    fn stream_matches_load() {
+        use crate::save::upload::Table;
+        let ref dir = std::env::temp_dir().join("robopoker-lookup-stream");
+        std::fs::create_dir_all(dir).unwrap();
+        Lookup::grow(Street::Pref).save_to(dir);
+        let streamed = Lookup::stream_from(dir, Street::Pref).collect::<Vec<_>>();
+        let loaded = BTreeMap::from(Lookup::load_from(dir, Street::Pref).unwrap());
+        assert!(streamed.len() == Street::Pref.n_isomorphisms());
+        assert!(streamed.into_iter().eq(loaded.into_iter()));
+        std::fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn read_reports_duplicates() {
+        use crate::save::upload::LoadError;
+        use crate::save::upload::Table;
+        use byteorder::WriteBytesExt;
+        use byteorder::BE;
+        use std::io::Write;
+        let ref path = std::env::temp_dir()
+            .join("robopoker-lookup-duplicates")
+            .to_string_lossy()
+            .to_string();
+        let lookup = Lookup::grow(Street::Pref);
+        let (first, abs) = lookup.0.iter().next().map(|(i, a)| (*i, *a)).unwrap();
+        let mut file = std::fs::File::create(path).unwrap();
+        file.write_all(Lookup::header()).unwrap();
+        for (iso, abs) in lookup.0.iter().chain(std::iter::once((&first, &abs))) {
+            file.write_u16::<BE>(2).unwrap();
+            file.write_u32::<BE>(8).unwrap();
+            file.write_i64::<BE>(i64::from(*iso)).unwrap();
+            file.write_u32::<BE>(8).unwrap();
+            file.write_i64::<BE>(i64::from(*abs)).unwrap();
+        }
+        file.write_u16::<BE>(Lookup::footer()).unwrap();
+        drop(file);
+        assert!(Lookup::read(path).err() == Some(LoadError::Duplicates(vec![i64::from(first)])));
+        std::fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn summary_reports_cluster_sizes() {
+        let lookup = Lookup::from(
+            IsomorphismIterator::from(Street::Pref)
+                .take(10)
+                .zip([0, 0, 0, 0, 0, 1, 1, 1, 2, 2])
+                .map(|(iso, k)| (iso, Abstraction::from((Street::Pref, k))))
+                .collect::<BTreeMap<Isomorphism, Abstraction>>(),
+        );
+        let summary = lookup.summary();
+        let line = |key: &str| {
+            summary
+                .lines()
+                .find(|line| line.starts_with(key))
+                .unwrap()
+                .split_whitespace()
+                .nth(1)
+                .unwrap()
+                .to_string()
+        };
+        assert!(line("clusters") == "3");
+        assert!(line("min") == "2");
+        assert!(line("max") == "5");
+        assert!(line("mean") == "3.33");
+        assert!(lookup.members().values().map(Vec::len).sum::<usize>() == 10);
+        assert!(Lookup::from(BTreeMap::new()).summary().is_empty());
+    }
+
+    #[test]
+    fn validate_complete() {
+        use crate::save::upload::Table;
+        let lookup = Lookup::grow(Street::Pref);
+        assert_eq!(lookup.validate(Street::Pref), Ok(()));
+    }
+
+    #[test]
+    fn validate_missing() {
+        use crate::save::upload::Table;
+        let mut lookup = Lookup::grow(Street::Pref);
+        let first = lookup.0.keys().next().cloned().unwrap();
+        lookup.0.remove(&first);
+        assert_eq!(lookup.validate(Street::Pref), Err(1));
+    }
 }
 
 #[cfg(feature = "native")]
@@ -137,15 +522,8 @@ impl crate::save::upload::Table for Lookup {
     /// abstractions for River are calculated once via obs.equity
     /// abstractions for Preflop are cequivalent to just enumerating isomorphisms
     fn grow(street: Street) -> Self {
-        use rayon::iter::IntoParallelIterator;
-        use rayon::iter::ParallelIterator;
         match street {
-            Street::Rive => IsomorphismIterator::from(Street::Rive)
-                .collect::<Vec<_>>()
-                .into_par_iter()
-                .map(|iso| (iso, Abstraction::from(iso.0.equity())))
-                .collect::<BTreeMap<_, _>>()
-                .into(),
+            Street::Rive => Self::river(IsomorphismIterator::from(Street::Rive)),
             Street::Pref => IsomorphismIterator::from(Street::Pref)
                 .enumerate()
                 .map(|(k, iso)| (iso, Abstraction::from((Street::Pref, k))))
@@ -154,56 +532,55 @@ impl crate::save::upload::Table for Lookup {
             Street::Flop | Street::Turn => panic!("lookup must be learned via layer for {street}"),
         }
     }
-    fn load(street: Street) -> Self {
-        let ref path = Self::path(street);
+    fn load(street: Street) -> Result<Self, crate::save::upload::LoadError> {
+        Self::load_from(&crate::save::upload::directory(), street)
+    }
+    fn save(&self) {
+        self.save_to(&crate::save::upload::directory())
+    }
+}
+
+#[cfg(feature = "native")]
+impl Lookup {
+    /// read from disk under a given directory
+    pub fn load_from(
+        dir: &std::path::Path,
+        street: Street,
+    ) -> Result<Self, crate::save::upload::LoadError> {
+        use crate::save::upload::Table;
+        let ref path = Self::path_in(dir, street);
         log::info!("{:<32}{:<32}", "loading     lookup", path);
-        use byteorder::ReadBytesExt;
-        use byteorder::BE;
-        use std::fs::File;
-        use std::io::BufReader;
-        use std::io::Read;
-        use std::io::Seek;
-        use std::io::SeekFrom;
-        let ref file = File::open(path).expect(&format!("open {}", path));
-        let mut lookup = BTreeMap::new();
-        let mut reader = BufReader::new(file);
-        let ref mut buffer = [0u8; 2];
-        reader.seek(SeekFrom::Start(19)).expect("seek past header");
-        while reader.read_exact(buffer).is_ok() {
-            match u16::from_be_bytes(buffer.clone()) {
-                2 => {
-                    assert!(8 == reader.read_u32::<BE>().expect("observation length"));
-                    let iso = reader.read_i64::<BE>().expect("read observation");
-                    assert!(8 == reader.read_u32::<BE>().expect("abstraction length"));
-                    let abs = reader.read_i64::<BE>().expect("read abstraction");
-                    let observation = Isomorphism::from(iso);
-                    let abstraction = Abstraction::from(abs);
-                    lookup.insert(observation, abstraction);
+        Self::read(path).inspect_err(|e| {
+            if let crate::save::upload::LoadError::Duplicates(ref keys) = e {
+                for iso in keys.iter().copied().map(Isomorphism::from) {
+                    log::error!("duplicate isomorphism in {}: {}", path, iso);
                 }
-                0xFFFF => break,
-                n => panic!("unexpected number of fields: {}", n),
             }
-        }
-        Self(lookup)
+        })
     }
-    fn save(&self) {
-        const N_FIELDS: u16 = 2;
-        let street = self.street();
-        let ref path = Self::path(street);
-        let ref mut file = File::create(path).expect(&format!("touch {}", path));
-        use byteorder::WriteBytesExt;
-        use byteorder::BE;
-        use std::fs::File;
-        use std::io::Write;
+    /// every row of a street's lookup on disk, without ever building
+    /// the whole table. rows ascend by Isomorphism, as written, and
+    /// are not checked for duplicates, so fold over them when a
+    /// street is too large to load_from.
+    pub fn stream(street: Street) -> impl Iterator<Item = (Isomorphism, Abstraction)> {
+        Self::stream_from(&crate::save::upload::directory(), street)
+    }
+    /// stream a street's lookup from under a given directory
+    pub fn stream_from(
+        dir: &std::path::Path,
+        street: Street,
+    ) -> impl Iterator<Item = (Isomorphism, Abstraction)> {
+        use crate::save::upload::Table;
+        let ref path = Self::path_in(dir, street);
+        log::info!("{:<32}{:<32}", "streaming   lookup", path);
+        Self::rows(path)
+    }
+    /// write to disk under a given directory
+    pub fn save_to(&self, dir: &std::path::Path) {
+        use crate::save::upload::Table;
+        let ref path = Self::path_in(dir, self.street());
         log::info!("{:<32}{:<32}", "saving      lookup", path);
-        file.write_all(Self::header()).expect("header");
-        for (Isomorphism(obs), abs) in self.0.iter() {
-            file.write_u16::<BE>(N_FIELDS).unwrap();
-            file.write_u32::<BE>(size_of::<i64>() as u32).unwrap();
-            file.write_i64::<BE>(i64::from(*obs)).unwrap();
-            file.write_u32::<BE>(size_of::<i64>() as u32).unwrap();
-            file.write_i64::<BE>(i64::from(*abs)).unwrap();
-        }
-        file.write_u16::<BE>(Self::footer()).expect("trailer");
+        self.write(path);
+        crate::save::upload::stamp(path);
     }
 }
@@ -1,5 +1,7 @@
 use super::equity::Equity;
+use super::exact::ExactTransport;
 use super::sinkhorn::Sinkhorn;
+use super::sinkhorn::SinkhornConfig;
 use crate::cards::street::Street;
 use crate::clustering::abstraction::Abstraction;
 use crate::clustering::histogram::Histogram;
@@ -12,8 +14,16 @@ use std::collections::BTreeMap;
 /// Distance metric for kmeans clustering.
 /// encapsulates distance between `Abstraction`s of the "previous" hierarchy,
 /// as well as: distance between `Histogram`s of the "current" hierarchy.
-#[derive(Default)]
-pub struct Metric(BTreeMap<Pair, Energy>);
+/// carries its Street explicitly, since Pairs of abstractions
+/// XOR away the street they were drawn from.
+pub struct Metric(BTreeMap<Pair, Energy>, Street);
+
+/// the river has no learned metric to speak of
+impl Default for Metric {
+    fn default() -> Self {
+        Self(BTreeMap::default(), Street::Rive)
+    }
+}
 
 impl Measure for Metric {
     type X = Abstraction;
@@ -41,40 +51,254 @@ impl Metric {
     }
 
     pub fn emd(&self, source: &Histogram, target: &Histogram) -> Energy {
+        self.emd_with(source, target, SinkhornConfig::default())
+    }
+
+    /// EMD with these Sinkhorn hyperparameters for Learned abstractions.
+    /// Percent abstractions are exact, so they ignore the config, and
+    /// so do Learned histograms small enough to solve exactly.
+    pub fn emd_with(
+        &self,
+        source: &Histogram,
+        target: &Histogram,
+        config: SinkhornConfig,
+    ) -> Energy {
         match source.peek() {
-            Abstraction::Learned(_) => Sinkhorn::from((source, target, self)).minimize().cost(),
+            Abstraction::Learned(_) if Self::exact(source, target) => {
+                ExactTransport::from((source, target, self))
+                    .minimize()
+                    .cost()
+            }
+            Abstraction::Learned(_) => Sinkhorn::from((source, target, self))
+                .with_config(config)
+                .minimize()
+                .cost(),
             Abstraction::Percent(_) => Equity::variation(source, target),
             Abstraction::Preflop(_) => unreachable!("no preflop emd"),
         }
     }
 
-    /// we're assuming tht the street is being generated AFTER the learned kmeans
-    /// cluster distance calculation. so we should have (Street::K() choose 2)
-    /// entreis in our abstraction pair lookup table.
-    /// if this is off by just a few then it probably means a bunch of collisions
-    /// maybe i should determinsitcally seed kmeans process, could be cool for reproducability too
-    ///
-    /// TODO
-    ///
-    /// determine street dynamiccaly by checking for existence of XOR'ed abstraction pairs using
-    /// Abstraction::From(Street, Index)
-    ///
-    /// it's also not great that we are FORCED to have different number of abstractions
-    /// clusters K means for each street to avoid nC2 collisions !!
-    /// we should either just store Street as Self.1 or determine from XOR hits what street we're on
-    /// whichever solution should work with test case so we don't have to remove test case
-    /// to not overwrite existing metric. we like overwriting river.metric bc it can be empty
-    fn street(&self) -> Street {
-        fn choose_2(k: usize) -> usize {
-            k * (k.saturating_sub(1)) / 2
+    /// distance over Turn Histograms in the joint space of River
+    /// equity distribution and potential. weight is the share of
+    /// the distance that comes from potential, the rest from EMD.
+    /// both features live on the unit interval, so neither swamps
+    /// the other unless weighted to.
+    pub fn combined(&self, source: &Histogram, target: &Histogram, weight: Energy) -> Energy {
+        assert!((0. ..=1.).contains(&weight), "potential weight in [0, 1]");
+        (1. - weight) * self.emd(source, target)
+            + weight * (source.potential() - target.potential()).abs()
+    }
+
+    /// attribute the EMD between two Histograms to the source bins,
+    /// according to how much mass each bin ships and how far.
+    /// entries sum to emd(source, target). Percent abstractions use the
+    /// exact monotone plan. for Learned abstractions the plan is exact
+    /// over small supports, and otherwise comes from Sinkhorn, so the
+    /// split is only as exact as the entropic approximation.
+    pub fn emd_breakdown(
+        &self,
+        source: &Histogram,
+        target: &Histogram,
+    ) -> Vec<(Abstraction, Energy)> {
+        match source.peek() {
+            Abstraction::Learned(_) if Self::exact(source, target) => {
+                ExactTransport::from((source, target, self))
+                    .minimize()
+                    .breakdown()
+            }
+            Abstraction::Learned(_) => Sinkhorn::from((source, target, self))
+                .minimize()
+                .breakdown(),
+            Abstraction::Percent(_) => Equity::breakdown(source, target),
+            Abstraction::Preflop(_) => unreachable!("no preflop emd"),
+        }
+    }
+
+    /// whether the combined support is small enough
+    /// that the transportation simplex beats Sinkhorn
+    fn exact(source: &Histogram, target: &Histogram) -> bool {
+        source.n() + target.n() <= crate::EXACT_TRANSPORT_SUPPORT
+    }
+
+    /// whether two Metrics share no Pair encodings,
+    /// so that both can be consulted without ambiguity
+    pub fn disjoint(&self, other: &Self) -> bool {
+        self.0.keys().all(|pair| !other.0.contains_key(pair))
+    }
+
+    /// number of Pairs measured. k centroids make k choose 2
+    pub fn n(&self) -> usize {
+        self.0.len()
+    }
+
+    /// the k Abstractions nearest to abs, nearest first. abs is
+    /// no distance from itself, so it is left out. a Pair only
+    /// names abs's partner if that partner is a well-formed
+    /// Abstraction of our Street, so the rest are skipped.
+    pub fn neighbors(&self, abs: &Abstraction, k: usize) -> Vec<(Abstraction, Energy)> {
+        assert!(abs.street() == self.street());
+        let mut neighbors = self
+            .0
+            .iter()
+            .map(|(pair, distance)| (pair.other(abs), *distance))
+            .filter(|(other, _)| other != abs)
+            .filter(|(other, _)| *other == Abstraction::from((self.street(), other.index())))
+            .collect::<Vec<(Abstraction, Energy)>>();
+        neighbors.sort_by(|(a, x), (b, y)| x.partial_cmp(y).unwrap().then(a.cmp(b)));
+        neighbors.truncate(k);
+        neighbors
+    }
+
+    /// number of Abstractions measured, which may exceed the
+    /// Street's default K. k of them make k choose 2 Pairs
+    pub fn k(&self) -> usize {
+        (1..).find(|k| k * (k - 1) / 2 >= self.n()).expect("finite")
+    }
+
+    /// the street whose abstractions we measure
+    pub fn street(&self) -> Street {
+        self.1
+    }
+
+    /// write one abstraction_i,abstraction_j,distance row per Pair,
+    /// under a header, for inspection outside of Postgres. rows keep
+    /// the larger index first, as Layer::metric measures them.
+    pub fn to_csv(&self, mut w: impl std::io::Write) -> std::io::Result<()> {
+        writeln!(w, "abstraction_i,abstraction_j,distance")?;
+        for (pair, distance) in self.0.iter() {
+            let (a, b) = pair.decompose(self.street(), self.k()).ok_or_else(|| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("{:?} is no pair of {} abstractions", pair, self.street()),
+                )
+            })?;
+            writeln!(w, "{},{},{}", a, b, distance)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "native")]
+impl Metric {
+    /// parse a single-street PGCOPY metric file
+    fn read(path: &str, street: Street) -> Result<Self, crate::save::upload::LoadError> {
+        Self::decode(crate::save::upload::open(path)?, street)
+    }
+    /// parse single-street PGCOPY metric rows, header to trailer
+    fn decode(
+        mut reader: crate::save::upload::Checked<impl std::io::Read>,
+        street: Street,
+    ) -> Result<Self, crate::save::upload::LoadError> {
+        use crate::save::upload::header;
+        use crate::save::upload::row;
+        use crate::save::upload::width;
+        use byteorder::ReadBytesExt;
+        use byteorder::BE;
+        let reader = &mut reader;
+        let mut metric = BTreeMap::new();
+        header(reader)?;
+        while row(reader, 2)? {
+            width(reader, 8)?;
+            let pair = reader.read_i64::<BE>()?;
+            width(reader, 4)?;
+            let dist = reader.read_f32::<BE>()?;
+            metric.insert(Pair::from(pair), dist);
         }
-        match self.0.len() {
-            n if n == choose_2(Street::Rive.k()) => Street::Rive,
-            n if n == choose_2(Street::Turn.k()) => Street::Turn,
-            n if n == choose_2(Street::Flop.k()) => Street::Flop,
-            n if n == choose_2(Street::Pref.k()) => Street::Pref,
-            _ => Street::Rive, // assertion of no-collisions is convenient for tests
+        Ok(Self(metric, street))
+    }
+    /// write a single-street PGCOPY metric file
+    fn write(&self, path: &str) {
+        const N_FIELDS: u16 = 2;
+        use crate::save::upload::Table;
+        use byteorder::WriteBytesExt;
+        use byteorder::BE;
+        use std::io::Write;
+        let ref mut file = crate::save::upload::writer(path).expect(&format!("touch {}", path));
+        file.write_all(Self::header()).expect("header");
+        for (pair, distance) in self.0.iter() {
+            file.write_u16::<BE>(N_FIELDS).unwrap();
+            file.write_u32::<BE>(size_of::<i64>() as u32).unwrap();
+            file.write_i64::<BE>(i64::from(*pair)).unwrap();
+            file.write_u32::<BE>(size_of::<f32>() as u32).unwrap();
+            file.write_f32::<BE>(*distance).unwrap();
+        }
+        crate::save::upload::trailer(file).expect("trailer");
+    }
+    /// read from disk under a given directory
+    pub fn load_from(
+        dir: &std::path::Path,
+        street: Street,
+    ) -> Result<Self, crate::save::upload::LoadError> {
+        use crate::save::upload::Table;
+        let ref path = Self::path_in(dir, street);
+        log::info!("{:<32}{:<32}", "loading     metric", path);
+        Self::read(path, street)
+    }
+    /// write to disk under a given directory
+    pub fn save_to(&self, dir: &std::path::Path) {
+        use crate::save::upload::Table;
+        let ref path = Self::path_in(dir, self.street());
+        log::info!("{:<32}{:<32}", "saving      metric", path);
+        self.write(path);
+        crate::save::upload::stamp(path);
+    }
+    /// write every street's Metric into one PGCOPY file for shipping.
+    /// Pairs of different streets can collide under XOR,
+    /// so each row carries its Street explicitly.
+    pub fn save_combined(path: &str, metrics: &BTreeMap<Street, Metric>) {
+        const N_FIELDS: u16 = 3;
+        use crate::save::upload::Table;
+        use byteorder::WriteBytesExt;
+        use byteorder::BE;
+        use std::io::Write;
+        log::info!("{:<32}{:<32}", "saving      combined metric", path);
+        let ref mut file = crate::save::upload::writer(path).expect(&format!("touch {}", path));
+        file.write_all(Self::header()).expect("header");
+        for (street, metric) in metrics.iter() {
+            for (pair, distance) in metric.0.iter() {
+                file.write_u16::<BE>(N_FIELDS).unwrap();
+                file.write_u32::<BE>(size_of::<i16>() as u32).unwrap();
+                file.write_i16::<BE>(*street as i16).unwrap();
+                file.write_u32::<BE>(size_of::<i64>() as u32).unwrap();
+                file.write_i64::<BE>(i64::from(*pair)).unwrap();
+                file.write_u32::<BE>(size_of::<f32>() as u32).unwrap();
+                file.write_f32::<BE>(*distance).unwrap();
+            }
+        }
+        crate::save::upload::trailer(file).expect("trailer");
+    }
+    /// inverse of save_combined, split back out by Street
+    pub fn load_combined(path: &str) -> BTreeMap<Street, Metric> {
+        log::info!("{:<32}{:<32}", "loading     combined metric", path);
+        Self::read_combined(path).unwrap_or_else(|e| panic!("{}: {}", path, e))
+    }
+    /// parse a combined PGCOPY metric file
+    fn read_combined(
+        path: &str,
+    ) -> Result<BTreeMap<Street, Metric>, crate::save::upload::LoadError> {
+        use crate::save::upload::header;
+        use crate::save::upload::row;
+        use crate::save::upload::width;
+        use byteorder::ReadBytesExt;
+        use byteorder::BE;
+        let ref mut reader = crate::save::upload::open(path)?;
+        let mut metrics = BTreeMap::<Street, Metric>::new();
+        header(reader)?;
+        while row(reader, 3)? {
+            width(reader, 2)?;
+            let street = reader.read_i16::<BE>()?;
+            width(reader, 8)?;
+            let pair = reader.read_i64::<BE>()?;
+            width(reader, 4)?;
+            let dist = reader.read_f32::<BE>()?;
+            let street = Street::from(street as isize);
+            metrics
+                .entry(street)
+                .or_insert_with(|| Self(BTreeMap::default(), street))
+                .0
+                .insert(Pair::from(pair), dist);
         }
+        Ok(metrics)
     }
 }
 
@@ -129,69 +353,25 @@ impl crate::save::upload::Table for Metric {
         "
         .to_string()
     }
-    fn load(street: Street) -> Self {
-        let ref path = Self::path(street);
-        log::info!("{:<32}{:<32}", "loading     metric", path);
-        use byteorder::ReadBytesExt;
-        use byteorder::BE;
-        use std::fs::File;
-        use std::io::BufReader;
-        use std::io::Read;
-        use std::io::Seek;
-        use std::io::SeekFrom;
-        let ref file = File::open(path).expect(&format!("open {}", path));
-        let mut metric = BTreeMap::new();
-        let mut reader = BufReader::new(file);
-        let ref mut buffer = [0u8; 2];
-        reader.seek(SeekFrom::Start(19)).expect("seek past header");
-        while reader.read_exact(buffer).is_ok() {
-            match u16::from_be_bytes(buffer.clone()) {
-                2 => {
-                    reader.read_u32::<BE>().expect("pair length");
-                    let pair = reader.read_i64::<BE>().expect("read pair");
-                    reader.read_u32::<BE>().expect("distance length");
-                    let dist = reader.read_f32::<BE>().expect("read distance");
-                    metric.insert(Pair::from(pair), dist);
-                    continue;
-                }
-                0xFFFF => break,
-                n => panic!("unexpected number of fields: {}", n),
-            }
-        }
-        Self(metric)
+    fn load(street: Street) -> Result<Self, crate::save::upload::LoadError> {
+        Self::load_from(&crate::save::upload::directory(), street)
     }
     fn save(&self) {
-        const N_FIELDS: u16 = 2;
-        let street = self.street();
-        let ref path = Self::path(street);
-        let ref mut file = File::create(path).expect(&format!("touch {}", path));
-        use byteorder::WriteBytesExt;
-        use byteorder::BE;
-        use std::fs::File;
-        use std::io::Write;
-        log::info!("{:<32}{:<32}", "saving      metric", path);
-        file.write_all(Self::header()).expect("header");
-        for (pair, distance) in self.0.iter() {
-            file.write_u16::<BE>(N_FIELDS).unwrap();
-            file.write_u32::<BE>(size_of::<i64>() as u32).unwrap();
-            file.write_i64::<BE>(i64::from(*pair)).unwrap();
-            file.write_u32::<BE>(size_of::<f32>() as u32).unwrap();
-            file.write_f32::<BE>(*distance).unwrap();
-        }
-        file.write_u16::<BE>(Self::footer()).expect("trailer");
+        self.save_to(&crate::save::upload::directory())
     }
     fn grow(_: Street) -> Self {
         unreachable!("metric must be learned from kmeans clustering")
     }
 }
-impl From<BTreeMap<Pair, Energy>> for Metric {
-    fn from(metric: BTreeMap<Pair, Energy>) -> Self {
+impl From<(Street, BTreeMap<Pair, Energy>)> for Metric {
+    fn from((street, metric): (Street, BTreeMap<Pair, Energy>)) -> Self {
         let max = metric.values().copied().fold(f32::MIN_POSITIVE, f32::max);
         Self(
             metric
                 .into_iter()
                 .map(|(index, distance)| (index, distance / max))
                 .collect(),
+            street,
         )
     }
 }
@@ -203,6 +383,307 @@ mod tests {
     use crate::save::upload::Table;
     use crate::Arbitrary;
 
+    #[test]
+    fn emd_breakdown_blames_the_far_bin() {
+        let ref a = (0..4)
+            .map(|i| Abstraction::from((Street::Flop, i)))
+            .collect::<Vec<Abstraction>>();
+        let metric = Metric(
+            a.iter()
+                .enumerate()
+                .flat_map(|(i, x)| a.iter().enumerate().map(move |(j, y)| (i, x, j, y)))
+                .filter(|(i, _, j, _)| i < j)
+                .map(|(i, x, j, y)| (Pair::from((x, y)), 0.1 * (j - i) as Energy))
+                .collect(),
+            Street::Flop,
+        );
+        let source = Histogram::from(vec![a[0], a[0], a[1], a[1]]);
+        let target = Histogram::from(vec![a[0], a[0], a[3], a[3]]);
+        let breakdown = metric.emd_breakdown(&source, &target);
+        let total = breakdown.iter().map(|(_, e)| e).sum::<Energy>();
+        let (worst, _) = breakdown
+            .iter()
+            .max_by(|(_, x), (_, y)| x.partial_cmp(y).unwrap())
+            .unwrap();
+        assert!(*worst == a[1]);
+        assert!((total - metric.emd(&source, &target)).abs() < 1e-4);
+    }
+
+    #[test]
+    fn tighter_sinkhorn_tolerance_approaches_exact_emd() {
+        let ref a = (0..3)
+            .map(|i| Abstraction::from((Street::Flop, i)))
+            .collect::<Vec<Abstraction>>();
+        let metric = Metric(
+            BTreeMap::from([
+                (Pair::from((&a[0], &a[1])), 0.5),
+                (Pair::from((&a[1], &a[2])), 0.5),
+                (Pair::from((&a[0], &a[2])), 1.0),
+            ]),
+            Street::Flop,
+        );
+        let ref source = Histogram::from(vec![a[0], a[0], a[0], a[1], a[2], a[2]]);
+        let ref target = Histogram::from(vec![a[0], a[1], a[1], a[1], a[2], a[2]]);
+        let exact = 1. / 6.;
+        let loose = SinkhornConfig {
+            max_iters: 1,
+            tolerance: 1.,
+            ..SinkhornConfig::default()
+        };
+        let tight = SinkhornConfig {
+            max_iters: 1024,
+            tolerance: 1e-6,
+            ..SinkhornConfig::default()
+        };
+        let sinkhorn = |config| {
+            Sinkhorn::from((source, target, &metric))
+                .with_config(config)
+                .minimize()
+                .cost()
+        };
+        let loose = (sinkhorn(loose) - exact).abs();
+        let tight = (sinkhorn(tight) - exact).abs();
+        assert!(tight < loose, "{} !< {}", tight, loose);
+        assert!(tight < 1e-2, "{}", tight);
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn pgcopy_round_trip(
+            rows in proptest::collection::btree_map(
+                proptest::prelude::any::<i64>(),
+                -1e3 as Energy..1e3,
+                0..64,
+            )
+        ) {
+            let ref path = std::env::temp_dir()
+                .join("robopoker-proptest.metric")
+                .to_string_lossy()
+                .to_string();
+            let save = Metric(
+                rows.into_iter().map(|(k, v)| (Pair::from(k), v)).collect(),
+                Street::Turn,
+            );
+            save.write(path);
+            let load = Metric::read(path, Street::Turn).unwrap();
+            proptest::prop_assert!(save.0 == load.0);
+        }
+    }
+
+    #[test]
+    fn csv_names_both_abstractions() {
+        let a = |i: usize| Abstraction::from((Street::Turn, i));
+        let metric = Metric(
+            BTreeMap::from([
+                (Pair::from((&a(1), &a(0))), 0.25),
+                (Pair::from((&a(2), &a(0))), 1.0),
+                (Pair::from((&a(2), &a(1))), 0.5),
+            ]),
+            Street::Turn,
+        );
+        let mut csv = Vec::new();
+        metric.to_csv(&mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        let mut lines = csv.lines();
+        assert!(lines.next() == Some("abstraction_i,abstraction_j,distance"));
+        let rows = lines
+            .map(|line| line.split(',').collect::<Vec<&str>>())
+            .map(|row| {
+                (
+                    Abstraction::try_from(row[0]).unwrap(),
+                    Abstraction::try_from(row[1]).unwrap(),
+                    row[2].parse::<Energy>().unwrap(),
+                )
+            })
+            .map(|(x, y, d)| (x.index(), y.index(), d))
+            .collect::<Vec<_>>();
+        assert!(rows.len() == 3);
+        for (i, j, d) in [(1, 0, 0.25), (2, 0, 1.0), (2, 1, 0.5)] {
+            assert!(rows.contains(&(i, j, d)), "{:?}", rows);
+        }
+    }
+
+    #[test]
+    fn csv_splits_pairs_beyond_default_k() {
+        let k = Street::Turn.k() + 8;
+        let a = |i: usize| Abstraction::from((Street::Turn, i));
+        let metric = Metric(
+            (0..k)
+                .flat_map(|i| (0..i).map(move |j| (i, j)))
+                .map(|(i, j)| (Pair::from((&a(i), &a(j))), 1.))
+                .collect(),
+            Street::Turn,
+        );
+        assert!(metric.k() == k);
+        let mut csv = Vec::new();
+        metric.to_csv(&mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        assert!(csv
+            .lines()
+            .any(|line| line.starts_with(&a(k - 1).to_string())));
+    }
+
+    #[test]
+    fn neighbors_are_nearest_first() {
+        let a = |i: usize| Abstraction::from((Street::Turn, i));
+        let metric = Metric(
+            BTreeMap::from([
+                (Pair::from((&a(1), &a(0))), 0.25),
+                (Pair::from((&a(2), &a(0))), 1.0),
+                (Pair::from((&a(2), &a(1))), 0.5),
+                (Pair::from((&a(3), &a(0))), 0.75),
+                (Pair::from((&a(3), &a(1))), 0.125),
+                (Pair::from((&a(3), &a(2))), 0.625),
+            ]),
+            Street::Turn,
+        );
+        assert!(metric.neighbors(&a(0), 2) == vec![(a(1), 0.25), (a(3), 0.75)]);
+        assert!(metric.neighbors(&a(1), 8) == vec![(a(3), 0.125), (a(0), 0.25), (a(2), 0.5)]);
+        assert!(metric.neighbors(&a(2), 0).is_empty());
+    }
+
+    #[test]
+    fn compressed_round_trip() {
+        let ref path = std::env::temp_dir()
+            .join("robopoker-compressed.metric")
+            .to_string_lossy()
+            .to_string();
+        let ref twin = format!("{}.zst", path);
+        let (save, ..) = EMD::random().inner();
+        save.write(path);
+        save.write(twin);
+        let plain = Metric::read(path, Street::Flop).unwrap();
+        let compressed = Metric::read(twin, Street::Flop).unwrap();
+        assert!(plain.0 == save.0);
+        assert!(compressed.0 == plain.0);
+        assert!(std::fs::read(twin).unwrap() != std::fs::read(path).unwrap());
+        // with only the twin on disk, the plain path still loads
+        std::fs::remove_file(path).unwrap();
+        assert!(Metric::read(path, Street::Flop).unwrap().0 == plain.0);
+        std::fs::remove_file(twin).ok();
+    }
+
+    #[test]
+    fn malformed_bytes_name_the_problem() {
+        use crate::save::upload::Checked;
+        use crate::save::upload::LoadError;
+        let ref path = std::env::temp_dir()
+            .join("robopoker-malformed.metric")
+            .to_string_lossy()
+            .to_string();
+        let (save, ..) = EMD::random().inner();
+        save.write(path);
+        let bytes = std::fs::read(path).unwrap();
+        std::fs::remove_file(path).ok();
+        let decode = |bytes: &[u8]| Metric::decode(Checked::from(bytes), Street::Flop).err();
+        let rows = bytes.len() - 2;
+        let mut fields = bytes.clone();
+        fields[rows - 21] = 3;
+        assert!(decode(&bytes).is_none());
+        assert!(decode(&bytes[..rows - 5]) == Some(LoadError::Truncated));
+        assert!(decode(&bytes[..rows]) == Some(LoadError::Trailer));
+        assert!(decode(&fields) == Some(LoadError::Fields(0x0003)));
+        assert!(matches!(decode(&bytes[1..]), Some(LoadError::Header(_))));
+        let nowhere = &std::env::temp_dir().join("robopoker-nowhere");
+        assert!(matches!(
+            Metric::load_from(nowhere, Street::Flop),
+            Err(LoadError::Open(_))
+        ));
+    }
+
+    #[test]
+    fn newer_version_is_rejected() {
+        use crate::save::upload::Checked;
+        use crate::save::upload::LoadError;
+        use crate::save::upload::VERSION;
+        let ref path = std::env::temp_dir()
+            .join("robopoker-newer.metric")
+            .to_string_lossy()
+            .to_string();
+        let (save, ..) = EMD::random().inner();
+        save.write(path);
+        let mut bytes = std::fs::read(path).unwrap();
+        std::fs::remove_file(path).ok();
+        // the version is the last u32 of the 23 byte header
+        bytes[19..23].copy_from_slice(&(VERSION + 1).to_be_bytes());
+        match Metric::decode(Checked::from(&bytes[..]), Street::Flop) {
+            Err(LoadError::Header(e)) => assert!(e.contains("unsupported version"), "{}", e),
+            _ => panic!("read a version {} file", VERSION + 1),
+        }
+    }
+
+    #[test]
+    fn checksum_catches_flipped_bits() {
+        use crate::save::upload::LoadError;
+        let ref path = std::env::temp_dir()
+            .join("robopoker-checksum.metric")
+            .to_string_lossy()
+            .to_string();
+        let (save, ..) = EMD::random().inner();
+        save.write(path);
+        let intact = std::fs::read(path).unwrap();
+        assert!(intact.ends_with(&[0xFF, 0xFF]));
+        assert!(Metric::read(path, Street::Flop).is_ok());
+        crate::save::upload::stamp(path);
+        assert!(Metric::read(path, Street::Flop).is_ok());
+        let mut corrupt = intact.clone();
+        corrupt[intact.len() - 3] ^= 1;
+        std::fs::write(path, corrupt).unwrap();
+        assert!(matches!(
+            Metric::read(path, Street::Flop),
+            Err(LoadError::Checksum(..))
+        ));
+        std::fs::remove_file(path).ok();
+        std::fs::remove_file(format!("{}.digest", path)).ok();
+    }
+
+    #[test]
+    fn combined_round_trip() {
+        let ref path = std::env::temp_dir()
+            .join("robopoker-combined.metric")
+            .to_string_lossy()
+            .to_string();
+        let metric = |street: Street, scale: Energy| {
+            let ref a = (0..4)
+                .map(|i| Abstraction::from((street, i)))
+                .collect::<Vec<Abstraction>>();
+            Metric(
+                a.iter()
+                    .enumerate()
+                    .flat_map(|(i, x)| a.iter().enumerate().map(move |(j, y)| (i, x, j, y)))
+                    .filter(|(i, _, j, _)| i < j)
+                    .map(|(i, x, j, y)| (Pair::from((x, y)), scale * (i + j) as Energy))
+                    .collect(),
+                street,
+            )
+        };
+        // same indices on both streets, so the Pairs collide
+        let save = BTreeMap::from([
+            (Street::Flop, metric(Street::Flop, 0.1)),
+            (Street::Turn, metric(Street::Turn, 0.2)),
+        ]);
+        Metric::save_combined(path, &save);
+        let load = Metric::load_combined(path);
+        assert!(load.len() == 2);
+        assert!(save
+            .iter()
+            .zip(load.iter())
+            .all(|((s1, m1), (s2, m2))| s1 == s2 && m1.0 == m2.0));
+        std::fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn emd_breakdown_ships_equity_mass() {
+        let p = |i: usize| Abstraction::from((Street::Rive, i));
+        let source = Histogram::from(vec![p(0), p(0), p(10), p(10)]);
+        let target = Histogram::from(vec![p(0), p(0), p(40), p(40)]);
+        let breakdown = Metric::default().emd_breakdown(&source, &target);
+        let total = breakdown.iter().map(|(_, e)| e).sum::<Energy>();
+        let shipped = breakdown.iter().find(|(a, _)| *a == p(10)).unwrap().1;
+        assert!((total - Metric::default().emd(&source, &target)).abs() < 1e-4);
+        assert!((shipped - total).abs() < 1e-4);
+    }
+
     #[ignore]
     #[test]
     fn persistence() {
@@ -210,7 +691,7 @@ mod tests {
         let emd = EMD::random();
         let save = emd.metric();
         save.save();
-        let load = Metric::load(street);
+        let load = Metric::load_or_panic(street);
         std::iter::empty()
             .chain(save.0.iter().zip(load.0.iter()))
             .chain(load.0.iter().zip(save.0.iter()))
@@ -1,13 +1,21 @@
 pub mod abstraction;
+pub mod abstractor;
+pub mod cache;
+pub mod config;
 pub mod emd;
 pub mod equity;
+pub mod exact;
 pub mod heuristic;
 pub mod histogram;
 pub mod layer;
 pub mod lookup;
 pub mod metric;
 pub mod pair;
+#[cfg(feature = "native")]
+pub mod pipeline;
 pub mod potential;
 pub mod progress;
+pub mod resolution;
 pub mod sinkhorn;
+pub mod stopwatch;
 pub mod transitions;
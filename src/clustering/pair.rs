@@ -1,3 +1,4 @@
+use crate::cards::street::Street;
 use crate::clustering::abstraction::Abstraction;
 use crate::transport::support::Support;
 
@@ -5,6 +6,9 @@ use crate::transport::support::Support;
 #[derive(Default, Copy, Clone, Hash, Eq, PartialEq, PartialOrd, Ord, Debug)]
 pub struct Pair(u64);
 
+/// XOR is commutative, so construction is order-independent:
+/// Pair::from((a, b)) == Pair::from((b, a)). Metric stores only
+/// the i > j triangle and relies on this for symmetric lookups.
 impl From<(&Abstraction, &Abstraction)> for Pair {
     fn from((a, b): (&Abstraction, &Abstraction)) -> Self {
         Self(u64::from(*a) ^ u64::from(*b))
@@ -21,4 +25,135 @@ impl From<i64> for Pair {
     }
 }
 
+impl Pair {
+    /// given one member of the Pair, recover the other
+    pub fn other(&self, a: &Abstraction) -> Abstraction {
+        Abstraction::from(self.0 ^ u64::from(*a))
+    }
+    /// recover both members of the Pair, given the street they were
+    /// drawn from. the street tag cancels under XOR, and Pairs of
+    /// different streets can collide, so it must be supplied, as
+    /// Metric does. we search that street's abstractions for one
+    /// whose partner is also a well-formed abstraction, among the
+    /// first k, since a street may be clustered wider than its
+    /// default. River abstractions are fixed equity bins, so k is
+    /// moot there. ordering is canonical, matching Layer::metric:
+    /// (larger, smaller) index.
+    pub fn decompose(&self, street: Street, k: usize) -> Option<(Abstraction, Abstraction)> {
+        let candidates = match street {
+            Street::Rive => Abstraction::all(street),
+            _ => (0..k).map(|i| Abstraction::from((street, i))).collect(),
+        };
+        candidates
+            .into_iter()
+            .map(|a| (a, self.other(&a)))
+            .filter(|(a, b)| a.index() > b.index())
+            .find(|(_, b)| *b == Abstraction::from((street, b.index())))
+    }
+    /// recover both members of a Pair of learned abstractions, the
+    /// only kind that a Metric stores. Flop and Turn Pairs never share
+    /// bits, so unlike decompose the street need not be supplied,
+    /// only how many abstractions it was clustered into.
+    /// same canonical ordering: (larger, smaller) index.
+    pub fn split(&self, k: usize) -> (Abstraction, Abstraction) {
+        [Street::Flop, Street::Turn]
+            .into_iter()
+            .find_map(|street| self.decompose(street, k))
+            .expect("pair of learned abstractions")
+    }
+}
+
 impl Support for Pair {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+
+    #[test]
+    fn learned_streets_never_collide() {
+        for (street, other) in [(Street::Flop, Street::Turn), (Street::Turn, Street::Flop)] {
+            let all = Abstraction::all(street);
+            for a in all.iter() {
+                for b in all.iter().filter(|b| a.index() > b.index()) {
+                    assert!(Pair::from((a, b)).decompose(other, other.k()).is_none());
+                }
+            }
+        }
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn split_inverts_from(
+            flop in proptest::prelude::any::<bool>(),
+            i in 0..Street::Flop.k(),
+            j in 0..Street::Flop.k(),
+        ) {
+            let street = if flop { Street::Flop } else { Street::Turn };
+            proptest::prop_assume!(i != j);
+            let a = Abstraction::from((street, i));
+            let b = Abstraction::from((street, j));
+            let (x, y) = Pair::from((&a, &b)).split(street.k());
+            let expected = std::collections::BTreeSet::from([a, b]);
+            proptest::prop_assert!(std::collections::BTreeSet::from([x, y]) == expected);
+            proptest::prop_assert!(x.index() > y.index());
+        }
+    }
+
+    #[test]
+    fn order_independent() {
+        for street in Street::all().iter().copied() {
+            let all = Abstraction::all(street);
+            for a in all.iter() {
+                for b in all.iter().take(8) {
+                    assert_eq!(Pair::from((a, b)), Pair::from((b, a)));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn decompose_inverts_from() {
+        let ref mut rng = rand::thread_rng();
+        for street in Street::all().iter().copied() {
+            let all = Abstraction::all(street);
+            for _ in 0..16 {
+                let a = all[rng.gen_range(0..all.len())];
+                let b = all[rng.gen_range(0..all.len())];
+                if a == b {
+                    continue;
+                }
+                let (hi, lo) = if a.index() > b.index() {
+                    (a, b)
+                } else {
+                    (b, a)
+                };
+                let k = street.k();
+                assert_eq!(Pair::from((&a, &b)).decompose(street, k), Some((hi, lo)));
+                assert_eq!(Pair::from((&b, &a)).decompose(street, k), Some((hi, lo)));
+                assert_eq!(Pair::from((&a, &b)).other(&a), b);
+            }
+        }
+    }
+
+    #[test]
+    fn colliding_pairs_decompose_by_street() {
+        let a = Abstraction::from((Street::Turn, 107));
+        let b = Abstraction::from((Street::Turn, 75));
+        let pair = Pair::from((&a, &b));
+        assert!(pair.decompose(Street::Turn, Street::Turn.k()) == Some((a, b)));
+        // the same bits also decompose into a pair of preflop hands
+        let (x, y) = pair.decompose(Street::Pref, Street::Pref.k()).unwrap();
+        assert!(Pair::from((&x, &y)) == pair);
+    }
+
+    #[test]
+    fn decompose_beyond_default_k() {
+        let k = Street::Turn.k() + 8;
+        let a = Abstraction::from((Street::Turn, k - 1));
+        let b = Abstraction::from((Street::Turn, 3));
+        let pair = Pair::from((&a, &b));
+        assert!(pair.decompose(Street::Turn, k) == Some((a, b)));
+        assert!(pair.split(k) == (a, b));
+    }
+}
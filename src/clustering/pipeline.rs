@@ -0,0 +1,165 @@
+use super::config::ClusterConfig;
+use super::layer::Layer;
+use crate::cards::street::Street;
+use crate::save::upload::SaveFormat;
+use crate::save::upload::Table;
+use crate::Energy;
+use crate::Hook;
+use crate::ProgressEvent;
+use std::path::PathBuf;
+
+/// single entry point for the abstraction build.
+/// carries every knob that would otherwise require
+/// editing source: output directory, kmeans hyperparameters,
+/// seed, on-disk format, and a progress hook. artifacts are
+/// read from and written to dir only.
+///
+/// Pipeline::new(dir).seed(42).k(Street::Turn, 400).run()
+/// Pipeline::new(dir).samples(256).run() // approximate River equity
+/// Pipeline::new(dir).potential(0.5).run() // Turn on equity + potential
+/// Pipeline::new(dir).limit(2).run() // a corner of the game tree, in seconds
+#[derive(Clone)]
+pub struct Pipeline {
+    dir: PathBuf,
+    config: ClusterConfig,
+    format: SaveFormat,
+    hook: Option<Hook>,
+}
+
+impl Pipeline {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            config: ClusterConfig::default(),
+            format: SaveFormat::default(),
+            hook: None,
+        }
+    }
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.config = self.config.with_seed(seed);
+        self
+    }
+    pub fn k(mut self, street: Street, k: usize) -> Self {
+        self.config = self.config.with_k(street, k);
+        self
+    }
+    pub fn t(mut self, street: Street, t: usize) -> Self {
+        self.config = self.config.with_t(street, t);
+        self
+    }
+    /// approximate River equity from this many sampled villain hands
+    pub fn samples(mut self, samples: usize) -> Self {
+        self.config = self.config.with_samples(samples);
+        self
+    }
+    /// weigh equity potential into Turn distances
+    pub fn potential(mut self, weight: Energy) -> Self {
+        self.config = self.config.with_potential(weight);
+        self
+    }
+    /// keep only the first few children of every isomorphism
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.config = self.config.with_limit(limit);
+        self
+    }
+    pub fn format(mut self, format: SaveFormat) -> Self {
+        self.format = format;
+        self
+    }
+    /// receive clustering progress events, from any thread
+    pub fn hook<F>(mut self, f: F) -> Self
+    where
+        F: Fn(ProgressEvent) + Send + Sync + 'static,
+    {
+        self.hook = Some(std::sync::Arc::new(f));
+        self
+    }
+    pub fn config(&self) -> &ClusterConfig {
+        &self.config
+    }
+    pub fn dir(&self) -> &PathBuf {
+        &self.dir
+    }
+    pub fn save_format(&self) -> SaveFormat {
+        self.format
+    }
+
+    /// learn every street not already in dir, river first
+    pub fn run(self) {
+        std::fs::create_dir_all(&self.dir).expect("create output directory");
+        self.config.save_to(&self.dir);
+        Street::all()
+            .into_iter()
+            .rev()
+            .filter(|&&s| !Layer::done_in(&self.dir, s))
+            .map(|&s| Layer::make_in(&self.dir, s, &self.config, self.hook.clone()))
+            .map(|layer| match self.format {
+                SaveFormat::Pgcopy => layer.save_to(&self.dir),
+            })
+            .count();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clustering::lookup::Lookup;
+    use crate::clustering::metric::Metric;
+
+    #[test]
+    fn builder_carries_config() {
+        let pipeline = Pipeline::new("/tmp/robopoker")
+            .seed(42)
+            .k(Street::Turn, 400)
+            .t(Street::Flop, 3)
+            .samples(256)
+            .potential(0.25)
+            .limit(2)
+            .format(SaveFormat::Pgcopy);
+        assert!(pipeline.config().seed() == Some(42));
+        assert!(pipeline.config().potential() == Some(0.25));
+        assert!(pipeline.config().samples() == Some(256));
+        assert!(pipeline.config().limit() == Some(2));
+        assert!(pipeline.save_format() == SaveFormat::Pgcopy);
+        assert!(pipeline.config().k(Street::Turn) == 400);
+        assert!(pipeline.config().k(Street::Flop) == Street::Flop.k());
+        assert!(pipeline.config().t(Street::Flop) == 3);
+        assert!(pipeline.config().t(Street::Turn) == Street::Turn.t());
+    }
+
+    #[test]
+    fn writes_into_directory() {
+        let ref dir = std::env::temp_dir().join("robopoker-pipeline-lookup");
+        std::fs::create_dir_all(dir).unwrap();
+        Lookup::grow(Street::Pref).save_to(dir);
+        assert!(Lookup::done_in(dir, Street::Pref));
+        assert!(Lookup::path_in(dir, Street::Pref).starts_with(dir.to_str().unwrap()));
+        assert!(
+            Lookup::load_from(dir, Street::Pref)
+                .unwrap()
+                .validate(Street::Pref)
+                == Ok(())
+        );
+        std::fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn runs_end_to_end() {
+        let ref dir = std::env::temp_dir().join("robopoker-pipeline-run");
+        std::fs::remove_dir_all(dir).ok();
+        let pipeline = Pipeline::new(dir)
+            .limit(1)
+            .seed(42)
+            .k(Street::Turn, 8)
+            .t(Street::Turn, 2)
+            .k(Street::Flop, 4)
+            .t(Street::Flop, 2);
+        pipeline.run();
+        for street in Street::all() {
+            assert!(Layer::done_in(dir, *street));
+        }
+        assert!(Metric::load_from(dir, Street::Turn).unwrap().street() == Street::Turn);
+        assert!(Metric::load_from(dir, Street::Flop).unwrap().street() == Street::Flop);
+        std::fs::remove_dir_all(dir).ok();
+    }
+}
@@ -2,7 +2,6 @@ use super::abstraction::Abstraction;
 use super::histogram::Histogram;
 use crate::transport::density::Density;
 use crate::Entropy;
-use crate::Probability;
 use std::collections::BTreeMap;
 use std::ops::AddAssign;
 
@@ -42,7 +41,7 @@ impl Potential {
             h.support()
                 .copied()
                 .map(|x| (x, h.n()))
-                .map(|(x, y)| (x, 1. / y as Probability))
+                .map(|(x, y)| (x, 1. / y as f32))
                 .map(|(x, y)| (x, y.ln() as Entropy))
                 .collect::<BTreeMap<_, _>>(),
         )
@@ -1,3 +1,5 @@
+use crate::Hook;
+use crate::ProgressEvent;
 #[deprecated]
 use std::time::Instant;
 
@@ -8,10 +10,12 @@ pub struct Progress {
     ticks: usize,
     begin: Instant,
     delta: Instant,
+    phase: &'static str,
+    hook: Option<Hook>,
 }
 impl Progress {
     pub fn new(total: usize, n: usize) -> Self {
-        let check = (total / n).min(1);
+        let check = (total / n).max(1);
         let now = Instant::now();
         Self {
             total,
@@ -19,16 +23,32 @@ impl Progress {
             ticks: 0,
             begin: now,
             delta: now,
+            phase: "",
+            hook: None,
         }
     }
+    /// forward every tick to this hook, tagged with phase
+    pub fn with_hook(mut self, phase: &'static str, hook: Option<Hook>) -> Self {
+        self.phase = phase;
+        self.hook = hook;
+        self
+    }
     pub fn tick(&mut self) {
         self.ticks += 1;
+        if let Some(ref hook) = self.hook {
+            hook(ProgressEvent {
+                phase: self.phase,
+                current: self.ticks as u64,
+                total: self.total as u64,
+                elapsed: self.begin.elapsed(),
+            });
+        }
         if self.ticks % self.check == 0 {
             let now = Instant::now();
             let total_t = now.duration_since(self.begin);
             let delta_t = now.duration_since(self.delta);
             self.delta = now;
-            log::info!(
+            log::debug!(
                 "progress: {:8.0?} {:>10} {:6.2}%   mean {:6.0}   last {:6.0}",
                 total_t,
                 self.ticks,
@@ -39,3 +59,23 @@ impl Progress {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::sync::Mutex;
+
+    #[test]
+    fn tick_forwards_to_hook() {
+        let events = Arc::new(Mutex::new(Vec::<ProgressEvent>::new()));
+        let sink = events.clone();
+        let hook: Hook = Arc::new(move |e| sink.lock().unwrap().push(e));
+        let mut progress = Progress::new(10, 2).with_hook("ticks", Some(hook));
+        (0..10).for_each(|_| progress.tick());
+        let events = events.lock().unwrap();
+        assert!(events.len() == 10);
+        assert!(events.iter().all(|e| e.phase == "ticks" && e.total == 10));
+        assert!(events.last().unwrap().current == 10);
+    }
+}
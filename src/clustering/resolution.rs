@@ -0,0 +1,117 @@
+use super::abstraction::Abstraction;
+use super::metric::Metric;
+use crate::transport::measure::Measure;
+use crate::Energy;
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+
+/// two nested abstraction granularities over the same Street.
+/// the coarse set is what the blueprint was trained on, the fine set
+/// is what a subgame solver refines it into. every fine Abstraction
+/// has exactly one coarse parent.
+///
+/// both sets live in the same Abstraction encoding, so by convention
+/// fine indices start where coarse indices stop. we check on
+/// construction that neither the Abstractions nor the Pairs collide.
+pub struct Resolution {
+    coarse: Metric,
+    fine: Metric,
+    parents: BTreeMap<Abstraction, Abstraction>,
+}
+
+impl From<(Metric, Metric, BTreeMap<Abstraction, Abstraction>)> for Resolution {
+    fn from((coarse, fine, parents): (Metric, Metric, BTreeMap<Abstraction, Abstraction>)) -> Self {
+        let children = parents.keys().collect::<BTreeSet<_>>();
+        assert!(
+            parents.values().all(|parent| !children.contains(parent)),
+            "fine and coarse abstractions collide"
+        );
+        assert!(coarse.disjoint(&fine), "fine and coarse pairs collide");
+        Self {
+            coarse,
+            fine,
+            parents,
+        }
+    }
+}
+
+impl Resolution {
+    /// the coarse parent of a fine Abstraction
+    pub fn coarsen(&self, fine: &Abstraction) -> Abstraction {
+        self.parents
+            .get(fine)
+            .copied()
+            .expect("fine abstraction has a parent")
+    }
+    /// every fine Abstraction refining a coarse one
+    pub fn refine(&self, coarse: &Abstraction) -> Vec<Abstraction> {
+        self.parents
+            .iter()
+            .filter(|(_, parent)| *parent == coarse)
+            .map(|(child, _)| *child)
+            .collect()
+    }
+    pub fn is_fine(&self, x: &Abstraction) -> bool {
+        self.parents.contains_key(x)
+    }
+}
+
+/// distances are only defined within a resolution.
+/// compare across resolutions by coarsening first.
+impl Measure for Resolution {
+    type X = Abstraction;
+    type Y = Abstraction;
+    fn distance(&self, x: &Self::X, y: &Self::Y) -> Energy {
+        match (self.is_fine(x), self.is_fine(y)) {
+            (true, true) => self.fine.distance(x, y),
+            (false, false) => self.coarse.distance(x, y),
+            _ => unreachable!("distance across resolutions"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards::street::Street;
+    use crate::clustering::pair::Pair;
+
+    fn metric(abstractions: &[Abstraction]) -> Metric {
+        Metric::from((
+            abstractions[0].street(),
+            abstractions
+                .iter()
+                .enumerate()
+                .flat_map(|(i, x)| {
+                    abstractions
+                        .iter()
+                        .enumerate()
+                        .map(move |(j, y)| (i, x, j, y))
+                })
+                .filter(|(i, _, j, _)| i < j)
+                .map(|(i, x, j, y)| (Pair::from((x, y)), (j - i) as Energy))
+                .collect::<BTreeMap<Pair, Energy>>(),
+        ))
+    }
+
+    #[test]
+    fn coarse_and_fine_distances() {
+        let coarse = (0..2)
+            .map(|i| Abstraction::from((Street::Flop, i)))
+            .collect::<Vec<Abstraction>>();
+        let fine = (2..6)
+            .map(|i| Abstraction::from((Street::Flop, i)))
+            .collect::<Vec<Abstraction>>();
+        let parents = fine
+            .iter()
+            .enumerate()
+            .map(|(i, f)| (*f, coarse[i / 2]))
+            .collect::<BTreeMap<Abstraction, Abstraction>>();
+        let resolution = Resolution::from((metric(&coarse), metric(&fine), parents));
+        assert!(resolution.distance(&coarse[0], &coarse[1]) == 1.);
+        assert!(resolution.distance(&fine[0], &fine[3]) == 1.);
+        assert!(resolution.distance(&fine[0], &fine[1]) < 1.);
+        assert!(resolution.coarsen(&fine[3]) == coarse[1]);
+        assert!(resolution.refine(&coarse[0]) == fine[..2].to_vec());
+    }
+}
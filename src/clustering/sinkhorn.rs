@@ -7,8 +7,30 @@ use crate::transport::density::Density;
 use crate::transport::measure::Measure;
 use crate::Energy;
 use crate::Entropy;
+use serde::Deserialize;
+use serde::Serialize;
 use std::collections::BTreeMap;
 
+/// hyperparameters of the Sinkhorn iteration. epsilon is the strength
+/// of entropic regularization, and iteration stops once the potentials
+/// move by less than tolerance in total, or after max_iters.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SinkhornConfig {
+    pub epsilon: Entropy,
+    pub max_iters: usize,
+    pub tolerance: Energy,
+}
+
+impl Default for SinkhornConfig {
+    fn default() -> Self {
+        Self {
+            epsilon: crate::SINKHORN_TEMPERATURE,
+            max_iters: crate::SINKHORN_ITERATIONS,
+            tolerance: crate::SINKHORN_TOLERANCE,
+        }
+    }
+}
+
 /// using this to represent an arbitrary instance of the Kontorovich-Rubinstein
 /// potential formulation of the optimal transport problem.
 pub struct Sinkhorn<'a> {
@@ -17,9 +39,14 @@ pub struct Sinkhorn<'a> {
     nu: &'a Histogram,
     lhs: Potential,
     rhs: Potential,
+    config: SinkhornConfig,
 }
 
 impl Sinkhorn<'_> {
+    pub fn with_config(mut self, config: SinkhornConfig) -> Self {
+        self.config = config;
+        self
+    }
     /// calculate ε-minimizing coupling by scaling potentials
     fn sinkhorn(&mut self) {
         #[allow(unused)]
@@ -92,15 +119,15 @@ impl Sinkhorn<'_> {
     }
     /// hyperparameter that determines strength of entropic regularization. incorrect units but whatever
     const fn temperature(&self) -> Entropy {
-        crate::SINKHORN_TEMPERATURE
+        self.config.epsilon
     }
     /// hyperparameter that determines maximum number of iterations
     const fn iterations(&self) -> usize {
-        crate::SINKHORN_ITERATIONS
+        self.config.max_iters
     }
     /// hyperparameter that determines stopping criteria
     const fn tolerance(&self) -> Energy {
-        crate::SINKHORN_TOLERANCE
+        self.config.tolerance
     }
 }
 
@@ -128,6 +155,17 @@ impl Coupling for Sinkhorn<'_> {
     }
 }
 
+impl Sinkhorn<'_> {
+    /// cost of the transport plan, split by the source bin
+    /// the mass was shipped from. sums to cost().
+    pub fn breakdown(&self) -> Vec<(Abstraction, Energy)> {
+        self.lhs
+            .support()
+            .map(|x| (*x, self.rhs.support().map(|y| self.flow(x, y)).sum()))
+            .collect()
+    }
+}
+
 impl<'a> From<(&'a Histogram, &'a Histogram, &'a Metric)> for Sinkhorn<'a> {
     fn from((mu, nu, metric): (&'a Histogram, &'a Histogram, &'a Metric)) -> Self {
         Self {
@@ -136,6 +174,7 @@ impl<'a> From<(&'a Histogram, &'a Histogram, &'a Metric)> for Sinkhorn<'a> {
             nu,
             lhs: Potential::uniform(mu),
             rhs: Potential::uniform(nu),
+            config: SinkhornConfig::default(),
         }
     }
 }
@@ -0,0 +1,73 @@
+use crate::cards::street::Street;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
+
+/// per-phase elapsed times for one street of clustering
+pub type Laps = Mutex<Vec<(&'static str, Duration)>>;
+
+/// scoped timer for a single clustering phase.
+/// logs and records its elapsed time on drop, so we
+/// still learn how long a phase ran if it panics.
+pub struct Stopwatch<'a> {
+    laps: &'a Laps,
+    street: Street,
+    phase: &'static str,
+    start: Instant,
+}
+
+impl<'a> From<(&'a Laps, Street, &'static str)> for Stopwatch<'a> {
+    fn from((laps, street, phase): (&'a Laps, Street, &'static str)) -> Self {
+        Self {
+            laps,
+            street,
+            phase,
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Drop for Stopwatch<'_> {
+    fn drop(&mut self) {
+        let elapsed = self.start.elapsed();
+        if std::thread::panicking() {
+            log::error!(
+                "{:<32}{:<32}{:?}",
+                format!("panicked in {}", self.phase),
+                self.street,
+                elapsed
+            );
+        } else {
+            log::info!(
+                "{:<32}{:<32}{:?}",
+                format!("elapsed     {}", self.phase),
+                self.street,
+                elapsed
+            );
+        }
+        if let Ok(mut laps) = self.laps.lock() {
+            laps.push((self.phase, elapsed));
+        }
+    }
+}
+
+/// summary table of all recorded phases for a street
+pub fn summary(laps: &Laps, street: Street) -> String {
+    let laps = laps.lock().expect("laps lock");
+    let total = laps.iter().map(|(_, d)| *d).sum::<Duration>();
+    laps.iter()
+        .map(|(phase, d)| {
+            (
+                phase,
+                d,
+                d.as_secs_f32() / total.as_secs_f32().max(f32::MIN_POSITIVE),
+            )
+        })
+        .map(|(phase, d, pct)| format!("{street} {:<12}{:>12.2?}{:>8.1}%", phase, d, pct * 100.))
+        .chain(std::iter::once(format!(
+            "{street} {:<12}{:>12.2?}",
+            "total", total
+        )))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
@@ -13,6 +13,83 @@ impl From<BTreeMap<Abstraction, Histogram>> for Decomp {
     }
 }
 
+#[cfg(feature = "native")]
+impl Decomp {
+    /// read from disk under a given directory
+    pub fn load_from(
+        dir: &std::path::Path,
+        street: Street,
+    ) -> Result<Self, crate::save::upload::LoadError> {
+        use crate::save::upload::Table;
+        let ref path = Self::path_in(dir, street);
+        log::info!("{:<32}{:<32}", "loading     transitions", path);
+        Self::read(path, street)
+    }
+    /// write to disk under a given directory
+    pub fn save_to(&self, dir: &std::path::Path) {
+        use crate::save::upload::Table;
+        let street = self
+            .0
+            .keys()
+            .next()
+            .copied()
+            .unwrap_or_else(|| Abstraction::from(0f32))
+            .street();
+        let ref path = Self::path_in(dir, street);
+        log::info!("{:<32}{:<32}", "saving      transition", path);
+        self.write(path);
+        crate::save::upload::stamp(path);
+    }
+    /// parse a PGCOPY transitions file. densities are stored as
+    /// f32, so counts are recovered against the street's fanout
+    fn read(path: &str, street: Street) -> Result<Self, crate::save::upload::LoadError> {
+        use crate::save::upload::header;
+        use crate::save::upload::row;
+        use crate::save::upload::width;
+        use byteorder::ReadBytesExt;
+        use byteorder::BE;
+        let ref mass = street.n_children() as f32;
+        let ref mut reader = crate::save::upload::open(path)?;
+        let mut decomp = BTreeMap::new();
+        header(reader)?;
+        while row(reader, 3)? {
+            width(reader, 8)?;
+            let from = reader.read_i64::<BE>()?;
+            width(reader, 8)?;
+            let into = reader.read_i64::<BE>()?;
+            width(reader, 4)?;
+            let weight = reader.read_f32::<BE>()?;
+            decomp
+                .entry(Abstraction::from(from))
+                .or_insert_with(Histogram::default)
+                .set(Abstraction::from(into), (weight * mass).round() as usize);
+        }
+        Ok(Self(decomp))
+    }
+    /// write a PGCOPY transitions file
+    fn write(&self, path: &str) {
+        const N_FIELDS: u16 = 3;
+        use crate::save::upload::Table;
+        use byteorder::WriteBytesExt;
+        use byteorder::BE;
+        use std::io::Write;
+        let ref mut file = crate::save::upload::writer(path).expect(&format!("touch {}", path));
+        file.write_all(Self::header()).expect("header");
+        for (from, histogram) in self.0.iter() {
+            for into in histogram.support() {
+                file.write_u16::<BE>(N_FIELDS).unwrap();
+                file.write_u32::<BE>(size_of::<i64>() as u32).unwrap();
+                file.write_i64::<BE>(i64::from(*from)).unwrap();
+                file.write_u32::<BE>(size_of::<i64>() as u32).unwrap();
+                file.write_i64::<BE>(i64::from(*into)).unwrap();
+                file.write_u32::<BE>(size_of::<f32>() as u32).unwrap();
+                file.write_f32::<BE>(histogram.density(into)).unwrap();
+            }
+        }
+        crate::save::upload::trailer(file).expect("trailer");
+    }
+}
+
 #[cfg(feature = "native")]
 impl crate::save::upload::Table for Decomp {
     fn name() -> String {
@@ -65,71 +142,50 @@ impl crate::save::upload::Table for Decomp {
         "
         .to_string()
     }
-    fn load(street: Street) -> Self {
-        let ref path = Self::path(street);
-        log::info!("{:<32}{:<32}", "loading     transitions", path);
-        use byteorder::ReadBytesExt;
-        use byteorder::BE;
-        use std::fs::File;
-        use std::io::BufReader;
-        use std::io::Read;
-        use std::io::Seek;
-        use std::io::SeekFrom;
-        let ref mass = street.n_children() as f32;
-        let ref file = File::open(path).expect(&format!("open {}", path));
-        let mut decomp = BTreeMap::new();
-        let mut reader = BufReader::new(file);
-        let ref mut buffer = [0u8; 2];
-        reader.seek(SeekFrom::Start(19)).expect("seek past header");
-        while reader.read_exact(buffer).is_ok() {
-            match u16::from_be_bytes(buffer.clone()) {
-                3 => {
-                    reader.read_u32::<BE>().expect("from abstraction");
-                    let from = reader.read_i64::<BE>().expect("read from abstraction");
-                    reader.read_u32::<BE>().expect("into abstraction");
-                    let into = reader.read_i64::<BE>().expect("read into abstraction");
-                    reader.read_u32::<BE>().expect("weight");
-                    let weight = reader.read_f32::<BE>().expect("read weight");
-                    decomp
-                        .entry(Abstraction::from(from))
-                        .or_insert_with(Histogram::default)
-                        .set(Abstraction::from(into), (weight * mass) as usize);
-                    continue;
-                }
-                0xFFFF => break,
-                n => panic!("unexpected number of fields: {}", n),
-            }
-        }
-        Self(decomp)
+    fn load(street: Street) -> Result<Self, crate::save::upload::LoadError> {
+        Self::load_from(&crate::save::upload::directory(), street)
     }
     fn save(&self) {
-        const N_FIELDS: u16 = 3;
-        let street = self
-            .0
-            .keys()
-            .next()
-            .copied()
-            .unwrap_or_else(|| Abstraction::from(0f32))
-            .street();
-        let ref path = Self::path(street);
-        let ref mut file = File::create(path).expect(&format!("touch {}", path));
-        use byteorder::WriteBytesExt;
-        use byteorder::BE;
-        use std::fs::File;
-        use std::io::Write;
-        log::info!("{:<32}{:<32}", "saving      transition", path);
-        file.write_all(Self::header()).expect("header");
-        for (from, histogram) in self.0.iter() {
-            for into in histogram.support() {
-                file.write_u16::<BE>(N_FIELDS).unwrap();
-                file.write_u32::<BE>(size_of::<i64>() as u32).unwrap();
-                file.write_i64::<BE>(i64::from(*from)).unwrap();
-                file.write_u32::<BE>(size_of::<i64>() as u32).unwrap();
-                file.write_i64::<BE>(i64::from(*into)).unwrap();
-                file.write_u32::<BE>(size_of::<f32>() as u32).unwrap();
-                file.write_f32::<BE>(histogram.density(into)).unwrap();
+        self.save_to(&crate::save::upload::directory())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    proptest::proptest! {
+        #[test]
+        fn pgcopy_round_trip(
+            rows in proptest::collection::btree_map(
+                0..Street::Flop.k(),
+                proptest::collection::vec(0..Street::Turn.k(), Street::Flop.n_children()),
+                0..16,
+            )
+        ) {
+            let ref path = std::env::temp_dir()
+                .join("robopoker-proptest.transitions")
+                .to_string_lossy()
+                .to_string();
+            let save = Decomp(
+                rows.into_iter()
+                    .map(|(from, into)| {
+                        let into = into
+                            .into_iter()
+                            .map(|i| Abstraction::from((Street::Turn, i)))
+                            .collect::<Vec<Abstraction>>();
+                        (Abstraction::from((Street::Flop, from)), Histogram::from(into))
+                    })
+                    .collect(),
+            );
+            save.write(path);
+            let load = Decomp::read(path, Street::Flop).unwrap();
+            proptest::prop_assert!(save.0.len() == load.0.len());
+            for ((a, x), (b, y)) in save.0.iter().zip(load.0.iter()) {
+                proptest::prop_assert!(a == b);
+                proptest::prop_assert!(x.support().eq(y.support()));
+                proptest::prop_assert!(x.support().all(|i| x.density(i) == y.density(i)));
             }
         }
-        file.write_u16::<BE>(Self::footer()).expect("trailer");
     }
 }
@@ -0,0 +1,46 @@
+use crate::Chips;
+use crate::N;
+
+/// forced bets posted before the first decision.
+/// antes are per-player dead money: they enter the pot
+/// and count toward what each player risks at showdown,
+/// but not toward the preflop stake that must be called.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Blinds {
+    pub sb: Chips,
+    pub bb: Chips,
+    pub ante: Chips,
+    pub n_players: usize,
+}
+
+impl Blinds {
+    /// total forced chips in the pot once everything is posted,
+    /// assuming no stack is too short to cover its share
+    pub const fn pot(&self) -> Chips {
+        self.sb + self.bb + self.ante * self.n_players as Chips
+    }
+}
+
+impl Default for Blinds {
+    fn default() -> Self {
+        Self::from((crate::S_BLIND, crate::B_BLIND, crate::ANTE))
+    }
+}
+
+/// blinds and a per-player ante at a full table
+impl From<(Chips, Chips, Chips)> for Blinds {
+    fn from((sb, bb, ante): (Chips, Chips, Chips)) -> Self {
+        Self::from((sb, bb, ante, N))
+    }
+}
+
+impl From<(Chips, Chips, Chips, usize)> for Blinds {
+    fn from((sb, bb, ante, n_players): (Chips, Chips, Chips, usize)) -> Self {
+        Self {
+            sb,
+            bb,
+            ante,
+            n_players,
+        }
+    }
+}
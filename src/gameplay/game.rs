@@ -1,6 +1,7 @@
 #![allow(dead_code)]
 
 use super::action::Action;
+use super::blinds::Blinds;
 use super::seat::Seat;
 use super::seat::State;
 use super::settlement::Settlement;
@@ -12,6 +13,7 @@ use crate::cards::observation::Observation;
 use crate::cards::street::Street;
 use crate::cards::strength::Strength;
 use crate::gameplay::ply::Turn;
+use crate::gameplay::showdown::PotId;
 use crate::gameplay::showdown::Showdown;
 use crate::Chips;
 use crate::N;
@@ -31,6 +33,23 @@ pub struct Game {
     board: Board, // could be [Card; N]
     dealer: Position,
     ticker: Position,
+    blinds: Blinds,
+}
+
+/// an undealt Game with these forced bets. the number of
+/// seats is fixed at compile time, so the Blinds must agree
+impl From<Blinds> for Game {
+    fn from(blinds: Blinds) -> Self {
+        assert!(
+            blinds.n_players == N,
+            "blinds for {} players at a {N} seat game",
+            blinds.n_players
+        );
+        Self {
+            blinds,
+            ..Self::base()
+        }
+    }
 }
 
 impl Game {
@@ -41,6 +60,7 @@ impl Game {
             seats: [Seat::from(STACK); N],
             dealer: 0usize,
             ticker: 1usize,
+            blinds: Blinds::default(),
         }
     }
     pub fn deal(mut self) -> Self {
@@ -48,6 +68,7 @@ impl Game {
         self
     }
     pub fn post(mut self) -> Self {
+        self.ante();
         self.act(Action::Blind(self.to_post()));
         self.act(Action::Blind(self.to_post()));
         self
@@ -66,8 +87,9 @@ impl Game {
     pub fn root() -> Self {
         Self::base().deal().post()
     }
-    pub fn blinds() -> Vec<Action> {
-        vec![Action::Blind(Self::sblind()), Action::Blind(Self::bblind())]
+    /// the blind Actions that open every hand of this Game
+    pub fn blinds(&self) -> Vec<Action> {
+        vec![Action::Blind(self.sblind()), Action::Blind(self.bblind())]
     }
     pub fn n(&self) -> usize {
         N
@@ -96,6 +118,11 @@ impl Game {
     pub fn actor(&self) -> &Seat {
         self.actor_ref()
     }
+    /// every seat's hole cards, for rollouts that must not
+    /// give one seat's cards to another
+    pub fn holes(&self) -> [Hole; N] {
+        self.seats.map(|seat| seat.cards())
+    }
     pub fn sweat(&self) -> Observation {
         Observation::from((
             Hand::from(self.actor().cards()), //
@@ -115,7 +142,7 @@ impl Game {
             return options;
         }
         if self.must_post() {
-            options.push(Action::Blind(Self::sblind()));
+            options.push(Action::Blind(self.to_post()));
             return options;
         }
         if self.may_raise() {
@@ -167,6 +194,7 @@ impl Game {
         self.wipe_board();
         self.deal_cards();
         self.move_button();
+        self.ante();
         self.act(Action::Blind(self.to_post()));
         self.act(Action::Blind(self.to_post()));
     }
@@ -204,6 +232,7 @@ impl Game {
             seat.reset_cards(deck.hole());
             seat.reset_stake();
             seat.reset_spent();
+            seat.reset_posted();
         }
     }
 
@@ -218,10 +247,11 @@ impl Game {
                 self.fold();
                 self.next_player();
             }
-            Action::Call(chips)
-            | Action::Blind(chips)
-            | Action::Raise(chips)
-            | Action::Shove(chips) => {
+            Action::Blind(chips) => {
+                self.blind(chips);
+                self.next_player();
+            }
+            Action::Call(chips) | Action::Raise(chips) | Action::Shove(chips) => {
                 self.bet(chips);
                 self.next_player();
             }
@@ -232,6 +262,18 @@ impl Game {
             }
         }
     }
+    fn ante(&mut self) {
+        let ante = self.blinds.ante;
+        for seat in self.seats.iter_mut() {
+            let ante = ante.min(seat.stack());
+            self.pot += ante;
+            seat.ante(ante);
+        }
+    }
+    fn blind(&mut self, blind: Chips) {
+        self.bet(blind);
+        self.actor_mut().post();
+    }
     fn bet(&mut self, bet: Chips) {
         assert!(self.actor_ref().stack() >= bet);
         self.pot += bet;
@@ -284,10 +326,11 @@ impl Game {
             self.is_everyone_alright()
         }
     }
-    /// blinds have not yet been posted // TODO some edge case of all in blinds
+    /// blinds have not yet been posted. tracked per seat rather than
+    /// by pot size, since short stacks post less than the Blinds
     fn must_post(&self) -> bool {
         if self.street() == Street::Pref {
-            self.pot() < Self::sblind() + Self::bblind()
+            self.seats.iter().filter(|seat| seat.posted()).count() < 2
         } else {
             false
         }
@@ -353,8 +396,8 @@ impl Game {
     pub fn to_post(&self) -> Chips {
         assert!(self.street() == Street::Pref);
         match (self.ticker as isize - self.dealer as isize) % self.n() as isize {
-            1 => Self::sblind().min(self.actor_ref().stack()),
-            _ => Self::bblind().min(self.actor_ref().stack()),
+            1 => self.sblind().min(self.actor_ref().stack()),
+            _ => self.bblind().min(self.actor_ref().stack()),
         }
     }
     pub fn to_shove(&self) -> Chips {
@@ -377,15 +420,67 @@ impl Game {
             });
         let relative_raise = most_large_stake - self.actor().stake();
         let marginal_raise = most_large_stake - next_large_stake;
-        let required_raise = std::cmp::max(marginal_raise, Self::bblind());
+        let required_raise = std::cmp::max(marginal_raise, self.bblind());
         relative_raise + required_raise
     }
 
     //
+    /// Strength of every seat's hand on each of EQUITY_ROLLOUTS
+    /// Rivers the board could run out to, drawn from the cards left
+    /// in the deck. seeded by the cards already out, so repeated
+    /// rollouts of the same cards agree. this is the expensive part
+    /// of an estimate, and depends on nothing but the cards.
+    pub fn rollouts(&self) -> Vec<[Strength; N]> {
+        use rand::rngs::SmallRng;
+        use rand::seq::IteratorRandom;
+        use rand::SeedableRng;
+        let board = Hand::from(self.board());
+        let seed = self
+            .seats
+            .iter()
+            .map(|seat| u64::from(Hand::from(seat.cards())))
+            .fold(u64::from(board), |seed, hole| seed.rotate_left(7) ^ hole);
+        let ref mut rng = SmallRng::seed_from_u64(seed);
+        let missing = Street::Rive.n_observed() - board.size();
+        (0..crate::EQUITY_ROLLOUTS)
+            .map(|_| Hand::from(self.deck()).choose_multiple(rng, missing))
+            .map(|runout| Hand::add(board, Hand::from(runout)))
+            .map(|river| {
+                self.seats
+                    .map(|seat| Strength::from(Hand::add(Hand::from(seat.cards()), river)))
+            })
+            .collect()
+    }
+    /// heuristic pnl of a Game cut off before showdown, for truncated
+    /// trees. each rollout is settled as a showdown between the seats'
+    /// actual hole cards, folds and side pots included, so pnls still
+    /// sum to zero. the estimate is the average over rollouts.
+    pub fn estimate(&self, rollouts: &[[Strength; N]]) -> Vec<Utility> {
+        let mut pnls = vec![0. as Utility; N];
+        for strengths in rollouts {
+            let ledger = self
+                .seats
+                .iter()
+                .zip(strengths)
+                .map(|(seat, strength)| Settlement::from((seat.spent(), seat.state(), *strength)))
+                .collect::<Vec<Settlement>>();
+            for (pnl, settlement) in pnls.iter_mut().zip(Showdown::from(ledger).settle()) {
+                *pnl += settlement.pnl() as Utility;
+            }
+        }
+        pnls.into_iter()
+            .map(|pnl| pnl / rollouts.len() as Utility)
+            .collect()
+    }
     pub fn settlements(&self) -> Vec<Settlement> {
         assert!(self.must_stop(), "non terminal game state:\n{}", self);
         Showdown::from(self.ledger()).settle()
     }
+    /// chips awarded to each seat from each pot, for checking pot math
+    pub fn awards(&self) -> Vec<(usize, Chips, PotId)> {
+        assert!(self.must_stop(), "non terminal game state:\n{}", self);
+        Showdown::from(self.ledger()).breakdown()
+    }
     fn ledger(&self) -> Vec<Settlement> {
         self.seats
             .iter()
@@ -455,11 +550,11 @@ impl Game {
             .expect("non-empty seats")
     }
 
-    pub const fn bblind() -> Chips {
-        crate::B_BLIND
+    pub fn bblind(&self) -> Chips {
+        self.blinds.bb
     }
-    pub const fn sblind() -> Chips {
-        crate::S_BLIND
+    pub fn sblind(&self) -> Chips {
+        self.blinds.sb
     }
 }
 
@@ -496,7 +591,104 @@ mod tests {
         assert!(game.ticker != game.dealer);
         assert!(game.board().street() == Street::Pref);
         assert!(game.actor().state() == State::Betting);
-        assert!(game.pot() == Game::sblind() + Game::bblind());
+        assert!(game.pot() == game.sblind() + game.bblind());
+    }
+
+    #[test]
+    fn translation_is_pseudoharmonic() {
+        use rand::rngs::SmallRng;
+        use rand::SeedableRng;
+        let game = Game::root();
+        let ref edges = [
+            Edge::Raise(Odds(1, 1)),
+            Edge::Raise(Odds(3, 1)),
+            Edge::Shove,
+        ];
+        let bets = edges
+            .iter()
+            .map(|e| match game.actionize(e) {
+                Action::Raise(chips) | Action::Shove(chips) => chips,
+                _ => unreachable!(),
+            })
+            .collect::<Vec<Chips>>();
+        let (a, b) = (bets[0], bets[1]);
+        assert!((a + b) % 2 == 0);
+        let mid = Action::Raise((a + b) / 2);
+        let pot = game.pot() as Utility;
+        let p = Odds::pseudoharmonic(
+            (a + b) as Utility / 2. / pot,
+            a as Utility / pot,
+            b as Utility / pot,
+        );
+        let ref mut rng = SmallRng::seed_from_u64(0);
+        let n = 10_000;
+        let lo = (0..n)
+            .map(|_| game.translate(&mid, edges, rng))
+            .inspect(|e| assert!(e == &edges[0] || e == &edges[1]))
+            .filter(|e| e == &edges[0])
+            .count();
+        assert!((lo as f32 / n as f32 - f32::from(p)).abs() < 0.02);
+        assert!(game.translate(&Action::Raise(a), edges, rng) == edges[0]);
+        assert!(game.translate(&Action::Raise(1), edges, rng) == edges[0]);
+        assert!(game.translate(&Action::Raise(bets[2]), edges, rng) == Edge::Shove);
+        assert!(game.translate(&Action::Fold, edges, rng) == Edge::Fold);
+    }
+
+    #[test]
+    fn antes_seed_pot() {
+        let blinds = Blinds::from((1, 2, 1));
+        let game = Game::from(blinds).deal().post();
+        assert!(game.pot() == 1 + 2 + N as Chips);
+        assert!(game.pot() > Game::root().pot());
+        assert!(game.to_call() == 1);
+    }
+
+    #[test]
+    fn antes_are_risked() {
+        let blinds = Blinds::from((1, 2, 1));
+        let game = Game::from(blinds).deal().post();
+        let game = game.apply(Action::Fold);
+        let pnl = game
+            .settlements()
+            .iter()
+            .map(|s| s.pnl())
+            .collect::<Vec<Chips>>();
+        assert!(pnl.iter().sum::<Chips>() == 0);
+        assert!(pnl.contains(&-2));
+        assert!(pnl.contains(&2));
+    }
+
+    #[test]
+    fn awards_conserve_chips() {
+        let game = Game::root().apply(Action::Fold);
+        let awards = game.awards();
+        let risked = game.seats.iter().map(|s| s.spent()).sum::<Chips>();
+        assert!(awards.iter().map(|(_, chips, _)| chips).sum::<Chips>() == risked);
+        assert!(awards
+            .iter()
+            .all(|(seat, _, pot)| *pot == 0 && game.seats[*seat].state() != State::Folding));
+    }
+
+    #[test]
+    fn estimate_is_head_to_head() {
+        let mut game = Game::root();
+        game.seats[0].reset_cards(Hole::try_from("As Ah").unwrap());
+        game.seats[1].reset_cards(Hole::try_from("Ks Kh").unwrap());
+        let rollouts = game.rollouts();
+        assert!(rollouts == game.rollouts());
+        assert!(rollouts.len() == crate::EQUITY_ROLLOUTS);
+        let pnl = game.estimate(&rollouts);
+        let share = |i: usize| (pnl[i] + game.seats[i].spent() as Utility) / game.pot() as Utility;
+        assert!((pnl[0] + pnl[1]).abs() < 1e-4);
+        assert!(share(0) > 0.7, "AA takes {} of the pot", share(0));
+        let game = game.apply(Action::Fold);
+        let pnl = game.estimate(&rollouts);
+        for (seat, pnl) in game.seats.iter().zip(pnl) {
+            match seat.state() {
+                State::Folding => assert!(pnl == -(seat.spent() as Utility)),
+                _ => assert!(pnl > 0.),
+            }
+        }
     }
 
     #[test]
@@ -724,6 +916,50 @@ impl Game {
         }
     }
 
+    /// inverse of actionize: map a concrete Action onto one of
+    /// the abstract Edges available here, for looking up a strategy
+    /// when an opponent bets a size that is not in our grid.
+    ///
+    /// only Raises need translating. we find the nearest aggressive
+    /// Edges below and above the bet by the chips they actionize to,
+    /// and pick between them at random with pseudo-harmonic weights.
+    /// bets outside the range of edges snap to the nearest end.
+    pub fn translate(&self, action: &Action, edges: &[Edge], rng: &mut impl rand::Rng) -> Edge {
+        let bet = match action {
+            Action::Raise(bet) => *bet,
+            _ => return Edge::from(*action),
+        };
+        let mut sizes = edges
+            .iter()
+            .filter(|e| e.is_aggro())
+            .map(|e| match self.actionize(e) {
+                Action::Raise(chips) | Action::Shove(chips) => (*e, chips),
+                _ => unreachable!("aggressive edges actionize to bets"),
+            })
+            .collect::<Vec<(Edge, Chips)>>();
+        sizes.sort_by_key(|(_, chips)| *chips);
+        sizes.dedup_by_key(|(_, chips)| *chips);
+        let above = sizes.partition_point(|(_, chips)| *chips < bet);
+        match (above.checked_sub(1).map(|i| sizes[i]), sizes.get(above)) {
+            (_, Some(&(edge, chips))) if chips == bet => edge,
+            (Some((lo, a)), Some(&(hi, b))) => {
+                let pot = self.pot() as Utility;
+                let p = Odds::pseudoharmonic(
+                    bet as Utility / pot,
+                    a as Utility / pot,
+                    b as Utility / pot,
+                );
+                if rng.gen::<f32>() < f32::from(p) {
+                    lo
+                } else {
+                    hi
+                }
+            }
+            (Some((edge, _)), None) | (None, Some(&(edge, _))) => edge,
+            (None, None) => panic!("no aggressive edges to translate {}", action),
+        }
+    }
+
     /// under the game tree constraints parametrized in lib.rs,
     /// what are the possible continuations of the Game given its
     /// full history? i.e. can we raise, and by how much.
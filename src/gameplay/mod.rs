@@ -1,4 +1,5 @@
 pub mod action;
+pub mod blinds;
 pub mod deal;
 pub mod game;
 pub mod ply;
@@ -7,6 +7,8 @@ pub struct Seat {
     stack: Chips,
     stake: Chips,
     spent: Chips,
+    /// whether this seat has posted its blind this hand
+    posted: bool,
     /// this field is the only non-public state, but if we're
     /// client-side then we can just fill it with clones of our own private cards.
     /// with this very natural method of obfuscation,
@@ -26,6 +28,7 @@ impl Seat {
             stack,
             spent: 0,
             stake: 0,
+            posted: false,
             state: State::Betting,
             cards: Hole::empty(),
         }
@@ -45,6 +48,9 @@ impl Seat {
     pub fn cards(&self) -> Hole {
         self.cards
     }
+    pub fn posted(&self) -> bool {
+        self.posted
+    }
 
     pub fn win(&mut self, win: Chips) {
         self.stack += win;
@@ -54,6 +60,15 @@ impl Seat {
         self.stake += bet;
         self.spent += bet;
     }
+    /// mark the blind as posted, whatever it amounted to
+    pub fn post(&mut self) {
+        self.posted = true;
+    }
+    /// dead money: risked at showdown, but not part of the stake to call
+    pub fn ante(&mut self, ante: Chips) {
+        self.stack -= ante;
+        self.spent += ante;
+    }
     pub fn reset_state(&mut self, state: State) {
         self.state = state;
     }
@@ -66,6 +81,9 @@ impl Seat {
     pub fn reset_spent(&mut self) {
         self.spent = 0;
     }
+    pub fn reset_posted(&mut self) {
+        self.posted = false;
+    }
 }
 
 impl std::fmt::Display for Seat {
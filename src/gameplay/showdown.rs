@@ -1,101 +1,111 @@
-use crate::cards::kicks::Kickers;
-use crate::cards::ranking::Ranking;
-use crate::cards::strength::Strength;
 use crate::gameplay::seat::State;
 use crate::gameplay::settlement::Settlement;
 use crate::Chips;
 
+/// main pot is 0, and side pots count up in order of the
+/// all-in level that caps them
+pub type PotId = usize;
+
 // ephemeral data structure that is used to calculate the results of a hand by iterating over hand.actions to calculate side pots, handling every edge case with generalized zero-cost logic
 pub struct Showdown {
     payouts: Vec<Settlement>,
-    distributing: Chips,
-    distributed: Chips,
-    best: Strength,
+    awards: Vec<(usize, Chips, PotId)>,
 }
 
 impl From<Vec<Settlement>> for Showdown {
     fn from(payouts: Vec<Settlement>) -> Self {
         Self {
             payouts,
-            distributing: 0 as Chips,
-            distributed: 0 as Chips,
-            best: Strength::from((Ranking::MAX, Kickers::default())),
+            awards: Vec::new(),
         }
     }
 }
 
 impl Showdown {
     pub fn settle(mut self) -> Vec<Settlement> {
-        'winners: while let Some(strength) = self.strongest() {
-            self.best = strength;
-            'pots: while let Some(amount) = self.remaining() {
-                self.distributing = amount;
-                self.distribute();
-                if self.is_complete() {
-                    break 'winners;
-                } else {
-                    continue 'pots;
-                }
-            }
-        }
+        self.resolve();
         self.payouts
     }
-    fn strongest(&self) -> Option<Strength> {
-        self.payouts
-            .iter()
-            .filter(|p| p.strength < self.best)
-            .filter(|p| p.status != State::Folding)
-            .map(|p| p.strength)
-            .max()
+    /// every award as (seat, chips, pot), in the order the pots
+    /// are paid out. each pot's awards sum to the chips in it,
+    /// and all of them together to the chips risked.
+    pub fn breakdown(mut self) -> Vec<(usize, Chips, PotId)> {
+        self.resolve();
+        self.awards
     }
-    fn remaining(&mut self) -> Option<Chips> {
-        self.distributed = self.distributing;
-        self.payouts
+    /// every distinct amount risked by a player still in the hand
+    /// caps a pot. each pot holds what everyone put in between the
+    /// cap below it and its own, and goes to the strongest of the
+    /// players who risked up to its cap. the top pot also takes
+    /// anything folded players put in above it.
+    fn resolve(&mut self) {
+        let levels = self.levels();
+        let top = self.payouts.iter().map(|p| p.risked).max().unwrap_or(0);
+        for (pot, &level) in levels.iter().enumerate() {
+            let floor = pot.checked_sub(1).map(|i| levels[i]).unwrap_or(0);
+            let cap = if pot + 1 == levels.len() { top } else { level };
+            let chips = self.chips(floor, cap);
+            self.distribute(pot, level, chips);
+        }
+    }
+    /// ascending all-in levels of the players still in the hand
+    fn levels(&self) -> Vec<Chips> {
+        let mut levels = self
+            .payouts
             .iter()
-            .filter(|p| p.strength == self.best)
-            .filter(|p| p.risked > self.distributed)
             .filter(|p| p.status != State::Folding)
             .map(|p| p.risked)
-            .min()
+            .filter(|r| *r > 0)
+            .collect::<Vec<Chips>>();
+        levels.sort();
+        levels.dedup();
+        levels
     }
-    fn winnings(&self) -> Chips {
+    /// chips everyone put in between floor and cap
+    fn chips(&self, floor: Chips, cap: Chips) -> Chips {
         self.payouts
             .iter()
-            .map(|p| p.risked)
-            .map(|s| std::cmp::min(s, self.distributing))
-            .map(|s| (s - self.distributed).max(0))
+            .map(|p| p.risked.min(cap) - p.risked.min(floor))
             .sum()
     }
-    fn distribute(&mut self) {
-        let chips = self.winnings();
+    /// split a pot among the strongest players eligible for it,
+    /// with odd chips going to the earliest seats
+    fn distribute(&mut self, pot: PotId, level: Chips, chips: Chips) {
+        let eligible = |p: &Settlement| p.status != State::Folding && p.risked >= level;
+        let best = self
+            .payouts
+            .iter()
+            .filter(|p| eligible(p))
+            .map(|p| p.strength)
+            .max()
+            .expect("someone risked up to every level");
         let mut winners = self
             .payouts
             .iter_mut()
-            .filter(|p| p.status != State::Folding)
-            .filter(|p| p.strength == self.best)
-            .filter(|p| p.risked > self.distributed)
-            .collect::<Vec<&mut Settlement>>();
+            .enumerate()
+            .filter(|(_, p)| eligible(p))
+            .filter(|(_, p)| p.strength == best)
+            .collect::<Vec<(usize, &mut Settlement)>>();
         let n = winners.len();
         let share = chips / n as Chips;
         let bonus = chips % n as Chips;
-        for winner in winners.iter_mut() {
-            winner.reward += share;
-        }
-        for winner in winners.iter_mut().take(bonus as usize) {
-            winner.reward += 1;
+        for (i, (seat, winner)) in winners.iter_mut().enumerate() {
+            let award = share + (i < bonus as usize) as Chips;
+            winner.reward += award;
+            if award > 0 {
+                self.awards.push((*seat, award, pot));
+            }
         }
     }
-    fn is_complete(&self) -> bool {
-        let staked = self.payouts.iter().map(|p| p.risked).sum::<Chips>();
-        let reward = self.payouts.iter().map(|p| p.reward).sum::<Chips>();
-        staked == reward
-    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::cards::kicks::Kickers;
     use crate::cards::rank::Rank;
+    use crate::cards::ranking::Ranking;
+    use crate::cards::strength::Strength;
     // Define functions for hand strengths
     fn ace_high() -> Strength {
         Strength::from((Ranking::HighCard(Rank::Ace), Kickers::default()))
@@ -239,4 +249,53 @@ mod tests {
         assert!(settlement[2].reward == 0);
         assert!(settlement[3].reward == 0);
     }
+
+    #[test]
+    fn three_way_all_in_breakdown() {
+        let ledger = || {
+            vec![
+                Settlement::from((050, State::Shoving, the_nuts())),
+                Settlement::from((100, State::Shoving, triplets())),
+                Settlement::from((100, State::Betting, one_pair())),
+            ]
+        };
+        let breakdown = Showdown::from(ledger()).breakdown();
+        // main pot is 3 x 50, the side pot 2 x 50 behind it
+        assert!(breakdown == vec![(0, 150, 0), (1, 100, 1)]);
+        let settlement = Showdown::from(ledger()).settle();
+        for (seat, settlement) in settlement.iter().enumerate() {
+            let awarded = breakdown
+                .iter()
+                .filter(|(s, _, _)| *s == seat)
+                .map(|(_, chips, _)| chips)
+                .sum::<Chips>();
+            assert!(awarded == settlement.reward);
+        }
+        let risked = ledger().iter().map(|s| s.risked).sum::<Chips>();
+        assert!(breakdown.iter().map(|(_, chips, _)| chips).sum::<Chips>() == risked);
+    }
+
+    #[test]
+    fn split_side_pot_breakdown() {
+        let breakdown = Showdown::from(vec![
+            Settlement::from((051, State::Shoving, the_nuts())),
+            Settlement::from((100, State::Betting, two_pair())),
+            Settlement::from((100, State::Betting, two_pair())),
+        ])
+        .breakdown();
+        // the side pot of 98 splits evenly, the main pot goes whole
+        assert!(breakdown == vec![(0, 153, 0), (1, 49, 1), (2, 49, 1)]);
+    }
+
+    #[test]
+    fn covering_winner_takes_each_pot_separately() {
+        let breakdown = Showdown::from(vec![
+            Settlement::from((50, State::Shoving, one_pair())),
+            Settlement::from((100, State::Shoving, two_pair())),
+            Settlement::from((150, State::Betting, the_nuts())),
+        ])
+        .breakdown();
+        // main pot 3 x 50, side pot 2 x 50, and the 50 nobody called
+        assert!(breakdown == vec![(2, 150, 0), (2, 100, 1), (2, 50, 2)]);
+    }
 }
@@ -9,6 +9,7 @@ pub mod cards;
 pub mod clustering;
 pub mod gameplay;
 pub mod mccfr;
+pub mod probability;
 pub mod search;
 pub mod transport;
 pub mod wasm;
@@ -19,20 +20,25 @@ type Equity = f32;
 type Energy = f32;
 type Entropy = f32;
 type Utility = f32;
-type Probability = f32;
+pub use probability::Probability;
 
 // game tree parameters
 const N: usize = 2;
 const STACK: Chips = 100;
 const B_BLIND: Chips = 2;
 const S_BLIND: Chips = 1;
+const ANTE: Chips = 0;
 const MAX_RAISE_REPEATS: usize = 3;
 const MAX_DEPTH_SUBGAME: usize = 16;
+const EQUITY_ROLLOUTS: usize = 16;
+const EQUITY_SAMPLES: usize = 0x4000;
+const EQUITY_ROLLOUT_CACHE: usize = 0x100000;
 
 /// sinkhorn optimal transport parameters
 const SINKHORN_TEMPERATURE: Entropy = 0.025;
 const SINKHORN_ITERATIONS: usize = 128;
 const SINKHORN_TOLERANCE: Energy = 0.001;
+const EXACT_TRANSPORT_SUPPORT: usize = 16;
 
 // kmeans clustering parameters
 const KMEANS_FLOP_TRAINING_ITERATIONS: usize = 20;
@@ -40,6 +46,7 @@ const KMEANS_TURN_TRAINING_ITERATIONS: usize = 24;
 const KMEANS_FLOP_CLUSTER_COUNT: usize = 128;
 const KMEANS_TURN_CLUSTER_COUNT: usize = 144;
 const KMEANS_EQTY_CLUSTER_COUNT: usize = 101;
+const KMEANS_METRIC_BATCH: usize = 0x400;
 
 // mccfr parameters
 const CFR_BATCH_SIZE: usize = 0x100;
@@ -49,6 +56,11 @@ const CFR_PRUNNING_PHASE: usize = 100_000_000 / CFR_BATCH_SIZE;
 const CFR_DISCOUNT_PHASE: usize = 100_000 / CFR_BATCH_SIZE;
 const MAIN_TRAINING_ITERATIONS: usize = CFR_ITERATIONS;
 const FINE_TRAINING_ITERATIONS: usize = 0x4000;
+const CFR_CHECKPOINT_INTERVAL: usize = 0x400;
+const CFR_CHECKPOINT_PERIOD: usize = 8;
+const CFR_CHECKPOINT_THRESHOLD: f32 = 1e-3;
+const CFR_CONCURRENT_UPDATES: bool = true;
+const CFR_OUTCOME_EXPLORATION: f32 = 0.6;
 
 // regret matching parameters
 const REGRET_MIN: Utility = -3e5;
@@ -60,19 +72,94 @@ pub trait Arbitrary {
     fn random() -> Self;
 }
 
+/// structured progress update, delivered to a progress hook
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProgressEvent {
+    pub phase: &'static str,
+    pub current: u64,
+    pub total: u64,
+    pub elapsed: std::time::Duration,
+}
+
+impl ProgressEvent {
+    /// linear extrapolation of remaining time
+    pub fn eta(&self) -> std::time::Duration {
+        match self.current {
+            0 => std::time::Duration::MAX,
+            n => self
+                .elapsed
+                .mul_f64((self.total.saturating_sub(n)) as f64 / n as f64),
+        }
+    }
+}
+
+/// thread-safe callback that receives ProgressEvents.
+/// each progress tracker carries its own, so an embedding
+/// application hooks exactly the work it starts
+pub type Hook = std::sync::Arc<dyn Fn(ProgressEvent) + Send + Sync>;
+
+/// terminal progress bar that also forwards events to its hook.
+/// increments are serialized so the hook observes them in order
+#[cfg(feature = "native")]
+pub struct Progress {
+    bar: indicatif::ProgressBar,
+    phase: &'static str,
+    hook: Option<Hook>,
+    count: std::sync::Mutex<u64>,
+    start: std::time::Instant,
+}
+
+#[cfg(feature = "native")]
+impl Progress {
+    /// name the phase reported in each ProgressEvent
+    pub fn with_phase(mut self, phase: &'static str) -> Self {
+        self.phase = phase;
+        self
+    }
+    /// forward every increment to this hook
+    pub fn with_hook(mut self, hook: Option<Hook>) -> Self {
+        self.hook = hook;
+        self
+    }
+    pub fn inc(&self, n: u64) {
+        self.bar.inc(n);
+        if let Some(ref hook) = self.hook {
+            let mut count = self.count.lock().expect("progress lock");
+            *count += n;
+            hook(ProgressEvent {
+                phase: self.phase,
+                current: *count,
+                total: self.bar.length().unwrap_or_default(),
+                elapsed: self.start.elapsed(),
+            });
+        }
+    }
+    pub fn finish(&self) {
+        self.bar.finish();
+    }
+}
+
 /// progress bar
 #[cfg(feature = "native")]
-pub fn progress(n: usize) -> indicatif::ProgressBar {
+pub fn progress(n: usize) -> Progress {
     let tick = std::time::Duration::from_secs(60);
     let style = "{spinner:.cyan} {elapsed} ~ {percent:>3}% {wide_bar:.cyan}";
     let style = indicatif::ProgressStyle::with_template(style).unwrap();
     let progress = indicatif::ProgressBar::new(n as u64);
     progress.set_style(style);
     progress.enable_steady_tick(tick);
-    progress
+    Progress {
+        bar: progress,
+        phase: "",
+        hook: None,
+        count: std::sync::Mutex::new(0),
+        start: std::time::Instant::now(),
+    }
 }
 
-/// initialize logging and exit on ctrl-c
+/// initialize logging and exit on ctrl-c.
+/// RUST_LOG sets the terminal level: info for phases,
+/// debug for iterations, trace for individual items.
 #[cfg(feature = "native")]
 pub fn init() {
     tokio::spawn(async move {
@@ -91,13 +178,17 @@ pub fn init() {
         .duration_since(std::time::UNIX_EPOCH)
         .expect("time moves slow")
         .as_secs();
+    let level = std::env::var("RUST_LOG")
+        .ok()
+        .and_then(|s| s.parse::<log::LevelFilter>().ok())
+        .unwrap_or(log::LevelFilter::Info);
     let file = simplelog::WriteLogger::new(
-        log::LevelFilter::Debug,
+        level.max(log::LevelFilter::Debug),
         config.clone(),
         std::fs::File::create(format!("logs/{}.log", time)).expect("create log file"),
     );
     let term = simplelog::TermLogger::new(
-        log::LevelFilter::Info,
+        level,
         config.clone(),
         simplelog::TerminalMode::Mixed,
         simplelog::ColorChoice::Auto,
@@ -117,3 +208,33 @@ pub async fn db() -> std::sync::Arc<tokio_postgres::Client> {
     tokio::spawn(connection);
     std::sync::Arc::new(client)
 }
+
+#[cfg(test)]
+#[cfg(feature = "native")]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::sync::Mutex;
+
+    #[test]
+    fn progress_hook_is_monotonic() {
+        use rayon::iter::IntoParallelIterator;
+        use rayon::iter::ParallelIterator;
+        let events = Arc::new(Mutex::new(Vec::<ProgressEvent>::new()));
+        let sink = events.clone();
+        let hook: Hook = Arc::new(move |e| sink.lock().unwrap().push(e));
+        let n = 256;
+        let progress = crate::progress(n).with_phase("test").with_hook(Some(hook));
+        let silent = crate::progress(n);
+        (0..n).into_par_iter().for_each(|_| progress.inc(1));
+        (0..n).into_par_iter().for_each(|_| silent.inc(1));
+        progress.finish();
+        silent.finish();
+        let events = events.lock().unwrap();
+        assert!(events.len() == n);
+        assert!(events.iter().all(|e| e.phase == "test"));
+        assert!(events.windows(2).all(|w| w[0].current < w[1].current));
+        assert!(events.iter().all(|e| e.total == n as u64));
+        assert!(events.last().unwrap().current == n as u64);
+    }
+}
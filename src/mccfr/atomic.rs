@@ -0,0 +1,114 @@
+use super::bucket::Bucket;
+use super::edge::Edge;
+use super::memory::Memory;
+use crate::Utility;
+use std::collections::BTreeMap;
+use std::sync::atomic::AtomicU32;
+use std::sync::atomic::Ordering;
+
+/// shared, lock-free view of every witnessed (Bucket, Edge).
+/// the key set is fixed once built, only the values move.
+pub type Accumulators = BTreeMap<Bucket, BTreeMap<Edge, AtomicMemory>>;
+
+/// lock-free counterpart of Memory, so that traversal threads
+/// can accumulate into shared Buckets without holding a write lock
+/// over the whole Profile.
+///
+/// there are no native f32 atomics, so each value is stored as its
+/// bit pattern in an AtomicU32 and updated with a compare-and-swap loop.
+/// additions commute, so concurrent adds land on the same total as
+/// sequential ones (up to f32 rounding order). discounting does not
+/// commute with adds, so it should be applied once per epoch via
+/// discount(), while no other thread is adding.
+#[derive(Debug, Default)]
+pub struct AtomicMemory {
+    regret: AtomicU32,
+    policy: AtomicU32,
+}
+
+impl AtomicMemory {
+    pub fn regret(&self) -> Utility {
+        f32::from_bits(self.regret.load(Ordering::Acquire))
+    }
+    pub fn policy(&self) -> f32 {
+        f32::from_bits(self.policy.load(Ordering::Acquire))
+    }
+    pub fn add_regret(&self, value: Utility) {
+        Self::update(&self.regret, |x| x + value);
+    }
+    pub fn add_policy(&self, value: f32) {
+        Self::update(&self.policy, |x| x + value);
+    }
+    /// discount in place. the regret factor is chosen from the
+    /// cumulative regret it scales, within the same compare-and-swap,
+    /// so that positive and negative regrets decay at their own rates
+    pub fn discount(&self, regret: impl Fn(Utility) -> f32, policy: f32) {
+        Self::update(&self.regret, |x| x * regret(x));
+        Self::update(&self.policy, |x| x * policy);
+    }
+    fn update(cell: &AtomicU32, f: impl Fn(f32) -> f32) {
+        cell.fetch_update(Ordering::AcqRel, Ordering::Acquire, |bits| {
+            Some(f(f32::from_bits(bits)).to_bits())
+        })
+        .expect("update always succeeds");
+    }
+}
+
+impl From<&Memory> for AtomicMemory {
+    fn from(memory: &Memory) -> Self {
+        Self {
+            regret: AtomicU32::new(memory.regret().to_bits()),
+            policy: AtomicU32::new(memory.policy().to_bits()),
+        }
+    }
+}
+
+impl From<&AtomicMemory> for Memory {
+    fn from(atomic: &AtomicMemory) -> Self {
+        Memory::from((atomic.regret(), atomic.policy()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn concurrent_adds_match_sequential() {
+        const THREADS: usize = 8;
+        const UPDATES: usize = 1000;
+        let atomic = AtomicMemory::from(&Memory::from((1., 1.)));
+        std::thread::scope(|scope| {
+            for _ in 0..THREADS {
+                scope.spawn(|| {
+                    for _ in 0..UPDATES {
+                        atomic.add_regret(0.5);
+                        atomic.add_policy(0.25);
+                    }
+                });
+            }
+        });
+        let mut sequential = Memory::from((1., 1.));
+        for _ in 0..THREADS * UPDATES {
+            sequential.add_regret(1., 0.5);
+            sequential.add_policy(1., 0.25);
+        }
+        assert!(Memory::from(&atomic) == sequential);
+    }
+
+    #[test]
+    fn discount_is_sign_aware() {
+        use crate::mccfr::discount::Discount;
+        let t = 4;
+        let dcfr = Discount::DCFR;
+        let gains = AtomicMemory::from(&Memory::from((2., 1.)));
+        let losses = AtomicMemory::from(&Memory::from((-2., 1.)));
+        for atomic in [&gains, &losses] {
+            atomic.discount(|x| dcfr.regret(t, x), dcfr.policy(t));
+        }
+        assert!(gains.regret() == 2. * dcfr.regret(t, 1.));
+        assert!(losses.regret() == -2. * dcfr.regret(t, -1.));
+        assert!(gains.regret() != -losses.regret());
+        assert!(gains.policy() == dcfr.policy(t));
+    }
+}
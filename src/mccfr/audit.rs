@@ -0,0 +1,37 @@
+use super::bucket::Bucket;
+
+/// post-load diagnostic over every Bucket in a Profile.
+/// stored policies are accumulated weights rather than
+/// probabilities, so "normalizable" means non-negative
+/// with a strictly positive sum. regrets may be negative
+/// under DCFR, but never below REGRET_MIN.
+#[derive(Debug, Default, PartialEq)]
+pub struct Audit {
+    pub nonfinite: Vec<Bucket>,
+    pub negative: Vec<Bucket>,
+    pub degenerate: Vec<Bucket>,
+    pub underflow: Vec<Bucket>,
+}
+
+impl Audit {
+    /// no corruption detected
+    pub fn is_empty(&self) -> bool {
+        self.nonfinite.is_empty()
+            && self.negative.is_empty()
+            && self.degenerate.is_empty()
+            && self.underflow.is_empty()
+    }
+    /// total number of flagged buckets, counted once per problem
+    pub fn len(&self) -> usize {
+        self.nonfinite.len() + self.negative.len() + self.degenerate.len() + self.underflow.len()
+    }
+}
+
+impl std::fmt::Display for Audit {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        writeln!(f, "{:<16}{:>10}", "non-finite", self.nonfinite.len())?;
+        writeln!(f, "{:<16}{:>10}", "negative policy", self.negative.len())?;
+        writeln!(f, "{:<16}{:>10}", "zero-sum policy", self.degenerate.len())?;
+        write!(f, "{:<16}{:>10}", "regret underflow", self.underflow.len())
+    }
+}
@@ -0,0 +1,28 @@
+use super::discount::Discount;
+use serde::Deserialize;
+use serde::Serialize;
+
+/// how each epoch's policy weighs into the average strategy.
+/// the accumulator is decayed ahead of every add, so an epoch
+/// counts as much as the product of the decays that follow it.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Averaging {
+    /// every epoch counts the same
+    Uniform,
+    /// epoch t counts t times over, as in Linear CFR
+    Linear,
+    /// decay by the Discount schedule, (t / (t + 1))^γ
+    #[default]
+    Discounted,
+}
+
+impl Averaging {
+    /// decay of the accumulated policy ahead of epoch t's add
+    pub fn policy(&self, discount: &Discount, t: usize) -> f32 {
+        match self {
+            Self::Uniform => 1.,
+            Self::Linear => t as f32 / (t as f32 + 1.),
+            Self::Discounted => discount.policy(t),
+        }
+    }
+}
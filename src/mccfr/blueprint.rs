@@ -7,13 +7,30 @@ use super::player::Player;
 use super::policy::Policy;
 use super::profile::Profile;
 use super::recall::Recall;
+use super::sampling::Sampling;
+use super::strategy::StrategyKind;
+use super::telemetry::EpochStats;
+use super::telemetry::Telemetry;
 use super::tree::Branch;
 use super::tree::Tree;
+use crate::cards::hand::Hand;
+use crate::cards::hole::Hole;
 use crate::cards::street::Street;
+use crate::cards::strength::Strength;
+use crate::gameplay::blinds::Blinds;
+use crate::gameplay::game::Game;
+use crate::gameplay::ply::Turn;
 use crate::Arbitrary;
+use crate::Utility;
+use crate::N;
+use petgraph::graph::NodeIndex;
+use std::collections::HashMap;
 use std::sync::Arc;
 use std::sync::RwLock;
 
+/// showdown Strengths of each rollout, keyed by everyone's hole cards and the board
+type Rollouts = RwLock<HashMap<([Hole; N], Hand), Vec<[Strength; N]>>>;
+
 /// this is how we learn the optimal strategy of
 /// the abstracted game. with the learned Encoder
 /// to abstract all Action and Game objects, we
@@ -30,9 +47,97 @@ use std::sync::RwLock;
 pub struct Blueprint {
     profile: Arc<RwLock<Profile>>,
     encoder: Encoder,
+    truncate_at: Option<Street>,
+    rollouts: Rollouts,
+    hook: Option<crate::Hook>,
+    telemetry: Option<Telemetry>,
+    concurrent: bool,
+    stopping: Option<(Tree, usize, Utility)>,
+    output: Option<std::path::PathBuf>,
 }
 
 impl Blueprint {
+    /// stop sampling Trees once betting on this Street closes.
+    /// the cut-off chance Nodes become leaves whose payoffs
+    /// are estimated from rollout Equity rather than showdown
+    pub fn with_truncation(mut self, street: Street) -> Self {
+        self.truncate_at = Some(street);
+        self
+    }
+
+    /// seed training Games with these blinds and antes
+    pub fn with_blinds(mut self, blinds: Blinds) -> Self {
+        self.encoder = self.encoder.with_blinds(blinds);
+        self
+    }
+
+    /// accumulate each epoch's updates into lock-free atomics from
+    /// every traversal thread, instead of applying them one at a time
+    /// under the Profile's write lock
+    pub fn with_concurrency(mut self, concurrent: bool) -> Self {
+        self.concurrent = concurrent;
+        self
+    }
+
+    /// sample our own decisions by this scheme. outcome sampling
+    /// walks a single trajectory per Tree instead of every Edge
+    pub fn with_sampling(self, sampling: Sampling) -> Self {
+        let _ = {
+            let mut profile = self.profile.write().unwrap();
+            *profile = std::mem::take(&mut *profile).with_sampling(sampling);
+        };
+        self
+    }
+
+    /// every so many epochs, log the exploitability of the average
+    /// strategy over this Tree, and stop training once it falls
+    /// below threshold. the Tree should be fully expanded, so that
+    /// best responses see every alternative
+    pub fn with_early_stopping(mut self, tree: Tree, every: usize, threshold: Utility) -> Self {
+        self.stopping = Some((tree, every, threshold));
+        self
+    }
+
+    /// exploitability of the average strategy over the early stopping
+    /// Tree. none until every decision in it has been witnessed
+    pub fn exploitability(&self) -> Option<Utility> {
+        let (tree, _, _) = self.stopping.as_ref()?;
+        let profile = self.profile.read().unwrap();
+        let ref root = tree.at(NodeIndex::new(0));
+        root.subtree()
+            .iter()
+            .filter(|node| matches!(node.player(), Player(Turn::Choice(_))))
+            .all(|node| profile.is_witnessed(node.bucket()))
+            .then(|| profile.exploitability(StrategyKind::Average, root))
+    }
+
+    /// write checkpoints and the trained Profile under this
+    /// directory rather than the default one, so that parallel
+    /// experiments don't clobber each other's files
+    pub fn with_output(mut self, dir: &std::path::Path) -> Self {
+        self.output = Some(dir.to_path_buf());
+        self
+    }
+
+    /// receive training progress events, from any thread
+    pub fn with_hook<F>(mut self, f: F) -> Self
+    where
+        F: Fn(crate::ProgressEvent) + Send + Sync + 'static,
+    {
+        self.hook = Some(Arc::new(f));
+        self
+    }
+
+    /// receive EpochStats after every epoch of solve, so that
+    /// regret and exploitability can be streamed to a file or TUI
+    pub fn with_telemetry<F>(mut self, f: F) -> Self
+    where
+        F: Fn(EpochStats) + Send + Sync + 'static,
+    {
+        self.telemetry = Some(Arc::new(f));
+        self
+    }
+
     /// after training, use the learned Profile to advise
     /// a Spot on how to play.
     pub fn policy(&self, recall: &Recall) -> Policy {
@@ -52,57 +157,180 @@ impl Blueprint {
         use crate::save::upload::Table;
         if Self::done(Street::random()) {
             log::info!("resuming regret minimization");
-            Self::load(Street::random()).solve(crate::FINE_TRAINING_ITERATIONS);
+            Self::load_or_panic(Street::random())
+                .with_concurrency(crate::CFR_CONCURRENT_UPDATES)
+                .solve(crate::FINE_TRAINING_ITERATIONS);
         } else {
             log::info!("starting regret minimization");
-            Self::grow(Street::random()).solve(crate::MAIN_TRAINING_ITERATIONS);
+            Self::grow(Street::random())
+                .with_concurrency(crate::CFR_CONCURRENT_UPDATES)
+                .solve(crate::MAIN_TRAINING_ITERATIONS);
         }
     }
 
-    /// the main training loop.
+    /// the main training loop, for t epochs.
     #[cfg(feature = "native")]
-    fn solve(self, t: usize) -> Self {
+    pub fn solve(self, t: usize) -> Self {
         log::info!("beginning training loop");
         use crate::save::upload::Table;
-        let progress = crate::progress(t * crate::CFR_BATCH_SIZE);
+        let batch = self.profile.read().unwrap().sampling().batch();
+        let progress = crate::progress(t * batch)
+            .with_phase("cfr")
+            .with_hook(self.hook.clone());
+        let ref dir = self.output();
+        let ref path = format!("{}.checkpoint", Profile::path_in(dir, Street::random()));
+        let mut checkpoint = super::checkpoint::Checkpoint::from(path.as_str());
         for _ in 0..t {
-            let counterfactuals = self.simulations();
-            let mut profile = self.profile.write().unwrap();
-            for counterfactual in counterfactuals {
-                let ref regret = counterfactual.regret();
-                let ref policy = counterfactual.policy();
-                let ref bucket = counterfactual.info().node().bucket().clone();
-                profile.add_regret(bucket, regret);
-                profile.add_policy(bucket, policy);
-                progress.inc(1);
+            let epoch = match self.concurrent {
+                true => self.step_concurrent(),
+                false => self.step(),
+            };
+            progress.inc(batch as u64);
+            if epoch.is_multiple_of(crate::CFR_CHECKPOINT_INTERVAL) {
+                checkpoint.save(&self.profile.read().unwrap());
             }
-            {
-                log::debug!(
-                    "epoch {:<10} touched {:<10}",
-                    profile.next(),
-                    profile.size()
-                );
+            let exploitability = self.measure(epoch);
+            self.report(epoch, exploitability);
+            if self.stops(exploitability) {
+                log::info!("stopping early at iteration {}", epoch);
+                break;
             }
         }
         progress.finish();
-        self.profile.read().unwrap().save();
+        self.profile.read().unwrap().save_to(dir);
         self
     }
 
+    /// directory that training writes into
+    #[cfg(feature = "native")]
+    fn output(&self) -> std::path::PathBuf {
+        self.output
+            .clone()
+            .unwrap_or_else(crate::save::upload::directory)
+    }
+
+    /// exploitability of the average strategy, on the
+    /// epochs that early stopping is configured to check
+    #[cfg(feature = "native")]
+    fn measure(&self, epoch: usize) -> Option<Utility> {
+        match self.stopping {
+            Some((_, every, _)) if epoch.is_multiple_of(every) => {
+                let exploitability = self.exploitability()?;
+                log::info!(
+                    "phase=cfr iteration={} exploitability={}",
+                    epoch,
+                    exploitability
+                );
+                Some(exploitability)
+            }
+            _ => None,
+        }
+    }
+
+    /// whether a measured exploitability is below threshold
+    #[cfg(feature = "native")]
+    fn stops(&self, exploitability: Option<Utility>) -> bool {
+        match (&self.stopping, exploitability) {
+            (Some((_, _, threshold)), Some(exploitability)) => exploitability < *threshold,
+            _ => false,
+        }
+    }
+
+    /// hand this epoch's EpochStats to the telemetry callback, if any.
+    /// regret magnitudes scan the whole Profile, so skip them otherwise
+    #[cfg(feature = "native")]
+    fn report(&self, epoch: usize, exploitability: Option<Utility>) {
+        if let Some(ref telemetry) = self.telemetry {
+            let profile = self.profile.read().unwrap();
+            let (mean_regret, max_regret) = profile.regret_magnitude();
+            telemetry(EpochStats {
+                epoch,
+                touched: profile.size(),
+                mean_regret,
+                max_regret,
+                exploitability,
+            });
+        }
+    }
+
+    /// one epoch: sample a batch of Trees and apply
+    /// their regret and policy updates to the Profile
+    #[cfg(feature = "native")]
+    fn step(&self) -> usize {
+        let counterfactuals = self.simulations();
+        let mut profile = self.profile.write().unwrap();
+        profile.apply(counterfactuals);
+        let epoch = profile.next();
+        log::debug!("phase=cfr iteration={} touched={}", epoch, profile.size());
+        epoch
+    }
+
+    /// one epoch, with updates accumulated concurrently. Trees are
+    /// sampled first, so that every Bucket they touch is witnessed
+    /// before the atomics are laid out. counterfactuals then read the
+    /// Profile as of the start of the epoch, just as in step()
+    #[cfg(feature = "native")]
+    fn step_concurrent(&self) -> usize {
+        use rayon::iter::IntoParallelIterator;
+        use rayon::iter::ParallelIterator;
+        let batch = self.profile.read().unwrap().sampling().batch();
+        let infos = (0..batch)
+            .into_par_iter()
+            .map(|_| self.tree())
+            .map(Partition::from)
+            .map(Vec::<Info>::from)
+            .flatten()
+            .collect::<Vec<Info>>();
+        let accumulators = {
+            let profile = self.profile.read().unwrap();
+            let buckets = infos
+                .iter()
+                .map(|info| info.node().bucket().clone())
+                .collect::<std::collections::BTreeSet<_>>();
+            let accumulators = profile.accumulators(buckets.iter());
+            profile.decay(&accumulators);
+            accumulators
+        };
+        let counterfactuals = infos
+            .into_par_iter()
+            .map(|info| {
+                let profile = self.profile.read().unwrap();
+                let ref bucket = info.node().bucket().clone();
+                let counterfactual = profile.counterfactual(info);
+                let ref regret = counterfactual.regret();
+                let ref policy = counterfactual.policy();
+                profile.accumulate(&accumulators, bucket, regret, policy);
+                counterfactual
+            })
+            .collect::<Vec<Counterfactual>>();
+        let mut profile = self.profile.write().unwrap();
+        profile.commit(&accumulators);
+        for counterfactual in counterfactuals {
+            let ref bucket = counterfactual.info().node().bucket().clone();
+            profile.observe(bucket, counterfactual.regret());
+        }
+        let epoch = profile.next();
+        log::debug!("phase=cfr iteration={} touched={}", epoch, profile.size());
+        epoch
+    }
+
     /// compute regret and policy updates for a batch of Trees.
+    /// Trees are sampled in parallel, then every Infoset of the
+    /// batch is evaluated in parallel against the same Profile
     #[cfg(feature = "native")]
     fn simulations(&self) -> Vec<Counterfactual> {
         use rayon::iter::IntoParallelIterator;
         use rayon::iter::ParallelIterator;
-        (0..crate::CFR_BATCH_SIZE)
+        let batch = self.profile.read().unwrap().sampling().batch();
+        let infos = (0..batch)
             .into_par_iter() // Now we can parallelize the search itself!
             .map(|_| self.tree())
             .inspect(|tree| log::trace!("{}", tree))
             .map(Partition::from)
             .map(Vec::<Info>::from)
             .flatten()
-            .map(|info| self.profile.read().unwrap().counterfactual(info))
-            .collect::<Vec<Counterfactual>>()
+            .collect::<Vec<Info>>();
+        self.profile.read().unwrap().counterfactuals(infos)
     }
 
     /// Build the Tree iteratively starting from the root node.
@@ -113,7 +341,7 @@ impl Blueprint {
         let ref root = tree.plant(self.encoder.seed());
         let mut todo = self.sample(root);
         while let Some(branch) = todo.pop() {
-            let ref node = tree.fork(branch);
+            let ref node = tree.fork(self.truncate(branch));
             let children = self.sample(node);
             todo.extend(children);
         }
@@ -129,6 +357,9 @@ impl Blueprint {
     fn sample(&self, node: &Node) -> Vec<Branch> {
         let chance = Player::chance();
         let walker = { self.profile.read().unwrap().walker() };
+        if node.data().estimate().is_some() {
+            return vec![];
+        }
         let branches = self.encoder.branches(node);
         match (branches.len(), node.player()) {
             (0, _) => vec![],
@@ -139,6 +370,42 @@ impl Blueprint {
         }
     }
 
+    /// whether this Game is about to deal past the truncation Street
+    fn truncates(&self, game: &Game) -> bool {
+        self.truncate_at
+            .map(|street| game.turn() == Turn::Chance && game.street() >= street)
+            .unwrap_or(false)
+    }
+
+    /// cut the Branch off with estimated payoffs, if it truncates
+    fn truncate(&self, branch: Branch) -> Branch {
+        match self.truncates(branch.0.game()) {
+            true => {
+                let estimate = self.estimate(branch.0.game());
+                Branch(branch.0.truncate(estimate), branch.1, branch.2)
+            }
+            false => branch,
+        }
+    }
+
+    /// rollout pnls of a truncated Game. the rollouts only depend on
+    /// the cards, so they are shared by every Tree that deals them.
+    /// the cache is dropped wholesale once it grows past its bound
+    fn estimate(&self, game: &Game) -> Vec<Utility> {
+        let key = (game.holes(), Hand::from(game.board()));
+        let cached = self.rollouts.read().unwrap().get(&key).cloned();
+        let rollouts = cached.unwrap_or_else(|| {
+            let rollouts = game.rollouts();
+            let mut cache = self.rollouts.write().unwrap();
+            if cache.len() >= crate::EQUITY_ROLLOUT_CACHE {
+                cache.clear();
+            }
+            cache.insert(key, rollouts.clone());
+            rollouts
+        });
+        game.estimate(&rollouts)
+    }
+
     fn touch_any(&self, branches: Vec<Branch>, node: &Node) -> Vec<Branch> {
         self.profile.read().unwrap().explore_any(branches, node)
     }
@@ -161,7 +428,7 @@ impl crate::save::upload::Table for Blueprint {
     }
 
     fn save(&self) {
-        self.profile.read().unwrap().save();
+        self.profile.read().unwrap().save_to(&self.output());
         self.encoder.save();
     }
 
@@ -170,17 +437,31 @@ impl crate::save::upload::Table for Blueprint {
         // but not necessarily a profile
         Self {
             profile: Arc::new(RwLock::new(Profile::default())),
-            encoder: Encoder::load(Street::random()),
+            encoder: Encoder::load_or_panic(Street::random()),
+            truncate_at: None,
+            rollouts: Rollouts::default(),
+            hook: None,
+            telemetry: None,
+            concurrent: false,
+            stopping: None,
+            output: None,
         }
     }
 
-    fn load(_: Street) -> Self {
+    fn load(_: Street) -> Result<Self, crate::save::upload::LoadError> {
         // basically the same as grow but w the expectation
         // that profile is trained & loaded
-        Self {
-            profile: Arc::new(RwLock::new(Profile::load(Street::random()))),
-            encoder: Encoder::load(Street::random()),
-        }
+        Ok(Self {
+            profile: Arc::new(RwLock::new(Profile::load(Street::random())?)),
+            encoder: Encoder::load(Street::random())?,
+            truncate_at: None,
+            rollouts: Rollouts::default(),
+            hook: None,
+            telemetry: None,
+            concurrent: false,
+            stopping: None,
+            output: None,
+        })
     }
 
     fn name() -> String {
@@ -207,3 +488,139 @@ impl crate::save::upload::Table for Blueprint {
         unimplemented!()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clustering::abstraction::Abstraction;
+    use crate::mccfr::data::Data;
+    use crate::mccfr::edge::Edge;
+
+    #[test]
+    fn flop_truncation_estimates_leaves() {
+        let blueprint = Blueprint::default().with_truncation(Street::Flop);
+        let abstraction = Abstraction::from((Street::Pref, 0));
+        let mut tree = Tree::empty(Player::default());
+        let mut head = tree.plant(Data::from((Game::root(), abstraction))).index();
+        // check or call along, until sampling would stop
+        while tree.at(head).data().estimate().is_none() {
+            let (edge, game) = tree
+                .at(head)
+                .branches()
+                .into_iter()
+                .find(|(e, _)| matches!(e, Edge::Check | Edge::Call | Edge::Draw))
+                .expect("passive line to showdown");
+            let branch = Branch(Data::from((game, abstraction)), edge, head);
+            head = tree.fork(blueprint.truncate(branch)).index();
+        }
+        let leaf = tree.at(head);
+        let game = leaf.data().game();
+        assert!(game.street() == Street::Flop);
+        assert!(game.turn() == Turn::Chance);
+        assert!(blueprint.sample(&leaf).is_empty());
+        assert!(tree
+            .all()
+            .iter()
+            .all(|n| n.data().game().street() < Street::Turn));
+        let ref p0 = Player(Turn::Choice(0));
+        let ref p1 = Player(Turn::Choice(1));
+        let estimate = game.estimate(&game.rollouts());
+        assert!(leaf.payoff(p0) == estimate[0]);
+        assert!(leaf.payoff(p1) == estimate[1]);
+        assert!((leaf.payoff(p0) + leaf.payoff(p1)).abs() < 1e-4);
+        assert!(blueprint.rollouts.read().unwrap().len() == 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "non terminal game state")]
+    fn untruncated_leaves_have_no_payoff() {
+        let abstraction = Abstraction::from((Street::Pref, 0));
+        let mut tree = Tree::empty(Player::default());
+        let root = tree.plant(Data::from((Game::root(), abstraction)));
+        root.payoff(&Player(Turn::Choice(0)));
+    }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn telemetry_fires_once_per_epoch() {
+        use crate::cards::isomorphisms::IsomorphismIterator;
+        use std::sync::Mutex;
+        let ref dir = std::env::temp_dir().join("robopoker-telemetry");
+        std::fs::create_dir_all(dir).unwrap();
+        let encoder = Encoder::from(
+            IsomorphismIterator::from(Street::Pref)
+                .enumerate()
+                .map(|(i, iso)| (iso, Abstraction::from((Street::Pref, i))))
+                .collect::<std::collections::BTreeMap<_, _>>(),
+        );
+        let stats = Arc::new(Mutex::new(Vec::<EpochStats>::new()));
+        let sink = stats.clone();
+        Blueprint {
+            encoder,
+            truncate_at: Some(Street::Pref),
+            ..Blueprint::default()
+        }
+        .with_sampling(Sampling::Outcome)
+        .with_output(dir)
+        .with_telemetry(move |s| sink.lock().unwrap().push(s))
+        .solve(3);
+        let stats = stats.lock().unwrap();
+        assert!(stats.iter().map(|s| s.epoch).eq(1..=3));
+        assert!(stats.windows(2).all(|w| w[0].touched <= w[1].touched));
+        assert!(stats.iter().all(|s| s.mean_regret <= s.max_regret));
+        assert!(stats.iter().all(|s| s.exploitability.is_none()));
+        assert!(stats[2].max_regret > 0.);
+        std::fs::remove_dir_all(dir).ok();
+    }
+
+    /// mean probability that the first player to act folds,
+    /// over every preflop hand class, after training the
+    /// preflop game alone under these blinds and antes
+    #[cfg(feature = "native")]
+    fn opening_folds(blinds: Blinds, epochs: usize, concurrent: bool) -> f32 {
+        use crate::cards::isomorphisms::IsomorphismIterator;
+        use crate::mccfr::path::Path;
+        let encoder = Encoder::from(
+            IsomorphismIterator::from(Street::Pref)
+                .enumerate()
+                .map(|(i, iso)| (iso, Abstraction::from((Street::Pref, i))))
+                .collect::<std::collections::BTreeMap<_, _>>(),
+        );
+        let blueprint = Blueprint {
+            encoder: encoder.with_blinds(blinds),
+            truncate_at: Some(Street::Pref),
+            concurrent,
+            ..Blueprint::default()
+        };
+        for _ in 0..epochs {
+            match blueprint.concurrent {
+                true => blueprint.step_concurrent(),
+                false => blueprint.step(),
+            };
+        }
+        let profile = blueprint.profile.read().unwrap();
+        let openings = profile
+            .buckets()
+            .filter(|bucket| bucket.0 == Path::default())
+            .map(|bucket| f32::from(profile.weight(bucket, &Edge::Fold)))
+            .collect::<Vec<f32>>();
+        assert!(openings.len() > 100);
+        openings.iter().sum::<f32>() / openings.len() as f32
+    }
+
+    #[test]
+    #[ignore]
+    #[cfg(feature = "native")]
+    /// slow in debug builds, so run it with --release --ignored
+    fn antes_loosen_openings() {
+        const EPOCHS: usize = 64;
+        let blinds = Blinds::default();
+        let antes = Blinds::from((blinds.sb, blinds.bb, blinds.bb));
+        assert!(antes.pot() > blinds.pot());
+        for concurrent in [false, true] {
+            let tight = opening_folds(blinds, EPOCHS, concurrent);
+            let loose = opening_folds(antes, EPOCHS, concurrent);
+            assert!(loose < tight, "{} !< {}", loose, tight);
+        }
+    }
+}
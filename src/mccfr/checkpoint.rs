@@ -0,0 +1,177 @@
+use super::bucket::Bucket;
+use super::edge::Edge;
+use super::profile::Profile;
+use crate::Probability;
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+
+/// periodic blueprint checkpoints that mostly write deltas.
+///
+/// a full PGCOPY snapshot is written at path every period checkpoints.
+/// in between, path.delta.k holds only the Buckets whose averaged
+/// strategy moved by more than threshold since the snapshot, with
+/// deltas applied, was last written. rows keep the blueprint layout,
+/// so a delta is itself a (partial) blueprint file.
+///
+/// each delta overwrites whole strategies, so loading is the snapshot
+/// with every delta laid over it in order. a new snapshot is written
+/// beside path and renamed over it, so a crash mid-write leaves the
+/// previous snapshot intact; only then are its stale deltas deleted.
+///
+/// between snapshots we keep only the averaged strategy vectors last
+/// written per Bucket, rather than a second copy of the Profile.
+pub struct Checkpoint {
+    path: String,
+    threshold: Probability,
+    period: usize,
+    deltas: usize,
+    written: Option<BTreeMap<Bucket, BTreeMap<Edge, Probability>>>,
+}
+
+impl From<&str> for Checkpoint {
+    fn from(path: &str) -> Self {
+        Self {
+            path: path.to_string(),
+            threshold: Probability::from(crate::CFR_CHECKPOINT_THRESHOLD),
+            period: crate::CFR_CHECKPOINT_PERIOD,
+            deltas: 0,
+            written: None,
+        }
+    }
+}
+
+impl Checkpoint {
+    /// record Buckets whose strategy moved by more than this
+    pub fn with_threshold(mut self, threshold: Probability) -> Self {
+        self.threshold = threshold;
+        self
+    }
+    /// write a full snapshot every this many checkpoints
+    pub fn with_period(mut self, period: usize) -> Self {
+        assert!(period > 0, "period must be positive");
+        self.period = period;
+        self
+    }
+    /// sidecar path of the k-th delta since the last snapshot
+    pub fn delta(path: &str, k: usize) -> String {
+        format!("{}.delta.{}", path, k)
+    }
+    /// write a snapshot or a delta, whichever is due.
+    /// returns the number of Buckets written
+    pub fn save(&mut self, profile: &Profile) -> usize {
+        match self.written.as_mut() {
+            Some(written) if self.deltas + 1 < self.period => {
+                let changed = profile
+                    .strategy_delta(written)
+                    .into_iter()
+                    .filter(|(_, delta)| *delta > self.threshold)
+                    .map(|(bucket, _)| bucket)
+                    .collect::<BTreeSet<_>>();
+                let delta = profile.subset(changed.iter());
+                self.deltas += 1;
+                let ref path = Self::delta(&self.path, self.deltas);
+                log::info!("{:<32}{:<32}", "saving      checkpoint", path);
+                delta.write(path);
+                profile.write_metadata(&self.path);
+                written.extend(profile.averages(changed.iter()));
+                changed.len()
+            }
+            _ => {
+                log::info!("{:<32}{:<32}", "saving      checkpoint", self.path);
+                let ref temp = format!("{}.tmp", self.path);
+                profile.write(temp);
+                std::fs::rename(temp, &self.path).expect("rename snapshot");
+                Self::clear(&self.path);
+                profile.write_metadata(&self.path);
+                self.deltas = 0;
+                self.written = Some(profile.averages(profile.buckets()));
+                profile.size()
+            }
+        }
+    }
+    /// the snapshot at path with its deltas applied in order
+    pub fn load(path: &str) -> Result<Profile, crate::save::upload::LoadError> {
+        log::info!("{:<32}{:<32}", "loading     checkpoint", path);
+        let mut profile = Profile::resume(path)?;
+        for k in 1.. {
+            let ref delta = Self::delta(path, k);
+            if !std::path::Path::new(delta).exists() {
+                break;
+            }
+            profile.overlay(Profile::read(delta)?);
+        }
+        Ok(profile)
+    }
+    /// remove the deltas of a stale snapshot
+    fn clear(path: &str) {
+        for k in 1.. {
+            if std::fs::remove_file(Self::delta(path, k)).is_err() {
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mccfr::policy::Policy;
+    use crate::mccfr::regret::Regret;
+    use crate::Arbitrary;
+    use rand::Rng;
+
+    /// train a few Buckets on random updates
+    fn perturb(profile: &mut Profile, buckets: &[Bucket]) {
+        let mut rng = rand::thread_rng();
+        for bucket in buckets {
+            let edges = profile
+                .policy(bucket)
+                .inner()
+                .keys()
+                .copied()
+                .collect::<Vec<Edge>>();
+            let regret = edges
+                .iter()
+                .map(|e| (*e, rng.gen_range(-1.0..1.0)))
+                .collect::<BTreeMap<_, _>>();
+            let policy = edges
+                .iter()
+                .map(|e| (*e, Probability::from(rng.gen_range(0.0..1.0))))
+                .collect::<BTreeMap<_, _>>();
+            profile.add_regret(bucket, &Regret::from(regret));
+            profile.add_policy(bucket, &Policy::from(policy));
+        }
+        profile.next();
+    }
+
+    #[test]
+    fn snapshot_and_deltas_reconstruct() {
+        let ref dir = std::env::temp_dir().join("robopoker-checkpoint");
+        std::fs::create_dir_all(dir).unwrap();
+        let path = |name: &str| dir.join(name).to_string_lossy().to_string();
+        let (ref base, ref full, ref loaded) = (path("base"), path("full"), path("loaded"));
+        let mut checkpoint = Checkpoint::from(base.as_str())
+            .with_threshold(Probability::ZERO)
+            .with_period(3);
+        let mut profile = Profile::random();
+        let buckets = profile
+            .buckets()
+            .filter(|b| profile.policy(b).inner().len() > 1)
+            .copied()
+            .collect::<Vec<Bucket>>();
+        assert!(checkpoint.save(&profile) == profile.size());
+        perturb(&mut profile, &buckets[..10]);
+        assert!(checkpoint.save(&profile) == 10);
+        perturb(&mut profile, &buckets[5..20]);
+        assert!(checkpoint.save(&profile) == 15);
+        assert!(std::path::Path::new(&Checkpoint::delta(base, 2)).exists());
+        profile.write(full);
+        Checkpoint::load(base).unwrap().write(loaded);
+        assert!(std::fs::read(full).unwrap() == std::fs::read(loaded).unwrap());
+        perturb(&mut profile, &buckets[..1]);
+        assert!(checkpoint.save(&profile) == profile.size());
+        assert!(!std::path::Path::new(&Checkpoint::delta(base, 1)).exists());
+        assert!(!std::path::Path::new(&format!("{}.tmp", base)).exists());
+        std::fs::remove_dir_all(dir).ok();
+    }
+}
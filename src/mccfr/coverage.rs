@@ -0,0 +1,58 @@
+use crate::cards::street::Street;
+use crate::clustering::abstraction::Abstraction;
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+
+/// how much of the abstraction a Profile has actually learned.
+///
+/// enumerating every reachable Bucket would mean walking the betting
+/// tree under every Abstraction, and the betting skeleton alone runs
+/// to millions of Nodes. so per Street we compare Abstractions: those
+/// the Encoder can produce are reachable, and those carried by some
+/// witnessed Bucket are covered. witnessed Buckets whose regrets and
+/// policies were never updated still play uniform, so they are
+/// counted as untrained.
+#[derive(Debug, Default, PartialEq)]
+pub struct Coverage {
+    pub reachable: BTreeMap<Street, BTreeSet<Abstraction>>,
+    pub covered: BTreeMap<Street, BTreeSet<Abstraction>>,
+    pub witnessed: BTreeMap<Street, usize>,
+    pub untrained: BTreeMap<Street, usize>,
+}
+
+impl Coverage {
+    /// fraction of a Street's reachable Abstractions
+    /// that no witnessed Bucket carries
+    pub fn uncovered(&self, street: Street) -> f32 {
+        let ref none = BTreeSet::new();
+        let reachable = self.reachable.get(&street).unwrap_or(none);
+        let covered = self.covered.get(&street).unwrap_or(none);
+        match reachable.len() {
+            0 => 0.,
+            n => 1. - reachable.intersection(covered).count() as f32 / n as f32,
+        }
+    }
+}
+
+impl std::fmt::Display for Coverage {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{:<8}{:>10}{:>10}{:>10}{:>10}{:>10}",
+            "street", "reachable", "covered", "uncovered", "witnessed", "untrained"
+        )?;
+        for street in Street::all() {
+            writeln!(
+                f,
+                "{:<8}{:>10}{:>10}{:>9.1}%{:>10}{:>10}",
+                street,
+                self.reachable.get(street).map(BTreeSet::len).unwrap_or(0),
+                self.covered.get(street).map(BTreeSet::len).unwrap_or(0),
+                100. * self.uncovered(*street),
+                self.witnessed.get(street).copied().unwrap_or(0),
+                self.untrained.get(street).copied().unwrap_or(0),
+            )?;
+        }
+        Ok(())
+    }
+}
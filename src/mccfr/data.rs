@@ -2,6 +2,7 @@ use super::bucket::Bucket;
 use crate::clustering::abstraction::Abstraction;
 use crate::gameplay::game::Game;
 use crate::mccfr::player::Player;
+use crate::Utility;
 
 #[derive(Debug)]
 pub struct Data {
@@ -11,6 +12,9 @@ pub struct Data {
     /// because it requires global information as a
     /// rank-1 hypergraph quantity
     infoset: Option<Bucket>,
+    /// payoffs of a leaf cut off by a truncated Tree,
+    /// estimated once when the Tree is built
+    estimate: Option<Vec<Utility>>,
 }
 
 impl From<(Game, Abstraction)> for Data {
@@ -19,6 +23,7 @@ impl From<(Game, Abstraction)> for Data {
             game,
             cluster,
             infoset: None,
+            estimate: None,
         }
     }
 }
@@ -47,4 +52,12 @@ impl Data {
     pub fn abstraction(&self) -> &Abstraction {
         &self.cluster
     }
+    /// mark a non-terminal Game as a leaf with estimated payoffs
+    pub fn truncate(mut self, estimate: Vec<Utility>) -> Self {
+        self.estimate = Some(estimate);
+        self
+    }
+    pub fn estimate(&self) -> Option<&[Utility]> {
+        self.estimate.as_deref()
+    }
 }
@@ -1,22 +1,43 @@
 use crate::Utility;
+use serde::Deserialize;
+use serde::Serialize;
 
-#[derive(Debug)]
+/// discounting schedule for regret and policy accumulation.
+/// at iteration t, positive regrets decay by t^α / (t^α + 1),
+/// negative regrets by t^β / (t^β + 1), and the average
+/// strategy by (t / (t + 1))^γ. Linear CFR is the special
+/// case α = β = γ = 1, where iteration t contributes with
+/// weight proportional to t.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct Discount {
     period: usize, // interval between strategy updates.
-    alpha: f32,    // α parameter. controls recency bias.
-    omega: f32,    // ω parameter. controls recency bias.
-    gamma: f32,    // γ parameter. controls recency bias.
+    alpha: f32,    // α parameter. decay of positive regrets.
+    #[serde(alias = "omega")]
+    beta: f32, // β parameter. decay of negative regrets.
+    gamma: f32,    // γ parameter. decay of the average strategy.
 }
 
-impl Discount {
-    pub const fn default() -> &'static Self {
-        &Self {
-            period: 1,
-            alpha: 1.5,
-            omega: 0.5,
-            gamma: 2.0,
-        }
+impl Default for Discount {
+    fn default() -> Self {
+        Self::DCFR
     }
+}
+
+impl Discount {
+    /// discounted CFR with the recommended (1.5, 0.0, 2.0) parameters
+    pub const DCFR: Self = Self {
+        period: 1,
+        alpha: 1.5,
+        beta: 0.0,
+        gamma: 2.0,
+    };
+    /// linear CFR. both regrets and policies are t-weighted
+    pub const LINEAR: Self = Self {
+        period: 1,
+        alpha: 1.0,
+        beta: 1.0,
+        gamma: 1.0,
+    };
 
     pub fn policy(&self, t: usize) -> f32 {
         (t as f32 / (t as f32 + 1.)).powf(self.gamma)
@@ -29,10 +50,152 @@ impl Discount {
             let x = (t as f32 / self.period as f32).powf(self.alpha);
             x / (x + 1.)
         } else if regret < 0. {
-            let x = (t as f32 / self.period as f32).powf(self.omega);
+            let x = (t as f32 / self.period as f32).powf(self.beta);
             x / (x + 1.)
         } else {
             1.
         }
     }
 }
+
+/// any (α, β, γ) regime, discounting every iteration
+impl From<(f32, f32, f32)> for Discount {
+    fn from((alpha, beta, gamma): (f32, f32, f32)) -> Self {
+        Self {
+            period: 1,
+            alpha,
+            beta,
+            gamma,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mccfr::memory::Memory;
+
+    #[test]
+    fn dcfr_scales_by_sign() {
+        let dcfr = Discount::from((1.5, 0., 2.));
+        assert!(dcfr == Discount::DCFR);
+        assert!(dcfr == Discount::default());
+        let t = 4;
+        assert!(dcfr.regret(t, 1.) == 8. / 9.);
+        assert!(dcfr.regret(t, -1.) == 1. / 2.);
+        assert!(dcfr.regret(t, 0.) == 1.);
+        assert!(dcfr.policy(t) == (4f32 / 5.).powf(2.));
+        assert!(Discount::from((1., 1., 1.)) == Discount::LINEAR);
+    }
+
+    #[test]
+    fn legacy_omega_deserializes() {
+        let json = r#"{"period":1,"alpha":1.5,"omega":0.5,"gamma":2.0}"#;
+        let legacy = serde_json::from_str::<Discount>(json).unwrap();
+        assert!(legacy == Discount::from((1.5, 0.5, 2.)));
+    }
+
+    #[test]
+    fn linear_regret_is_t_weighted() {
+        let discount = Discount::LINEAR;
+        let mut memory = Memory::default();
+        let n = 64;
+        for t in 1..=n {
+            memory.add_regret(discount.regret(t, 1.), 1.);
+        }
+        let expected = (1..=n).map(|t| (t + 1) as f32).sum::<f32>() / (n + 1) as f32;
+        assert!((memory.regret() - expected).abs() < 1e-3);
+    }
+
+    #[test]
+    fn linear_policy_is_t_weighted() {
+        let discount = Discount::LINEAR;
+        let n = 9;
+        let impulse = |s: usize| {
+            let mut memory = Memory::default();
+            for t in 1..=n {
+                let value = if t == s { 1. } else { 0. };
+                memory.add_policy(discount.policy(t), value);
+            }
+            memory.policy()
+        };
+        assert!((impulse(9) / impulse(1) - 5.).abs() < 1e-4);
+    }
+
+    /// self-play of Rock Paper Scissors under Linear CFR.
+    /// the row player starts with a regret for Rock so that
+    /// the dynamics have somewhere to converge from.
+    #[test]
+    fn linear_converges_on_rps() {
+        const PAYOFF: [[Utility; 3]; 3] = [[0., -1., 1.], [1., 0., -1.], [-1., 1., 0.]];
+        let discount = Discount::LINEAR;
+        let matching = |memories: &[Memory; 3]| {
+            let positive = memories.clone().map(|m| m.regret().max(0.));
+            let total = positive.iter().sum::<Utility>();
+            match total > 0. {
+                true => positive.map(|r| r / total),
+                false => [1. / 3.; 3],
+            }
+        };
+        let mut players = [
+            [Memory::from((1., 0.)), Memory::default(), Memory::default()],
+            [Memory::default(), Memory::default(), Memory::default()],
+        ];
+        let mut reference = [[0f64; 3]; 2];
+        for t in 0..0x10000 {
+            let strategies = [matching(&players[0]), matching(&players[1])];
+            for (hero, memories) in players.iter_mut().enumerate() {
+                let villain = strategies[1 - hero];
+                let values = PAYOFF.map(|row| (0..3).map(|j| row[j] * villain[j]).sum::<Utility>());
+                let expected = (0..3)
+                    .map(|a| values[a] * strategies[hero][a])
+                    .sum::<Utility>();
+                for (a, memory) in memories.iter_mut().enumerate() {
+                    let regret = values[a] - expected;
+                    memory.add_regret(discount.regret(t, regret), regret);
+                    memory.add_policy(discount.policy(t), strategies[hero][a]);
+                    reference[hero][a] += (t + 1) as f64 * strategies[hero][a] as f64;
+                }
+            }
+        }
+        let averages = players.clone().map(|memories| {
+            let total = memories.iter().map(Memory::policy).sum::<f32>();
+            memories.map(|m| m.policy() / total)
+        });
+        for (average, reference) in averages.iter().zip(reference.iter()) {
+            let total = reference.iter().sum::<f64>();
+            for (p, r) in average.iter().zip(reference.iter()) {
+                assert!((*p as f64 - r / total).abs() < 1e-4);
+            }
+        }
+        let exploitability = averages
+            .iter()
+            .rev()
+            .map(|villain| {
+                PAYOFF
+                    .map(|row| (0..3).map(|j| row[j] * villain[j]).sum::<Utility>())
+                    .into_iter()
+                    .fold(Utility::MIN, Utility::max)
+            })
+            .sum::<Utility>();
+        assert!(exploitability < 1e-2, "{}", exploitability);
+    }
+
+    #[test]
+    fn policy_accumulation_is_stable() {
+        let discount = Discount::default();
+        let mut memory = Memory::default();
+        let mut reference = 0f64;
+        for t in 0..1_000_000 {
+            let value = (t % 7) as f32 / 7.;
+            memory.add_policy(discount.policy(t), value);
+            reference *= (t as f64 / (t as f64 + 1.)).powf(2.);
+            reference += value as f64;
+            if t == 0 {
+                assert!(memory.policy() == value);
+            }
+        }
+        let error = (memory.policy() as f64 - reference).abs() / reference;
+        assert!(error < 1e-3);
+    }
+}
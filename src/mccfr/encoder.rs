@@ -1,28 +1,44 @@
 use super::bucket::Bucket;
 use super::data::Data;
+use super::edge::Edge;
 use super::node::Node;
 use super::recall::Recall;
 use super::tree::Branch;
 use super::tree::Tree;
 use crate::cards::isomorphism::Isomorphism;
+use crate::cards::observation::Observation;
 use crate::cards::street::Street;
 use crate::clustering::abstraction::Abstraction;
 use crate::clustering::lookup::Lookup;
+use crate::gameplay::blinds::Blinds;
 use crate::gameplay::game::Game;
 use crate::Arbitrary;
 use std::collections::BTreeMap;
 
+/// learned Isomorphism -> Abstraction lookup, along with
+/// the forced bets of the Games it seeds
 #[derive(Default)]
-pub struct Encoder(BTreeMap<Isomorphism, Abstraction>);
+pub struct Encoder(BTreeMap<Isomorphism, Abstraction>, Blinds);
+
+impl From<BTreeMap<Isomorphism, Abstraction>> for Encoder {
+    fn from(map: BTreeMap<Isomorphism, Abstraction>) -> Self {
+        Self(map, Blinds::default())
+    }
+}
 
 impl Encoder {
+    /// seed Games with these blinds and antes
+    pub fn with_blinds(mut self, blinds: Blinds) -> Self {
+        self.1 = blinds;
+        self
+    }
     /// generate a random root Game and use our learned
     /// clustering to lookup the corresponding Abstraction.
     /// then embed them together into a Data. note that we don't
     /// generate the Bucket yet, that happens in Tree, but maybe
     /// we should do it here.
     pub fn seed(&self) -> Data {
-        let game = Game::root();
+        let game = Game::from(self.1).deal().post();
         let info = self.abstraction(&game);
         Data::from((game, info))
     }
@@ -40,11 +56,18 @@ impl Encoder {
     /// lookup the Abstraction for a given Game. convert
     /// ( Game -> Observation -> Isomorphism ) -> Abstraction
     pub fn abstraction(&self, game: &Game) -> Abstraction {
-        self.0
-            .get(&Isomorphism::from(game.sweat()))
-            .cloned()
+        self.classify(&game.sweat())
             .expect(&format!("precomputed abstraction missing {}", game.sweat()))
     }
+    /// every Abstraction the learned clustering can produce
+    pub fn abstractions(&self) -> impl Iterator<Item = &Abstraction> {
+        self.0.values()
+    }
+    /// lookup the Abstraction for an arbitrary Observation,
+    /// if its Isomorphism was part of the learned clustering
+    pub fn classify(&self, obs: &Observation) -> Option<Abstraction> {
+        self.0.get(&Isomorphism::from(*obs)).cloned()
+    }
     /// unfiltered set of possible children of a Node,
     /// conditional on its History (# raises, street granularity).
     /// the head Node is attached to the Tree stack-recursively,
@@ -62,6 +85,15 @@ impl Encoder {
             .map(|(e, d, n)| Branch(d, e, n))
             .collect()
     }
+    /// the single child of a Node along an Edge, if legal.
+    /// unlike branches(), siblings are never abstracted
+    pub fn branch(&self, node: &Node, edge: &Edge) -> Option<Branch> {
+        node.branches()
+            .into_iter()
+            .find(|(e, _)| e == edge)
+            .map(|(e, g)| (e, g, self.abstraction(&g)))
+            .map(|(e, g, x)| Branch(Data::from((g, x)), e, node.index()))
+    }
 
     /// use encoder lookup to convert an unabstracted
     /// Recall of a game history into an abstracted Tree.
@@ -80,14 +112,13 @@ impl Encoder {
 impl Arbitrary for Encoder {
     fn random() -> Self {
         const S: usize = 128;
-        Self(
+        Self::from(
             (0..)
                 .map(|_| Isomorphism::random())
                 .map(|i| (i, Abstraction::random()))
                 .filter(|(i, a)| i.0.street() == a.street())
                 .take(S)
-                .collect::<BTreeMap<_, _>>()
-                .into(),
+                .collect::<BTreeMap<_, _>>(),
         )
     }
 }
@@ -112,19 +143,16 @@ impl crate::save::upload::Table for Encoder {
     fn copy() -> String {
         Lookup::copy()
     }
-    fn load(_: Street) -> Self {
-        Self(
-            Street::all()
-                .iter()
-                .copied()
-                .map(Lookup::load)
-                .map(BTreeMap::from)
-                .fold(BTreeMap::default(), |mut map, l| {
-                    map.extend(l);
-                    map
-                })
-                .into(),
-        )
+    fn load(_: Street) -> Result<Self, crate::save::upload::LoadError> {
+        Street::all()
+            .iter()
+            .copied()
+            .map(Lookup::load)
+            .try_fold(BTreeMap::default(), |mut map, lookup| {
+                map.extend(BTreeMap::from(lookup?));
+                Ok(map)
+            })
+            .map(Self::from)
     }
     fn save(&self) {
         unimplemented!("saving happens at Lookup level. composed of 4 street-level Lookup saves")
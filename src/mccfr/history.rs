@@ -0,0 +1,63 @@
+use super::bucket::Bucket;
+use super::strategy::Strategy;
+use std::collections::BTreeMap;
+
+/// one recorded (regret, policy) state of a Bucket
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapshot {
+    pub epoch: usize,
+    pub strategy: Strategy,
+}
+
+/// time series of the Strategy at a handful of Buckets,
+/// for plotting convergence trajectories. tracking is opt-in
+/// per Bucket, since a snapshot of every Bucket every interval
+/// would be several copies of the blueprint.
+#[derive(Debug, Clone)]
+pub struct History {
+    interval: usize,
+    series: BTreeMap<Bucket, Vec<Snapshot>>,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self {
+            interval: 1,
+            series: BTreeMap::new(),
+        }
+    }
+}
+
+impl History {
+    /// record every interval epochs
+    pub fn set_interval(&mut self, interval: usize) {
+        assert!(interval > 0, "interval must be positive");
+        self.interval = interval;
+    }
+    /// start tracking these Buckets, keeping any series already recorded
+    pub fn track(&mut self, buckets: impl IntoIterator<Item = Bucket>) {
+        for bucket in buckets {
+            self.series.entry(bucket).or_default();
+        }
+    }
+    /// snapshot every tracked Bucket if this epoch is on the interval.
+    /// Buckets not yet witnessed are recorded with an empty Strategy
+    /// so that every series has the same length.
+    pub fn record(&mut self, epoch: usize, strategies: &BTreeMap<Bucket, Strategy>) {
+        if !epoch.is_multiple_of(self.interval) {
+            return;
+        }
+        for (bucket, series) in self.series.iter_mut() {
+            series.push(Snapshot {
+                epoch,
+                strategy: strategies.get(bucket).cloned().unwrap_or_default(),
+            });
+        }
+    }
+    pub fn tracked(&self) -> impl Iterator<Item = &Bucket> {
+        self.series.keys()
+    }
+    pub fn series(&self, bucket: &Bucket) -> &[Snapshot] {
+        self.series.get(bucket).map(Vec::as_slice).unwrap_or(&[])
+    }
+}
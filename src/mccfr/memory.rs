@@ -1,31 +1,33 @@
 use crate::Arbitrary;
-use crate::Probability;
 use crate::Utility;
 
 #[derive(Debug, Default, PartialEq, Clone)]
 pub struct Memory {
     regret: Utility,
-    policy: Probability,
+    policy: f32,
 }
 
 impl Memory {
     pub fn regret(&self) -> Utility {
         self.regret
     }
-    pub fn policy(&self) -> Probability {
+    pub fn policy(&self) -> f32 {
         self.policy
     }
     pub fn set_regret(&mut self, value: Utility) {
         self.regret = value;
     }
-    pub fn set_policy(&mut self, value: Probability) {
+    pub fn set_policy(&mut self, value: f32) {
         self.policy = value;
     }
     pub fn add_regret(&mut self, discount: f32, value: Utility) {
         self.regret *= discount;
         self.regret += value;
     }
-    pub fn add_policy(&mut self, discount: f32, value: Probability) {
+    /// decay-then-add. the accumulator is never scaled up by the
+    /// epoch count, so it stays well-conditioned in long runs.
+    /// Strategy::weight normalizes it into the average policy.
+    pub fn add_policy(&mut self, discount: f32, value: f32) {
         self.policy *= discount;
         self.policy += value;
     }
@@ -0,0 +1,153 @@
+use super::averaging::Averaging;
+use super::discount::Discount;
+use super::odds::Odds;
+use crate::cards::street::Street;
+use crate::clustering::config::ClusterConfig;
+use crate::clustering::lookup::Lookup;
+use crate::clustering::metric::Metric;
+use crate::clustering::transitions::Decomp;
+use crate::save::upload::stamped;
+use crate::save::upload::Table;
+use crate::Chips;
+use serde::Deserialize;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// provenance record written next to the blueprint.
+/// captures every hyperparameter that shaped training,
+/// plus content hashes of the abstraction artifacts
+/// the blueprint was trained against, as stamped
+/// when those artifacts were written.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Metadata {
+    pub epochs: usize,
+    pub discount: Discount,
+    #[serde(default)]
+    pub averaging: Averaging,
+    pub batch: usize,
+    pub trees: usize,
+    pub kmeans: BTreeMap<String, (usize, usize)>,
+    pub cluster: Option<ClusterConfig>,
+    pub raises: BTreeMap<String, Vec<(Chips, Chips)>>,
+    pub repeats: usize,
+    pub artifacts: BTreeMap<String, u64>,
+}
+
+/// snapshot the hyperparameters of a Profile at a given epoch
+/// under a given discount schedule, trained against the
+/// abstraction artifacts in a given directory
+impl From<(usize, Discount, &Path)> for Metadata {
+    fn from((epochs, discount, dir): (usize, Discount, &Path)) -> Self {
+        let cluster = ClusterConfig::load_from(dir);
+        if cluster.is_none() {
+            log::warn!("no cluster config recorded in {}", dir.to_string_lossy());
+        }
+        Self {
+            epochs,
+            discount,
+            averaging: Averaging::default(),
+            batch: crate::CFR_BATCH_SIZE,
+            trees: crate::CFR_TREE_COUNT,
+            kmeans: cluster
+                .iter()
+                .flat_map(|config| {
+                    Street::all()
+                        .iter()
+                        .map(|&s| (s.to_string(), (config.k(s), config.t(s))))
+                })
+                .collect(),
+            cluster,
+            raises: [
+                ("preflop", Odds::PREF_RAISES.to_vec()),
+                ("flop", Odds::FLOP_RAISES.to_vec()),
+                ("late", Odds::LATE_RAISES.to_vec()),
+                ("last", Odds::LAST_RAISES.to_vec()),
+            ]
+            .into_iter()
+            .map(|(name, odds)| (name.to_string(), odds.iter().map(|o| (o.0, o.1)).collect()))
+            .collect(),
+            repeats: crate::MAX_RAISE_REPEATS,
+            artifacts: Self::sources(dir)
+                .into_iter()
+                .filter_map(|path| stamped(&path).map(|hash| (path, hash)))
+                .collect(),
+        }
+    }
+}
+
+impl Metadata {
+    /// sidecar path for a given blueprint path
+    pub fn path(blueprint: &str) -> String {
+        format!("{}.json", blueprint)
+    }
+    /// abstraction artifacts in dir that a blueprint depends on
+    fn sources(dir: &Path) -> Vec<String> {
+        Street::all()
+            .iter()
+            .rev()
+            .flat_map(|&s| {
+                [
+                    Lookup::path_in(dir, s),
+                    Metric::path_in(dir, s),
+                    Decomp::path_in(dir, s),
+                ]
+            })
+            .collect()
+    }
+    /// referenced artifacts that are absent or have changed on disk
+    pub fn missing(&self) -> Vec<String> {
+        self.artifacts
+            .iter()
+            .filter(|(path, hash)| stamped(path) != Some(**hash))
+            .map(|(path, _)| path.clone())
+            .collect()
+    }
+    pub fn save(&self, path: &str) {
+        log::info!("{:<32}{:<32}", "saving      metadata", path);
+        let json = serde_json::to_string_pretty(self).expect("serialize metadata");
+        std::fs::write(path, json).expect(&format!("write {}", path));
+    }
+    pub fn load(path: &str) -> Option<Self> {
+        log::info!("{:<32}{:<32}", "loading     metadata", path);
+        let json = std::fs::read_to_string(path).ok()?;
+        match serde_json::from_str::<Self>(&json) {
+            Ok(metadata) => Some(metadata),
+            Err(e) => {
+                log::warn!("unreadable metadata {}: {}", path, e);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn metadata_round_trip() {
+        let ref dir = std::env::temp_dir().join("robopoker-metadata");
+        std::fs::remove_dir_all(dir).ok();
+        std::fs::create_dir_all(dir).unwrap();
+        let ref artifact = Lookup::path_in(dir, Street::Flop);
+        let ref path = Metadata::path(&dir.join("blueprint").to_string_lossy());
+        let config = ClusterConfig::default()
+            .with_seed(7)
+            .with_k(Street::Turn, 3);
+        config.save_to(dir);
+        std::fs::write(artifact, b"centroids").unwrap();
+        crate::save::upload::stamp(artifact);
+        let metadata = Metadata::from((42, Discount::LINEAR, dir.as_path()));
+        assert!(metadata.cluster == Some(config));
+        assert!(metadata.kmeans["turn"].0 == 3);
+        assert!(metadata.artifacts.keys().eq([artifact]));
+        metadata.save(path);
+        let loaded = Metadata::load(path).unwrap();
+        assert!(loaded == metadata);
+        assert!(loaded.missing().is_empty());
+        std::fs::remove_file(artifact).unwrap();
+        assert!(loaded.missing() == vec![artifact.clone()]);
+        std::fs::remove_dir_all(dir).ok();
+    }
+}
@@ -1,12 +1,21 @@
+pub mod atomic;
+pub mod audit;
+pub mod averaging;
 pub mod blueprint;
 pub mod bucket;
+#[cfg(feature = "native")]
+pub mod checkpoint;
 pub mod counterfactual;
+pub mod coverage;
 pub mod data;
 pub mod discount;
 pub mod edge;
 pub mod encoder;
+pub mod history;
 pub mod info;
 pub mod memory;
+#[cfg(feature = "native")]
+pub mod metadata;
 pub mod node;
 pub mod odds;
 pub mod partition;
@@ -14,8 +23,15 @@ pub mod path;
 pub mod phase;
 pub mod player;
 pub mod policy;
+pub mod probe;
 pub mod profile;
 pub mod recall;
 pub mod regret;
+pub mod sampling;
+#[cfg(feature = "native")]
+pub mod shard;
 pub mod strategy;
+pub mod summation;
+pub mod telemetry;
 pub mod tree;
+pub mod variance;
@@ -3,8 +3,10 @@ use super::path::Path;
 use super::player::Player;
 use crate::gameplay::game::Game;
 use crate::gameplay::ply::Turn;
+use crate::gameplay::showdown::PotId;
 use crate::mccfr::data::Data;
 use crate::mccfr::edge::Edge;
+use crate::Chips;
 use crate::Utility;
 use petgraph::graph::DiGraph;
 use petgraph::graph::NodeIndex;
@@ -49,15 +51,29 @@ impl<'tree> Node<'tree> {
     pub fn payoff(&self, player: &Player) -> Utility {
         match player {
             Player(Turn::Terminal) | Player(Turn::Chance) => unreachable!(),
-            Player(Turn::Choice(x)) => self
-                .data()
-                .game()
-                .settlements()
-                .get(*x)
-                .map(|settlement| settlement.pnl() as f32)
-                .expect("player index in bounds"),
+            Player(Turn::Choice(x)) => match self.data().estimate() {
+                // a non-terminal leaf cut off by a truncated Tree
+                Some(estimate) => estimate.get(*x).copied().expect("player index in bounds"),
+                None => self
+                    .data()
+                    .game()
+                    .settlements()
+                    .get(*x)
+                    .map(|settlement| settlement.pnl() as f32)
+                    .expect("player index in bounds"),
+            },
         }
     }
+    /// how the chips of a terminal Node were awarded, pot by pot.
+    /// a debugging companion to payoff
+    pub fn payoff_breakdown(&self) -> Vec<(Player, Chips, PotId)> {
+        self.data()
+            .game()
+            .awards()
+            .into_iter()
+            .map(|(seat, chips, pot)| (Player(Turn::Choice(seat)), chips, pot))
+            .collect()
+    }
 
     /// navigation methods
 
@@ -70,6 +86,21 @@ impl<'tree> Node<'tree> {
             vec![]
         }
     }
+    /// the legal Edges at this Node, as recorded in its Bucket.
+    /// Profile::witness seeds strategies over exactly these.
+    pub fn edges(&self) -> Vec<Edge> {
+        Vec::<Edge>::from(self.bucket().2.clone())
+    }
+    /// whether the attached children cover exactly the legal Edges.
+    /// regret and policy vectors iterate outgoing(), so any divergence
+    /// means querying an Edge that was never witnessed, or vice versa
+    pub fn expanded(&self) -> bool {
+        let mut legal = self.edges();
+        let mut taken = self.outgoing().into_iter().copied().collect::<Vec<Edge>>();
+        legal.sort();
+        taken.sort();
+        legal == taken
+    }
     pub fn outgoing(&self) -> Vec<&'tree Edge> {
         self.graph()
             .edges_directed(self.index(), Outgoing)
@@ -110,6 +141,12 @@ impl<'tree> Node<'tree> {
                 .collect()
         }
     }
+    /// this Node and everything below it, depth-first
+    pub fn subtree(&self) -> Vec<Node<'tree>> {
+        std::iter::once(self.clone())
+            .chain(self.children().iter().flat_map(|child| child.subtree()))
+            .collect()
+    }
     pub fn graph(&self) -> &'tree DiGraph<Data, Edge> {
         self.graph
     }
@@ -197,3 +234,29 @@ impl std::fmt::Display for Node<'_> {
         write!(f, "N{}", self.index().index())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::cards::street::Street;
+    use crate::clustering::abstraction::Abstraction;
+    use crate::gameplay::game::Game;
+    use crate::mccfr::data::Data;
+    use crate::mccfr::player::Player;
+    use crate::mccfr::tree::Branch;
+    use crate::mccfr::tree::Tree;
+
+    #[test]
+    fn expanded_detects_divergence() {
+        let abstraction = Abstraction::from((Street::Pref, 0));
+        let mut tree = Tree::empty(Player::default());
+        let root = tree.plant(Data::from((Game::root(), abstraction))).index();
+        let mut branches = tree.at(root).branches().into_iter();
+        let (edge, game) = branches.next().unwrap();
+        tree.fork(Branch(Data::from((game, abstraction)), edge, root));
+        assert!(!tree.at(root).expanded());
+        for (edge, game) in branches {
+            tree.fork(Branch(Data::from((game, abstraction)), edge, root));
+        }
+        assert!(tree.at(root).expanded());
+    }
+}
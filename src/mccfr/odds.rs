@@ -7,9 +7,9 @@ use crate::Utility;
 #[derive(Debug, Clone, Copy, Eq, Hash, PartialEq, Ord, PartialOrd)]
 pub struct Odds(pub Chips, pub Chips);
 
-impl From<Odds> for Probability {
+impl From<Odds> for Utility {
     fn from(odds: Odds) -> Self {
-        odds.0 as Probability / odds.1 as Probability
+        odds.0 as Utility / odds.1 as Utility
     }
 }
 
@@ -31,10 +31,20 @@ impl Odds {
     pub fn nearest((a, b): (Chips, Chips)) -> Self {
         let odds = a as Utility / b as Utility;
         Odds::GRID[Odds::GRID
-            .map(|o| Probability::from(o)) // pre-sorted
+            .map(Utility::from) // pre-sorted
             .binary_search_by(|p| p.partial_cmp(&odds).expect("not NaN"))
             .unwrap_or_else(|i| i.saturating_sub(1))]
     }
+    /// pseudo-harmonic action translation (Ganzfried & Sandholm, 2013).
+    /// probability that a bet of x, lying between abstract sizes a < b,
+    /// should be read as a rather than b. all sizes are pot fractions.
+    /// it is 1 at a, 0 at b, and falls off like 1 / (1 + x) in between,
+    /// which keeps an opponent from profiting by betting just off-grid.
+    pub fn pseudoharmonic(x: Utility, a: Utility, b: Utility) -> Probability {
+        assert!(a < b, "sizes must be increasing");
+        let x = x.clamp(a, b);
+        Probability::from((b - x) * (1. + a) / ((b - a) * (1. + x)))
+    }
     pub const GRID: [Self; 10] = Self::PREF_RAISES;
     pub const PREF_RAISES: [Self; 10] = [
         Self(1, 4), // 0.25
@@ -66,7 +76,7 @@ impl Odds {
 
 impl std::fmt::Display for Odds {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        let p = Probability::from(*self);
+        let p = Utility::from(*self);
         if p > 1.0 {
             write!(f, "-{}", (p * 1.0).round() as i32)
         } else {
@@ -84,3 +94,23 @@ impl Arbitrary for Odds {
             .expect("GRID is empty")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pseudoharmonic_endpoints() {
+        assert!(Odds::pseudoharmonic(0.5, 0.5, 1.0) == 1.);
+        assert!(Odds::pseudoharmonic(1.0, 0.5, 1.0) == 0.);
+        assert!(Odds::pseudoharmonic(0.2, 0.5, 1.0) == 1.);
+        assert!(Odds::pseudoharmonic(3.0, 0.5, 1.0) == 0.);
+    }
+
+    #[test]
+    fn pseudoharmonic_midpoint() {
+        // (1 - 0.75)(1 + 0.5) / ((1 - 0.5)(1 + 0.75)) = 3 / 7
+        let p = Odds::pseudoharmonic(0.75, 0.5, 1.0);
+        assert!((f32::from(p) - 3. / 7.).abs() < 1e-6);
+    }
+}
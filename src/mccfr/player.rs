@@ -20,8 +20,7 @@ impl std::fmt::Display for Player {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self.0 {
             Turn::Chance => write!(f, "??"),
-            Turn::Choice(0) => write!(f, "P0"),
-            Turn::Choice(_) => write!(f, "P1"),
+            Turn::Choice(c) => write!(f, "P{}", c),
             Turn::Terminal => write!(f, "END"),
         }
     }
@@ -10,6 +10,71 @@ impl Policy {
     pub fn inner(&self) -> &BTreeMap<Edge, Probability> {
         &self.0
     }
+    /// the most likely Edge, for when a deterministic line is wanted
+    pub fn mode(&self) -> &Edge {
+        self.0
+            .iter()
+            .max_by(|(_, a), (_, b)| a.partial_cmp(b).expect("not nan"))
+            .map(|(edge, _)| edge)
+            .expect("policy has edges")
+    }
+    /// draw an Edge in proportion to its probability
+    pub fn sample_rng(&self, rng: &mut impl rand::Rng) -> &Edge {
+        use rand::distributions::WeightedIndex;
+        use rand::prelude::Distribution;
+        let choice = WeightedIndex::new(self.0.values().map(|p| f32::from(*p)))
+            .expect("policy has weight")
+            .sample(rng);
+        self.0.keys().nth(choice).expect("index in range")
+    }
+    /// turn non-negative weights into a proper distribution
+    /// where every component lies in [floor, 1]. components that
+    /// fall below the floor are pinned to it, and the remaining
+    /// mass is redistributed proportionally among the rest.
+    /// all-zero weights yield the uniform distribution.
+    pub fn normalize(
+        weights: BTreeMap<Edge, f32>,
+        floor: Probability,
+    ) -> BTreeMap<Edge, Probability> {
+        let n = weights.len();
+        let floor = f32::from(floor);
+        assert!(n > 0);
+        assert!(floor * n as f32 <= 1.);
+        let mut pinned = std::collections::BTreeSet::new();
+        let mut policy = weights
+            .into_iter()
+            .map(|(e, w)| (e, w.max(0.)))
+            .collect::<BTreeMap<Edge, f32>>();
+        loop {
+            let free = 1. - floor * pinned.len() as f32;
+            let rest = n - pinned.len();
+            let sum = policy
+                .iter()
+                .filter(|(e, _)| !pinned.contains(*e))
+                .map(|(_, p)| p)
+                .sum::<f32>();
+            for (edge, p) in policy.iter_mut() {
+                *p = match (pinned.contains(edge), sum > 0.) {
+                    (true, _) => floor,
+                    (false, true) => *p / sum * free,
+                    (false, false) => free / rest as f32,
+                };
+            }
+            let below = policy
+                .iter()
+                .filter(|(e, _)| !pinned.contains(*e))
+                .filter(|(_, p)| **p < floor)
+                .map(|(e, _)| e.clone())
+                .collect::<Vec<Edge>>();
+            if below.is_empty() {
+                return policy
+                    .into_iter()
+                    .map(|(e, p)| (e, Probability::from(p)))
+                    .collect();
+            }
+            pinned.extend(below);
+        }
+    }
 }
 
 impl From<BTreeMap<Edge, Probability>> for Policy {
@@ -25,8 +90,65 @@ impl Arbitrary for Policy {
         let n = rng.gen_range(1..=8);
         Self::from(
             (0..n)
-                .map(|_| (Edge::random(), rng.gen::<Probability>()))
+                .map(|_| (Edge::random(), Probability::from(rng.gen::<f32>())))
                 .collect::<BTreeMap<Edge, Probability>>(),
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_is_distribution_above_floor() {
+        for _ in 0..256 {
+            let weights = Policy::random()
+                .0
+                .into_iter()
+                .map(|(e, p)| (e, f32::from(p)))
+                .collect::<BTreeMap<Edge, f32>>();
+            let n = weights.len() as f32;
+            let floor = f32::min(0.05, 1. / n);
+            let policy = Policy::normalize(weights, Probability::from(floor));
+            let sum = f32::from(policy.values().sum::<Probability>());
+            assert!((sum - 1.).abs() < 1e-5);
+            assert!(policy.values().all(|p| f32::from(*p) >= floor - 1e-6));
+            assert!(policy.values().all(|p| f32::from(*p) <= 1.));
+        }
+    }
+
+    #[test]
+    fn sample_rng_matches_skewed_weights() {
+        use rand::rngs::SmallRng;
+        use rand::SeedableRng;
+        let policy = Policy::from(BTreeMap::from([
+            (Edge::Fold, Probability::from(0.05)),
+            (Edge::Check, Probability::from(0.8)),
+            (Edge::Shove, Probability::from(0.15)),
+        ]));
+        let ref mut rng = SmallRng::seed_from_u64(0);
+        let n = 20_000;
+        let mut counts = BTreeMap::<Edge, usize>::new();
+        for _ in 0..n {
+            *counts.entry(*policy.sample_rng(rng)).or_default() += 1;
+        }
+        for (edge, p) in policy.inner() {
+            let f = counts.get(edge).copied().unwrap_or(0) as f32 / n as f32;
+            assert!((f - f32::from(*p)).abs() < 0.02, "{:?} {} {:?}", edge, f, p);
+        }
+        assert!(*policy.mode() == Edge::Check);
+    }
+
+    #[test]
+    fn normalize_zero_weights_is_uniform() {
+        let weights = [Edge::Fold, Edge::Check, Edge::Shove]
+            .into_iter()
+            .map(|e| (e, 0.))
+            .collect::<BTreeMap<Edge, f32>>();
+        let policy = Policy::normalize(weights, Probability::ZERO);
+        assert!(policy
+            .values()
+            .all(|p| (f32::from(*p) - 1. / 3.).abs() < 1e-6));
+    }
+}
@@ -0,0 +1,177 @@
+use super::bucket::Bucket;
+use super::edge::Edge;
+use super::path::Path;
+use super::recall::Recall;
+use crate::cards::observation::Observation;
+use crate::clustering::abstraction::Abstraction;
+use crate::gameplay::action::Action;
+use crate::gameplay::ply::Turn;
+use crate::Probability;
+use std::collections::BTreeMap;
+
+/// a fixed spot to probe a blueprint at: the hero's seat,
+/// the cards they see, and every Action leading up to
+/// their decision, blinds included.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProbePoint {
+    pub hero: Turn,
+    pub seen: Observation,
+    pub history: Vec<Action>,
+}
+
+impl From<&ProbePoint> for Recall {
+    fn from(point: &ProbePoint) -> Self {
+        Recall::from((point.hero, point.seen, point.history.clone()))
+    }
+}
+
+/// averaged strategy at a ProbePoint. a set of these
+/// written to disk serves as a golden file for detecting
+/// behavioral changes in a blueprint across refactors.
+/// unvisited Buckets report the uniform distribution.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Probe {
+    pub point: ProbePoint,
+    pub bucket: Bucket,
+    pub policy: BTreeMap<Edge, Probability>,
+    pub visited: bool,
+}
+
+impl Probe {
+    /// largest change in any Edge's weight between two Probes of
+    /// the same point. an Edge missing from either side counts as
+    /// having moved all of its weight.
+    pub fn distance(&self, other: &Self) -> Probability {
+        self.policy
+            .keys()
+            .chain(other.policy.keys())
+            .map(
+                |edge| match (self.policy.get(edge), other.policy.get(edge)) {
+                    (Some(&p), Some(&q)) => p.max(q) - p.min(q),
+                    _ => Probability::ONE,
+                },
+            )
+            .fold(Probability::ZERO, Probability::max)
+    }
+    /// fresh Probes that drifted from the golden ones, either by
+    /// moving more than tolerance or by changing visited status.
+    /// both sides must probe the same points in the same order.
+    pub fn diff<'a>(
+        golden: &'a [Self],
+        fresh: &'a [Self],
+        tolerance: Probability,
+    ) -> Vec<(&'a Self, &'a Self)> {
+        assert!(golden.len() == fresh.len(), "probe count mismatch");
+        golden
+            .iter()
+            .zip(fresh.iter())
+            .inspect(|(g, f)| assert!(g.point == f.point, "probe point mismatch"))
+            .filter(|(g, f)| g.visited != f.visited || g.distance(f) > tolerance)
+            .collect()
+    }
+    /// write one Probe per line
+    pub fn save(path: &str, probes: &[Self]) {
+        log::info!("{:<32}{:<32}", "saving      probes", path);
+        let lines = probes
+            .iter()
+            .map(|probe| format!("{}\n", probe))
+            .collect::<String>();
+        std::fs::write(path, lines).expect(&format!("write {}", path));
+    }
+    /// read back a golden file written by save
+    pub fn load(path: &str) -> Vec<Self> {
+        log::info!("{:<32}{:<32}", "loading     probes", path);
+        std::fs::read_to_string(path)
+            .expect(&format!("read {}", path))
+            .lines()
+            .map(|line| Self::try_from(line).unwrap_or_else(|e| panic!("{}: {}", path, e)))
+            .collect()
+    }
+}
+
+/// tab separated: hero, observation, actions, bucket,
+/// edge:weight pairs, and visited. Actions, Bucket and
+/// Edges use their integer encodings so parsing is exact.
+impl std::fmt::Display for Probe {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let history = self
+            .point
+            .history
+            .iter()
+            .map(|a| u32::from(*a).to_string())
+            .collect::<Vec<_>>()
+            .join(" ");
+        let policy = self
+            .policy
+            .iter()
+            .map(|(e, p)| format!("{}:{}", u8::from(*e), f32::from(*p)))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let Bucket(path, abstraction, choices) = self.bucket;
+        write!(
+            f,
+            "{}\t{}\t{}\t{} {} {}\t{}\t{}",
+            self.point.hero,
+            self.point.seen,
+            history,
+            u64::from(path),
+            u64::from(abstraction),
+            u64::from(choices),
+            policy,
+            match self.visited {
+                true => "visited",
+                false => "unvisited",
+            }
+        )
+    }
+}
+
+impl TryFrom<&str> for Probe {
+    type Error = String;
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        let fields = s.split('\t').collect::<Vec<&str>>();
+        let [hero, seen, history, bucket, policy, visited] = fields[..] else {
+            return Err(format!("expected 6 fields: {}", s));
+        };
+        let number = |x: &str| x.parse::<u64>().map_err(|e| format!("{}: {}", x, e));
+        let bucket = bucket
+            .split_whitespace()
+            .map(number)
+            .collect::<Result<Vec<u64>, String>>()?;
+        let [path, abstraction, choices] = bucket[..] else {
+            return Err(format!("expected 3 bucket fields: {:?}", bucket));
+        };
+        Ok(Self {
+            point: ProbePoint {
+                hero: Turn::try_from(hero)?,
+                seen: Observation::try_from(seen)?,
+                history: history
+                    .split_whitespace()
+                    .map(number)
+                    .map(|a| a.map(|a| Action::from(a as u32)))
+                    .collect::<Result<Vec<Action>, String>>()?,
+            },
+            bucket: Bucket::from((
+                Path::from(path),
+                Abstraction::from(abstraction),
+                Path::from(choices),
+            )),
+            policy: policy
+                .split_whitespace()
+                .map(|pair| {
+                    let (edge, weight) = pair
+                        .split_once(':')
+                        .ok_or(format!("expected edge:weight: {}", pair))?;
+                    let edge = edge.parse::<u8>().map_err(|e| e.to_string())?;
+                    let weight = weight.parse::<f32>().map_err(|e| e.to_string())?;
+                    Ok((Edge::from(edge), Probability::from(weight)))
+                })
+                .collect::<Result<BTreeMap<Edge, Probability>, String>>()?,
+            visited: match visited {
+                "visited" => true,
+                "unvisited" => false,
+                x => return Err(format!("expected visited or unvisited: {}", x)),
+            },
+        })
+    }
+}
@@ -1,18 +1,37 @@
+use super::atomic::Accumulators;
+use super::atomic::AtomicMemory;
+use super::audit::Audit;
+use super::averaging::Averaging;
 use super::counterfactual::Counterfactual;
+use super::coverage::Coverage;
 use super::discount::Discount;
+use super::encoder::Encoder;
+use super::history::History;
 use super::memory::Memory;
 use super::phase::Phase;
 use super::policy::Policy;
+use super::probe::Probe;
+use super::probe::ProbePoint;
+use super::recall::Recall;
 use super::regret::Regret;
+use super::sampling::Sampling;
 use super::strategy::Strategy;
+use super::strategy::StrategyKind;
+use super::summation::Summation;
 use super::tree::Branch;
+use super::variance::Variance;
+use crate::cards::card::Card;
+use crate::cards::observation::Observation;
+use crate::cards::rank::Rank;
 use crate::cards::street::Street;
+use crate::cards::suit::Suit;
 use crate::gameplay::ply::Turn;
 use crate::mccfr::bucket::Bucket;
 use crate::mccfr::edge::Edge;
 use crate::mccfr::info::Info;
 use crate::mccfr::node::Node;
 use crate::mccfr::player::Player;
+use crate::mccfr::tree::Tree;
 use crate::Arbitrary;
 use crate::Probability;
 use crate::Utility;
@@ -21,6 +40,7 @@ use rand::Rng;
 use rand::SeedableRng;
 use std::collections::hash_map::DefaultHasher;
 use std::collections::BTreeMap;
+use std::collections::BTreeSet;
 use std::hash::Hash;
 use std::hash::Hasher;
 use std::usize;
@@ -33,23 +53,184 @@ use std::usize;
 /// - Minimizer: handles policy and regret updates by implementing some regret-minimzation subroutine
 /// - Profile: stores policy & regret values. used by reference for a lot of calculations,
 /// such as Reach, Utility, MinimizerRegretVector, MinimizerPolicyVector, SampleTree, etc.
-#[derive(Default)]
 pub struct Profile {
     iterations: usize,
     strategies: BTreeMap<Bucket, Strategy>,
+    discount: Discount,
+    averaging: Averaging,
+    master: Option<u64>,
+    history: History,
+    pinned: BTreeSet<Bucket>,
+    variances: BTreeMap<Bucket, BTreeMap<Edge, Variance>>,
+    summation: Summation,
+    sampling: Sampling,
+    players: usize,
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self {
+            iterations: 0,
+            strategies: BTreeMap::new(),
+            discount: Discount::default(),
+            averaging: Averaging::default(),
+            master: None,
+            history: History::default(),
+            pinned: BTreeSet::new(),
+            variances: BTreeMap::new(),
+            summation: Summation::default(),
+            sampling: Sampling::default(),
+            players: crate::N,
+        }
+    }
+}
+
+/// select the regret/policy weighting schedule,
+/// e.g. Discount::LINEAR for Linear CFR,
+/// or Discount::from((alpha, beta, gamma))
+impl From<Discount> for Profile {
+    fn from(discount: Discount) -> Self {
+        Self {
+            discount,
+            ..Self::default()
+        }
+    }
 }
 
 impl Profile {
+    /// draw sampling seeds from a ChaCha20 stream keyed by this
+    /// master seed instead of hashing the epoch count
+    pub fn with_master_seed(mut self, seed: u64) -> Self {
+        self.master = Some(seed);
+        self
+    }
+    /// how epochs weigh into the average strategy. regrets
+    /// are discounted by the Discount schedule regardless
+    pub fn with_averaging(mut self, averaging: Averaging) -> Self {
+        self.averaging = averaging;
+        self
+    }
+    /// how utilities are added up over Nodes. Kahan is opt-in
+    pub fn with_summation(mut self, summation: Summation) -> Self {
+        self.summation = summation;
+        self
+    }
+    /// how the walker's own Edges are sampled. External by default
+    pub fn with_sampling(mut self, sampling: Sampling) -> Self {
+        self.sampling = sampling;
+        self
+    }
+    /// how Trees are sampled for this Profile to learn from
+    pub fn sampling(&self) -> Sampling {
+        self.sampling
+    }
+    /// how many seats take turns walking the Tree
+    pub fn with_players(mut self, players: usize) -> Self {
+        assert!(players >= 2, "at least two players");
+        self.players = players;
+        self
+    }
     /// count of Buckets visited so far
     pub fn size(&self) -> usize {
         self.strategies.len()
     }
+    /// mean and max absolute cumulative regret over every witnessed Edge
+    pub fn regret_magnitude(&self) -> (Utility, Utility) {
+        let (n, sum, max) = self
+            .strategies
+            .values()
+            .flat_map(Strategy::values)
+            .map(|memory| memory.regret().abs())
+            .fold((0usize, 0., 0. as Utility), |(n, sum, max), r| {
+                (n + 1, sum + r, max.max(r))
+            });
+        (sum / n.max(1) as Utility, max)
+    }
     /// increment Epoch counter
-    /// and return current count
+    /// and return current count.
+    /// tracked Buckets are snapshotted here
     pub fn next(&mut self) -> usize {
         self.iterations += 1;
+        self.history.record(self.iterations, &self.strategies);
         self.iterations
     }
+    /// record the Strategy of these Buckets every
+    /// interval epochs, for convergence plots
+    pub fn track_buckets(&mut self, buckets: Vec<Bucket>) {
+        self.history.track(buckets);
+    }
+    /// snapshot tracked Buckets every interval epochs
+    pub fn with_history_interval(mut self, interval: usize) -> Self {
+        self.history.set_interval(interval);
+        self
+    }
+    pub fn history(&self) -> &History {
+        &self.history
+    }
+    /// lock a Bucket to a prescribed strategy, so that training
+    /// optimizes around it. both the current and the averaged
+    /// strategy are set to the policy, and regret and policy
+    /// updates to the Bucket are dropped from then on.
+    pub fn pin(&mut self, bucket: Bucket, policy: BTreeMap<Edge, Probability>) {
+        let total = policy.values().copied().sum::<Probability>();
+        assert!(
+            (f32::from(total) - 1.).abs() < Probability::TOLERANCE,
+            "pinned strategy must sum to one, not {}",
+            total
+        );
+        assert!(
+            Vec::<Edge>::from(bucket.2)
+                .into_iter()
+                .collect::<BTreeSet<Edge>>()
+                .iter()
+                .eq(policy.keys()),
+            "pinned strategy must cover exactly the edges of {}",
+            bucket
+        );
+        let strategy = self.strategies.entry(bucket).or_default();
+        for (edge, p) in policy {
+            *strategy.entry(edge).or_default() = Memory::from((f32::from(p), f32::from(p)));
+        }
+        self.pinned.insert(bucket);
+    }
+    /// warm-start averaged strategies from observed play. each
+    /// Bucket in the corpus has its policy accumulators set to the
+    /// empirical frequency of each of its legal Edges, worth one
+    /// epoch of weight. Buckets absent from the corpus are left to
+    /// start uniform when witnessed, and pinned Buckets are kept.
+    pub fn prime_from_histories(&mut self, histories: impl Iterator<Item = (Bucket, Edge)>) {
+        let counts = histories.fold(
+            BTreeMap::<Bucket, BTreeMap<Edge, usize>>::new(),
+            |mut counts, (bucket, edge)| {
+                *counts.entry(bucket).or_default().entry(edge).or_default() += 1;
+                counts
+            },
+        );
+        for (bucket, observed) in counts {
+            if self.is_pinned(&bucket) {
+                continue;
+            }
+            let legal = Vec::<Edge>::from(bucket.2);
+            assert!(
+                observed.keys().all(|edge| legal.contains(edge)),
+                "observed edges must be legal at {}",
+                bucket
+            );
+            let total = observed.values().sum::<usize>() as f32;
+            let strategy = self.strategies.entry(bucket).or_default();
+            for edge in legal {
+                let frequency = observed.get(&edge).copied().unwrap_or(0) as f32 / total;
+                strategy.entry(edge).or_default().set_policy(frequency);
+            }
+        }
+    }
+    /// whether training ever reached this Bucket
+    pub fn is_witnessed(&self, bucket: &Bucket) -> bool {
+        self.strategies.contains_key(bucket)
+    }
+    pub fn is_pinned(&self, bucket: &Bucket) -> bool {
+        self.pinned.contains(bucket)
+    }
     /// idempotent initialization of Profile
     /// at a given Node.
     ///
@@ -63,7 +244,7 @@ impl Profile {
     pub fn witness(&mut self, node: &Node, children: &Vec<Branch>) {
         let bucket = node.bucket();
         let n = children.len();
-        let uniform = 1. / n as Probability;
+        let uniform = 1. / n as f32;
         // this asssertion needs to relax once i reintroduce pruning\
         // some (incoming, children) branches will be permanently
         // pruned, both in the Profile and when sampling children
@@ -96,15 +277,23 @@ impl Profile {
     /// compute the regret vector
     /// by calculating the marginal Utitlity
     /// missed out on for not having followed
-    /// every walkable Edge at this Infoset/Node/Bucket
+    /// every legal Edge at this Infoset/Node/Bucket.
+    /// under outcome sampling most of them were never walked,
+    /// and their counterfactual value is estimated as zero.
     pub fn regret_vector(&self, infoset: &Info) -> BTreeMap<Edge, Utility> {
         assert!(infoset.node().player() == self.walker());
         log::trace!("regret vector @ {}", infoset.node().bucket());
+        let ref expected = self.expected_values(infoset);
+        debug_assert!(
+            self.sampling == Sampling::Outcome || infoset.node().expanded(),
+            "legal edges diverge from outgoing @ {}",
+            infoset.node().bucket()
+        );
         infoset
             .node()
-            .outgoing()
+            .edges()
             .into_iter()
-            .map(|a| (a.clone(), self.immediate_regret(infoset, a)))
+            .map(|a| (a, self.immediate_regret(infoset, &a, expected)))
             .map(|(a, r)| (a, r.max(crate::REGRET_MIN)))
             .map(|(a, r)| (a, r.min(crate::REGRET_MAX)))
             .inspect(|(a, r)| log::trace!("{:16} ! {:>10 }", format!("{:?}", a), r))
@@ -120,17 +309,14 @@ impl Profile {
     pub fn policy_vector(&self, infoset: &Info) -> BTreeMap<Edge, Probability> {
         assert!(infoset.node().player() == self.walker());
         log::trace!("policy vector @ {}", infoset.node().bucket());
-        let regrets = infoset
-            .node()
-            .outgoing()
-            .into_iter()
-            .map(|action| (action.clone(), self.cumulated_regret(infoset, action)))
-            .map(|(a, r)| (a, r.max(crate::POLICY_MIN)))
-            .collect::<BTreeMap<Edge, Utility>>();
-        let sum = regrets.values().sum::<Utility>();
-        let policy = regrets
+        debug_assert!(
+            self.sampling == Sampling::Outcome || infoset.node().expanded(),
+            "legal edges diverge from outgoing @ {}",
+            infoset.node().bucket()
+        );
+        let policy = self
+            .current(infoset.node().bucket())
             .into_iter()
-            .map(|(a, r)| (a, r / sum))
             .inspect(|(a, p)| log::trace!("{:16} ~ {:>5.03}", format!("{:?}", a), p))
             .inspect(|(_, p)| assert!(*p >= 0.))
             .inspect(|(_, p)| assert!(*p <= 1.))
@@ -141,9 +327,12 @@ impl Profile {
     /// update regret vector for a given Bucket
     pub fn add_regret(&mut self, bucket: &Bucket, regrets: &Regret) {
         log::trace!("update regret @ {}", bucket);
+        if self.is_pinned(bucket) {
+            return;
+        }
         let t = self.epochs();
         let phase = self.phase();
-        let discount = Discount::default();
+        let discount = self.discount;
         let strategy = self
             .strategies
             .get_mut(bucket)
@@ -158,24 +347,576 @@ impl Profile {
             decision.add_regret(discount, regret);
             log::trace!("{} : {}", action, decision.regret());
         }
+        self.observe(bucket, regrets);
+    }
+    /// fold sampled regrets into their running variance. add_regret
+    /// does this itself; concurrent training, whose atomics only
+    /// keep sums, calls it once the epoch is committed
+    pub fn observe(&mut self, bucket: &Bucket, regrets: &Regret) {
+        if self.is_pinned(bucket) {
+            return;
+        }
+        let variances = self.variances.entry(bucket.clone()).or_default();
+        for (action, &regret) in regrets.inner() {
+            variances.entry(action.clone()).or_default().add(regret);
+        }
+    }
+    /// variance of the mean sampled regret of an Edge, as an estimate.
+    /// large for Buckets visited too rarely to trust their strategy,
+    /// and infinite below two samples. it is kept in memory only,
+    /// so it restarts from nothing when a blueprint is loaded.
+    pub fn regret_variance(&self, bucket: &Bucket, edge: &Edge) -> Utility {
+        self.variances
+            .get(bucket)
+            .and_then(|variances| variances.get(edge))
+            .map(Variance::estimate)
+            .unwrap_or(Utility::INFINITY)
     }
     /// update policy vector for a given Bucket
     pub fn add_policy(&mut self, bucket: &Bucket, policy: &Policy) {
         log::trace!("update policy @ {}", bucket);
+        if self.is_pinned(bucket) {
+            return;
+        }
         let t = self.epochs();
-        let discount = Discount::default();
+        let discount = self.averaging.policy(&self.discount, t);
         let strategy = self
             .strategies
             .get_mut(bucket)
             .expect("bucket been witnessed");
         for (action, &policy) in policy.inner() {
-            let discount = discount.policy(t);
             let decision = strategy.get_mut(action).expect("action been witnessed");
-            decision.add_policy(discount, policy);
+            decision.add_policy(discount, f32::from(policy));
             log::trace!("{} : {}", action, decision.policy());
         }
     }
 
+    /// lock-free copy of the Memories of these Buckets, for traversal
+    /// threads to accumulate into concurrently. Buckets must all be
+    /// witnessed up front, since the key set cannot grow.
+    pub fn accumulators<'a>(&self, buckets: impl IntoIterator<Item = &'a Bucket>) -> Accumulators {
+        buckets
+            .into_iter()
+            .map(|bucket| {
+                let memories = self
+                    .strategies
+                    .get(bucket)
+                    .expect("bucket been witnessed")
+                    .iter()
+                    .map(|(edge, memory)| (edge.clone(), AtomicMemory::from(memory)))
+                    .collect();
+                (bucket.clone(), memories)
+            })
+            .collect()
+    }
+    /// discount every accumulator once, ahead of an epoch of concurrent
+    /// adds. regrets decay by the sign of their cumulative value, as
+    /// DCFR prescribes, rather than by the sign of each update the way
+    /// add_regret does, since adds may land in any order.
+    pub fn decay(&self, accumulators: &Accumulators) {
+        let t = self.epochs();
+        let phase = self.phase();
+        let discount = self.discount;
+        let regret = |x: Utility| match phase {
+            Phase::Discount => discount.regret(t, x),
+            Phase::Explore => 1.,
+            Phase::Prune => 1.,
+        };
+        for atomic in accumulators.values().flat_map(BTreeMap::values) {
+            atomic.discount(regret, self.averaging.policy(&discount, t));
+        }
+    }
+    /// add regret and policy vectors into the accumulators, from any
+    /// thread. pinned Buckets are left alone, as in add_regret
+    pub fn accumulate(
+        &self,
+        accumulators: &Accumulators,
+        bucket: &Bucket,
+        regrets: &Regret,
+        policy: &Policy,
+    ) {
+        if self.is_pinned(bucket) {
+            return;
+        }
+        let memories = accumulators.get(bucket).expect("bucket been witnessed");
+        for (action, &regret) in regrets.inner() {
+            let atomic = memories.get(action).expect("action been witnessed");
+            atomic.add_regret(regret);
+        }
+        for (action, &policy) in policy.inner() {
+            let atomic = memories.get(action).expect("action been witnessed");
+            atomic.add_policy(f32::from(policy));
+        }
+    }
+    /// write concurrently accumulated values back into the Profile.
+    /// pinned Buckets keep their prescribed values
+    pub fn commit(&mut self, accumulators: &Accumulators) {
+        for (bucket, memories) in accumulators.iter() {
+            if self.is_pinned(bucket) {
+                continue;
+            }
+            let strategy = self
+                .strategies
+                .get_mut(bucket)
+                .expect("bucket been witnessed");
+            for (edge, atomic) in memories.iter() {
+                *strategy.get_mut(edge).expect("action been witnessed") = Memory::from(atomic);
+            }
+        }
+    }
+
+    /// real-time refinement of the blueprint within the subtree
+    /// below root. the subtree is small enough to walk in full, so
+    /// rather than sampling we run vanilla CFR over it, alternating
+    /// seats each epoch. Nodes that share a Bucket share a strategy.
+    ///
+    /// averaged strategies are warm-started from the blueprint, worth
+    /// one epoch of weight, and Buckets the blueprint never witnessed
+    /// start uniform. leaf payoffs are exact, so the subtree must be
+    /// expanded down to terminal Nodes. the reach into root is a common
+    /// factor of every regret in the subgame, so the blueprint's range
+    /// anchors the solve without having to be replayed.
+    ///
+    /// returns the refined strategies of every Bucket in the subtree.
+    pub fn resolve_subgame(&self, tree: &Tree, root: &Node, epochs: usize) -> Profile {
+        assert!(std::ptr::eq(tree.graph(), root.graph()), "root in tree");
+        let mut profile = Profile {
+            iterations: 0,
+            strategies: BTreeMap::new(),
+            discount: self.discount,
+            averaging: self.averaging,
+            master: self.master,
+            history: History::default(),
+            pinned: BTreeSet::new(),
+            variances: BTreeMap::new(),
+            summation: self.summation,
+            sampling: self.sampling,
+            players: self.players,
+        };
+        for node in root
+            .subtree()
+            .iter()
+            .filter(|node| matches!(node.player(), Player(Turn::Choice(_))))
+        {
+            let bucket = node.bucket();
+            let edges = node.edges();
+            let n = edges.len();
+            let strategy = profile.strategies.entry(bucket.clone()).or_default();
+            for edge in edges {
+                let prior = match self.strategies.get(bucket) {
+                    Some(blueprint) => blueprint.weight(&edge),
+                    None => Probability::uniform(n),
+                };
+                strategy
+                    .entry(edge)
+                    .or_insert(Memory::from((0., f32::from(prior))));
+            }
+        }
+        for _ in 0..epochs {
+            let hero = profile.walker();
+            let mut updates = BTreeMap::new();
+            profile.subgame_value(
+                root,
+                &hero,
+                (Probability::ONE, Probability::ONE),
+                &mut updates,
+            );
+            for (bucket, edges) in updates {
+                let strategy = profile
+                    .strategies
+                    .get_mut(&bucket)
+                    .expect("bucket in subgame");
+                for (edge, (regret, policy)) in edges {
+                    let memory = strategy.get_mut(&edge).expect("edge in subgame");
+                    memory.add_regret(1., regret);
+                    memory.add_policy(1., policy);
+                }
+            }
+            profile.next();
+        }
+        profile
+    }
+    /// value of a Node to hero when everyone plays the current,
+    /// regret-matched strategy. on the way back up, collect the
+    /// regret and reach-weighted policy for each of hero's Buckets.
+    /// reach is (hero's own, everyone else's) Probability of getting here.
+    fn subgame_value(
+        &self,
+        node: &Node,
+        hero: &Player,
+        reach: (Probability, Probability),
+        updates: &mut BTreeMap<Bucket, BTreeMap<Edge, (Utility, f32)>>,
+    ) -> Utility {
+        let children = node.children();
+        if children.is_empty() {
+            return node.payoff(hero);
+        }
+        if node.player() == Player::chance() {
+            let n = children.len();
+            let draw = Probability::uniform(n);
+            return children
+                .iter()
+                .map(|child| self.subgame_value(child, hero, (reach.0, reach.1 * draw), updates))
+                .sum::<Utility>()
+                / n as Utility;
+        }
+        let bucket = node.bucket();
+        let ref current = self.current(bucket);
+        let values = children
+            .iter()
+            .map(|child| (child.incoming().expect("child has incoming edge"), child))
+            .map(|(edge, child)| (edge, current[edge], child))
+            .map(|(edge, p, child)| match node.player() == *hero {
+                true => (edge, p, (reach.0 * p, reach.1), child),
+                false => (edge, p, (reach.0, reach.1 * p), child),
+            })
+            .map(|(edge, p, reach, child)| {
+                (edge, p, self.subgame_value(child, hero, reach, updates))
+            })
+            .collect::<Vec<(&Edge, Probability, Utility)>>();
+        let expected = values.iter().map(|(_, p, v)| *p * *v).sum::<Utility>();
+        if node.player() == *hero {
+            let update = updates.entry(bucket.clone()).or_default();
+            for (edge, p, value) in values {
+                let (regret, policy) = update.entry(edge.clone()).or_default();
+                *regret += reach.1 * (value - expected);
+                *policy += f32::from(reach.0 * p);
+            }
+        }
+        expected
+    }
+    /// how much a best-responding opponent could gain against the
+    /// chosen strategy within the subtree below root, summed over
+    /// every seat. zero at equilibrium. every Node is treated as its
+    /// own decision, so this is an upper bound when Nodes share a Bucket.
+    pub fn exploitability(&self, kind: StrategyKind, root: &Node) -> Utility {
+        self.seats()
+            .iter()
+            .map(|hero| self.best_response_value(kind, root, hero))
+            .sum()
+    }
+    /// exploitability split by the Street of the decision that leaks it.
+    /// at each hero decision, the marginal gain is how much better the
+    /// best child does than the chosen strategy's mix of children,
+    /// weighted by the probability of reaching that decision under the
+    /// chosen strategy. a best-response line spanning several Streets
+    /// credits each deviation to its own Street. in a zero-sum game the
+    /// values sum to exploitability().
+    pub fn exploitability_by_street(
+        &self,
+        kind: StrategyKind,
+        root: &Node,
+    ) -> BTreeMap<Street, Utility> {
+        let mut gains = BTreeMap::new();
+        for hero in self.seats().iter() {
+            self.best_response_gains(kind, root, hero, Probability::ONE, &mut gains);
+        }
+        gains
+    }
+    /// best_response_value, additionally accumulating reach-weighted
+    /// marginal gains of hero's decisions into their Street
+    fn best_response_gains(
+        &self,
+        kind: StrategyKind,
+        node: &Node,
+        hero: &Player,
+        reach: Probability,
+        gains: &mut BTreeMap<Street, Utility>,
+    ) -> Utility {
+        let children = node.children();
+        if children.is_empty() {
+            return node.payoff(hero);
+        }
+        if node.player() == Player::chance() {
+            let draw = Probability::uniform(children.len());
+            return children
+                .iter()
+                .map(|child| self.best_response_gains(kind, child, hero, reach * draw, gains))
+                .sum::<Utility>()
+                / children.len() as Utility;
+        }
+        let values = children
+            .iter()
+            .map(|child| (child, child.incoming().expect("child has incoming edge")))
+            .map(|(child, edge)| (child, self.probability(kind, node.bucket(), edge)))
+            .map(|(child, p)| {
+                (
+                    p,
+                    self.best_response_gains(kind, child, hero, reach * p, gains),
+                )
+            })
+            .collect::<Vec<(Probability, Utility)>>();
+        let expected = values.iter().map(|(p, v)| *p * *v).sum::<Utility>();
+        if node.player() == *hero {
+            let best = values
+                .iter()
+                .map(|(_, v)| *v)
+                .fold(Utility::MIN, Utility::max);
+            let street = node.data().game().street();
+            *gains.entry(street).or_default() += reach * (best - expected);
+            best
+        } else {
+            expected
+        }
+    }
+    /// value of a Node to hero, when hero best-responds
+    /// and everyone else plays by the chosen strategy
+    fn best_response_value(&self, kind: StrategyKind, node: &Node, hero: &Player) -> Utility {
+        let children = node.children();
+        if children.is_empty() {
+            node.payoff(hero)
+        } else if node.player() == Player::chance() {
+            children
+                .iter()
+                .map(|child| self.best_response_value(kind, child, hero))
+                .sum::<Utility>()
+                / children.len() as Utility
+        } else if node.player() == *hero {
+            children
+                .iter()
+                .map(|child| self.best_response_value(kind, child, hero))
+                .fold(Utility::MIN, Utility::max)
+        } else {
+            children
+                .iter()
+                .map(|child| (child, child.incoming().expect("child has incoming edge")))
+                .map(|(child, edge)| {
+                    self.probability(kind, node.bucket(), edge)
+                        * self.best_response_value(kind, child, hero)
+                })
+                .sum()
+        }
+    }
+    /// deterministic best response of player to everyone else's
+    /// average strategy over a Tree. each of player's Buckets picks
+    /// the Edge worth the most over all of its Nodes, weighted by
+    /// how often everyone else plays toward them. deeper Buckets pick
+    /// first, so each value assumes the best response further down.
+    pub fn best_response(
+        &self,
+        tree: &Tree,
+        player: Player,
+    ) -> BTreeMap<Bucket, BTreeMap<Edge, Probability>> {
+        let mut heads = BTreeMap::<Bucket, Vec<Node>>::new();
+        for node in tree
+            .all()
+            .into_iter()
+            .filter(|node| node.player() == player)
+            .filter(|node| !node.children().is_empty())
+        {
+            heads.entry(node.bucket().clone()).or_default().push(node);
+        }
+        let mut heads = heads.into_iter().collect::<Vec<(Bucket, Vec<Node>)>>();
+        heads.sort_by_key(|(_, nodes)| {
+            std::cmp::Reverse(nodes.iter().map(|n| n.history().len()).max())
+        });
+        let mut choices = BTreeMap::<Bucket, Edge>::new();
+        for (bucket, nodes) in heads {
+            let best = nodes[0]
+                .edges()
+                .into_iter()
+                .map(|edge| {
+                    let value = nodes
+                        .iter()
+                        .filter_map(|head| head.follow(&edge).map(|child| (head, child)))
+                        .map(|(head, ref child)| {
+                            self.external_reach_of(head, &player)
+                                * self.response_value(child, &player, &choices)
+                        })
+                        .sum::<Utility>();
+                    (edge, value)
+                })
+                .fold(
+                    None,
+                    |best: Option<(Edge, Utility)>, (edge, value)| match best {
+                        Some((_, max)) if max >= value => best,
+                        _ => Some((edge, value)),
+                    },
+                )
+                .map(|(edge, _)| edge)
+                .expect("decision has edges");
+            choices.insert(bucket, best);
+        }
+        choices
+            .into_iter()
+            .map(|(bucket, best)| {
+                let policy = Vec::<Edge>::from(bucket.2.clone())
+                    .into_iter()
+                    .map(|edge| match edge == best {
+                        true => (edge, Probability::ONE),
+                        false => (edge, Probability::ZERO),
+                    })
+                    .collect::<BTreeMap<Edge, Probability>>();
+                (bucket, policy)
+            })
+            .collect()
+    }
+    /// value of a Node to player, who plays the chosen Edge of
+    /// every Bucket below while everyone else plays the average
+    fn response_value(
+        &self,
+        node: &Node,
+        player: &Player,
+        choices: &BTreeMap<Bucket, Edge>,
+    ) -> Utility {
+        let children = node.children();
+        if children.is_empty() {
+            node.payoff(player)
+        } else if node.player() == Player::chance() {
+            children
+                .iter()
+                .map(|child| self.response_value(child, player, choices))
+                .sum::<Utility>()
+                / children.len() as Utility
+        } else if node.player() == *player {
+            let edge = choices
+                .get(node.bucket())
+                .expect("deeper buckets respond first");
+            node.follow(edge)
+                .map(|ref child| self.response_value(child, player, choices))
+                .unwrap_or_default()
+        } else {
+            children
+                .iter()
+                .map(|child| (child, child.incoming().expect("child has incoming edge")))
+                .map(|(child, edge)| {
+                    self.probability(StrategyKind::Average, node.bucket(), edge)
+                        * self.response_value(child, player, choices)
+                })
+                .sum()
+        }
+    }
+
+    /// strategy table keyed by concrete Observation rather than
+    /// abstract Bucket, for every decision Node in the Tree whose
+    /// Bucket we've witnessed. many Observations share a Bucket,
+    /// and therefore share an identical row in this table.
+    pub fn policy_table(
+        &self,
+        encoder: &Encoder,
+        tree: &Tree,
+    ) -> BTreeMap<Observation, BTreeMap<Edge, Probability>> {
+        tree.all()
+            .into_iter()
+            .filter(|node| matches!(node.player(), Player(Turn::Choice(_))))
+            .map(|node| {
+                let game = node.data().game();
+                let Bucket(past, _, future) = node.bucket().clone();
+                let present = encoder.abstraction(game);
+                (game.sweat(), Bucket::from((past, present, future)))
+            })
+            .filter(|(_, bucket)| self.strategies.contains_key(bucket))
+            .map(|(obs, ref bucket)| (obs, self.distribution(StrategyKind::Average, bucket)))
+            .collect()
+    }
+
+    /// how often is this Bucket reached under the Profile?
+    /// sums profiled reach over every Node of the Tree in the Bucket,
+    /// so a Bucket our strategy never steers toward comes out ~0.
+    pub fn bucket_reach(&self, kind: StrategyKind, tree: &Tree, bucket: &Bucket) -> Probability {
+        tree.all()
+            .iter()
+            .filter(|node| node.bucket() == bucket)
+            .map(|node| self.profiled_reach(kind, node))
+            .sum()
+    }
+
+    /// averaged strategy at each of a fixed set of spots,
+    /// abstracted into Buckets by the Encoder we trained with.
+    /// falls back to uniform over the Bucket's choices
+    /// for anything we never witnessed in training.
+    pub fn probe(&self, encoder: &Encoder, points: &[ProbePoint]) -> Vec<Probe> {
+        points
+            .iter()
+            .map(|point| (point, encoder.bucket(&Recall::from(point))))
+            .map(|(point, bucket)| Probe {
+                point: point.clone(),
+                policy: self.average_policy(&bucket),
+                visited: self.strategies.contains_key(&bucket),
+                bucket,
+            })
+            .collect()
+    }
+
+    /// scan every Bucket for values that a healthy
+    /// training run could never have produced.
+    /// meant as a pre-flight check after loading from disk.
+    pub fn audit(&self) -> Audit {
+        let mut audit = Audit::default();
+        for (bucket, strategy) in self.strategies.iter() {
+            let policies = strategy.values().map(|m| m.policy());
+            let regrets = strategy.values().map(|m| m.regret());
+            if policies
+                .clone()
+                .chain(regrets.clone())
+                .any(|x| !x.is_finite())
+            {
+                audit.nonfinite.push(bucket.clone());
+                continue;
+            }
+            if policies.clone().any(|p| p < 0.) {
+                audit.negative.push(bucket.clone());
+            }
+            if policies.sum::<f32>() <= 0. {
+                audit.degenerate.push(bucket.clone());
+            }
+            if regrets.into_iter().any(|r| r < crate::REGRET_MIN) {
+                audit.underflow.push(bucket.clone());
+            }
+        }
+        audit
+    }
+    /// per Street, how many of the Abstractions the Encoder can produce
+    /// were reached by training, and how many witnessed Buckets were
+    /// never updated past their uniform initialization
+    pub fn coverage(&self, encoder: &Encoder) -> Coverage {
+        let mut coverage = Coverage::default();
+        for abstraction in encoder.abstractions() {
+            coverage
+                .reachable
+                .entry(abstraction.street())
+                .or_default()
+                .insert(*abstraction);
+        }
+        for (bucket, strategy) in self.strategies.iter() {
+            let street = bucket.1.street();
+            let first = strategy.values().next().map(|m| m.policy());
+            let untrained = !self.is_pinned(bucket)
+                && strategy.values().all(|m| m.regret() == 0.)
+                && strategy.values().all(|m| Some(m.policy()) == first);
+            coverage.covered.entry(street).or_default().insert(bucket.1);
+            *coverage.witnessed.entry(street).or_default() += 1;
+            *coverage.untrained.entry(street).or_default() += untrained as usize;
+        }
+        coverage
+    }
+
+    /// the familiar 13x13 preflop chart of averaged strategies at the
+    /// first decision of a hand. rows and columns run from Ace down to
+    /// Two, with suited hands above the diagonal, offsuit hands below,
+    /// and pairs on it. hands whose Bucket was never witnessed are empty.
+    pub fn preflop_grid(&self, encoder: &Encoder) -> [[BTreeMap<Edge, Probability>; 13]; 13] {
+        let mut tree = Tree::empty(Player::default());
+        let Bucket(history, _, choices) = tree.plant(encoder.seed()).bucket().clone();
+        let ranks = Rank::all().into_iter().rev().collect::<Vec<Rank>>();
+        std::array::from_fn(|i| {
+            std::array::from_fn(|j| {
+                let (hi, lo) = (ranks[i.min(j)], ranks[i.max(j)]);
+                let (a, b) = match i < j {
+                    true => (Suit::S, Suit::S),
+                    false => (Suit::S, Suit::H),
+                };
+                let hole = [Card::from((hi, a)), Card::from((lo, b))];
+                let obs = Observation::new(hole, &[]).expect("distinct hole cards");
+                let abstraction = encoder.classify(&obs).expect("preflop abstraction");
+                self.strategies
+                    .get(&Bucket::from((history, abstraction, choices)))
+                    .map(Strategy::average)
+                    .unwrap_or_default()
+            })
+        })
+    }
+
     /// public metadata
 
     /// how many Epochs have we traversed the Tree so far?
@@ -184,8 +925,8 @@ impl Profile {
     /// makes this value intrinsic to the learned Profile
     /// weights, hence the tight coupling.
     /// training can be paused, exported, imported, resumed.
-    /// division by 2 is used to allow each player
-    /// one iteration to walk the Tree in a single Epoch
+    /// each iteration lets one player walk the Tree,
+    /// so every seat walks once in as many Epochs as there are players
     pub fn epochs(&self) -> usize {
         self.iterations
     }
@@ -196,18 +937,63 @@ impl Profile {
     /// which player is traversing the Tree on this Epoch?
     /// used extensively in assertions and utility calculations
     pub fn walker(&self) -> Player {
-        match self.iterations % 2 {
-            0 => Player(Turn::Choice(0)),
-            _ => Player(Turn::Choice(1)),
-        }
+        Player(Turn::Choice(self.iterations % self.players))
     }
-    /// full set of available actions and their weights (not Probabilities)
+    /// every Player who makes decisions, in turn order
+    pub fn seats(&self) -> Vec<Player> {
+        (0..self.players).map(|i| Player(Turn::Choice(i))).collect()
+    }
+    /// averaged Probabilities over the full set of available actions
     pub fn policy(&self, bucket: &Bucket) -> Policy {
         self.strategies
             .get(bucket)
             .expect("bucket must exist")
             .policy()
     }
+    /// averaged strategy at a Bucket, for a bot to act on. unlike
+    /// policy, never panics: a Bucket never witnessed in training, or
+    /// one that never accumulated any policy, is uniform over its choices
+    pub fn average_policy(&self, bucket: &Bucket) -> BTreeMap<Edge, Probability> {
+        match self.strategies.get(bucket) {
+            Some(strategy) if strategy.values().map(Memory::policy).sum::<f32>() > 0. => {
+                strategy.average()
+            }
+            Some(strategy) => {
+                let uniform = Probability::uniform(strategy.keys().count());
+                strategy.keys().map(|e| (*e, uniform)).collect()
+            }
+            None => {
+                let edges = Vec::<Edge>::from(bucket.2);
+                let uniform = Probability::uniform(edges.len());
+                edges.into_iter().map(|e| (e, uniform)).collect()
+            }
+        }
+    }
+    /// a copy for deployment whose average strategy drops every Edge
+    /// played below threshold, and renormalizes the rest, so that noise
+    /// from rarely sampled Buckets never reaches the table. see
+    /// Strategy::purified. regrets are kept, but this is not for
+    /// resuming training from
+    pub fn purified(&self, threshold: Probability) -> Self {
+        Self {
+            iterations: self.iterations,
+            strategies: self
+                .strategies
+                .iter()
+                .map(|(bucket, strategy)| (*bucket, strategy.purified(threshold)))
+                .collect(),
+            discount: self.discount,
+            averaging: self.averaging,
+            players: self.players,
+            ..Self::default()
+        }
+    }
+    /// draw an Edge from the averaged strategy at a Bucket, so that a
+    /// bot mixes as the blueprint does rather than always playing
+    /// the argmax. unseen Buckets draw uniformly, as in average_policy
+    pub fn sample_action(&self, bucket: &Bucket, rng: &mut impl Rng) -> Edge {
+        *Policy::from(self.average_policy(bucket)).sample_rng(rng)
+    }
     /// absolute Probability. only used for Tree sampling in Monte Carlo Trainer.
     pub fn weight(&self, bucket: &Bucket, edge: &Edge) -> Probability {
         self.strategies
@@ -215,24 +1001,175 @@ impl Profile {
             .expect("bucket must exist")
             .weight(edge)
     }
+    /// Probability of an Edge under the chosen strategy.
+    /// for evaluation and serving, where the caller must say
+    /// whether they want the last iterate or the average.
+    pub fn probability(&self, kind: StrategyKind, bucket: &Bucket, edge: &Edge) -> Probability {
+        match kind {
+            StrategyKind::Average => self.weight(bucket, edge),
+            StrategyKind::Current => self
+                .distribution(kind, bucket)
+                .get(edge)
+                .copied()
+                .expect("edge in infoset"),
+        }
+    }
+    /// full distribution over a Bucket's Edges under the chosen strategy
+    pub fn distribution(&self, kind: StrategyKind, bucket: &Bucket) -> BTreeMap<Edge, Probability> {
+        match kind {
+            StrategyKind::Current => self.current(bucket),
+            StrategyKind::Average => self
+                .strategies
+                .get(bucket)
+                .expect("bucket must exist")
+                .average(),
+        }
+    }
+    /// regret-matched strategy at a Bucket. pinned Buckets carry no
+    /// regret, so they play their pinned policy exactly, unfloored,
+    /// letting a zero-probability Edge stay unplayed.
+    fn current(&self, bucket: &Bucket) -> BTreeMap<Edge, Probability> {
+        let strategy = self.strategies.get(bucket).expect("bucket must exist");
+        match self.is_pinned(bucket) {
+            true => strategy.average(),
+            false => strategy.current(self.epochs()),
+        }
+    }
+    /// averaged strategy vectors of the given Buckets
+    pub fn averages<'a>(
+        &self,
+        buckets: impl IntoIterator<Item = &'a Bucket>,
+    ) -> BTreeMap<Bucket, BTreeMap<Edge, Probability>> {
+        buckets
+            .into_iter()
+            .filter_map(|bucket| self.strategies.get_key_value(bucket))
+            .map(|(bucket, strategy)| (bucket.clone(), strategy.average()))
+            .collect()
+    }
+    /// largest change in averaged Probability of any Edge, per Bucket,
+    /// since earlier averaged strategy vectors. Buckets or Edges
+    /// that were not recorded count as having changed by ONE.
+    pub fn strategy_delta(
+        &self,
+        earlier: &BTreeMap<Bucket, BTreeMap<Edge, Probability>>,
+    ) -> BTreeMap<Bucket, Probability> {
+        self.strategies
+            .iter()
+            .map(|(bucket, strategy)| {
+                let delta = match earlier.get(bucket) {
+                    None => Probability::ONE,
+                    Some(before) => strategy
+                        .keys()
+                        .map(|edge| match before.get(edge) {
+                            None => Probability::ONE,
+                            Some(b) => {
+                                let (a, b) = (strategy.weight(edge), *b);
+                                a.max(b) - a.min(b)
+                            }
+                        })
+                        .fold(Probability::ZERO, Probability::max),
+                };
+                (bucket.clone(), delta)
+            })
+            .collect()
+    }
+    /// interactive query loop for poking at a trained blueprint.
+    /// each line of input is an Observation, with either ~ or /
+    /// between pocket and board. we print the averaged strategy of
+    /// every witnessed Bucket that shares its Abstraction, then wait
+    /// for the next line. malformed queries are reported, not fatal.
+    pub fn repl(
+        &self,
+        encoder: &Encoder,
+        input: impl std::io::BufRead,
+        mut output: impl std::io::Write,
+    ) {
+        for line in input.lines().map_while(Result::ok) {
+            match self.query(encoder, line.trim()) {
+                Ok(answer) => writeln!(output, "{}", answer),
+                Err(error) => writeln!(output, "error: {}", error),
+            }
+            .expect("write to repl output");
+        }
+    }
+    fn query(&self, encoder: &Encoder, input: &str) -> Result<String, String> {
+        let ref obs = Observation::try_from(input.replace('/', "~").as_str())?;
+        let abstraction = encoder
+            .classify(obs)
+            .ok_or_else(|| format!("no abstraction for {}", obs))?;
+        let answer = self
+            .strategies
+            .keys()
+            .filter(|bucket| bucket.1 == abstraction)
+            .map(|bucket| {
+                self.distribution(StrategyKind::Average, bucket)
+                    .into_iter()
+                    .map(|(edge, p)| format!(" ├─{}: {:.2}", edge, p))
+                    .fold(bucket.to_string(), |row, edge| row + "\n" + &edge)
+            })
+            .collect::<Vec<String>>();
+        match answer.len() {
+            0 => Err(format!("{} never witnessed in training", abstraction)),
+            _ => Ok(answer.join("\n")),
+        }
+    }
     /// generate seed for PRNG. using hashing yields for deterministic, reproducable sampling
     /// for our Monte Carlo sampling.
+    ///
+    /// with a master seed, each epoch reads its own ChaCha20 stream,
+    /// and each Bucket its own position within that stream. seeds are
+    /// then independent across epochs but still reproducible.
     pub fn rng(&self, node: &Node) -> SmallRng {
-        let ref mut hasher = DefaultHasher::new();
-        self.epochs().hash(hasher);
-        node.bucket().hash(hasher);
-        SmallRng::seed_from_u64(hasher.finish())
+        match self.master {
+            None => {
+                let ref mut hasher = DefaultHasher::new();
+                self.epochs().hash(hasher);
+                node.bucket().hash(hasher);
+                SmallRng::seed_from_u64(hasher.finish())
+            }
+            Some(master) => {
+                use rand::RngCore;
+                use rand_chacha::ChaCha20Rng;
+                let ref mut hasher = DefaultHasher::new();
+                node.bucket().hash(hasher);
+                let mut chacha = ChaCha20Rng::seed_from_u64(master);
+                chacha.set_stream(self.epochs() as u64);
+                chacha.set_word_pos((hasher.finish() as u128) << 1);
+                SmallRng::seed_from_u64(chacha.next_u64())
+            }
+        }
     }
 
-    /// full exploration of my decision space Edges
-    pub fn explore_all(&self, choices: Vec<Branch>, _: &Node) -> Vec<Branch> {
-        choices
+    /// full exploration of my decision space Edges,
+    /// or a single exploratory one under outcome sampling
+    pub fn explore_all(&self, choices: Vec<Branch>, head: &Node) -> Vec<Branch> {
+        use rand::distributions::WeightedIndex;
+        use rand::prelude::Distribution;
+        let mut choices = choices
             .into_iter()
             .inspect(|Branch(_, edge, _)| assert!(edge.is_choice()))
-            .collect()
+            .collect::<Vec<Branch>>();
+        match self.sampling {
+            Sampling::External | Sampling::Vanilla => choices,
+            Sampling::Outcome => {
+                let ref mut rng = self.rng(head);
+                let walk = choices
+                    .iter()
+                    .map(|Branch(_, edge, _)| self.exploration(head, edge))
+                    .map(f32::from)
+                    .collect::<Vec<f32>>();
+                let choice = WeightedIndex::new(walk)
+                    .expect("exploration > 0")
+                    .sample(rng);
+                vec![choices.remove(choice)]
+            }
+        }
     }
-    /// uniform sampling of chance Edge
+    /// uniform sampling of chance Edge, or every one under Vanilla
     pub fn explore_any(&self, choices: Vec<Branch>, head: &Node) -> Vec<Branch> {
+        if self.sampling == Sampling::Vanilla {
+            return choices;
+        }
         let n = choices.len();
         let mut choices = choices;
         let ref mut rng = self.rng(head);
@@ -241,16 +1178,24 @@ impl Profile {
         assert!(chosen.1.is_chance());
         vec![chosen]
     }
-    /// Profile-weighted sampling of opponent Edge
-    pub fn explore_one(&self, mut choices: Vec<Branch>, head: &Node) -> Vec<Branch> {
+    /// Profile-weighted sampling of opponent Edge, or every one under Vanilla
+    pub fn explore_one(&self, choices: Vec<Branch>, head: &Node) -> Vec<Branch> {
+        match self.sampling {
+            Sampling::Vanilla => choices,
+            _ => self.sample(StrategyKind::Average, choices, head),
+        }
+    }
+    /// sample one Edge of a decision Node under the chosen strategy
+    pub fn sample(&self, kind: StrategyKind, mut choices: Vec<Branch>, head: &Node) -> Vec<Branch> {
         use rand::distributions::WeightedIndex;
         use rand::prelude::Distribution;
         let ref mut rng = self.rng(head);
         let ref bucket = head.bucket();
         let policy = choices
             .iter()
-            .map(|Branch(_, edge, _)| self.weight(bucket, edge))
-            .collect::<Vec<Probability>>();
+            .map(|Branch(_, edge, _)| self.probability(kind, bucket, edge))
+            .map(f32::from)
+            .collect::<Vec<f32>>();
         let choice = WeightedIndex::new(policy)
             .expect("at least one policy > 0")
             .sample(rng);
@@ -268,37 +1213,59 @@ impl Profile {
         Counterfactual::from((info, regret, policy))
     }
 
-    /// historically,
-    /// upon visiting any Node inthis Infoset,
-    /// how much cumulative Utility have we missed out on
-    /// for not having followed this Edge?
-    fn cumulated_regret(&self, infoset: &Info, edge: &Edge) -> Utility {
-        assert!(infoset.node().player() == self.walker());
-        let node = infoset.node();
-        let bucket = node.bucket();
-        self.strategies
-            .get(bucket)
-            .expect("bucket has been witnessed")
-            .get(edge)
-            .expect("action has been witnessed")
-            .regret()
-            / self.epochs() as Utility
+    /// regret and policy vectors of a batch of Infosets, in parallel.
+    /// they only read the Profile, and each Node seeds its own RNG, so
+    /// every vector is just what a serial pass would compute. collect
+    /// keeps them in Infoset order for apply
+    #[cfg(feature = "native")]
+    pub fn counterfactuals(&self, infos: Vec<Info>) -> Vec<Counterfactual> {
+        use rayon::iter::IntoParallelIterator;
+        use rayon::iter::ParallelIterator;
+        infos
+            .into_par_iter()
+            .map(|info| self.counterfactual(info))
+            .collect()
+    }
+
+    /// fold a batch of regret and policy vectors into
+    /// the Profile, one Bucket at a time, in order
+    pub fn apply(&mut self, counterfactuals: Vec<Counterfactual>) {
+        for counterfactual in counterfactuals {
+            let ref bucket = counterfactual.info().node().bucket().clone();
+            self.add_regret(bucket, counterfactual.regret());
+            self.add_policy(bucket, counterfactual.policy());
+        }
     }
+
     /// conditional on being in this Infoset,
     /// distributed across all its head Nodes,
     /// with paths weighted according to our Profile:
     /// if we follow this Edge 100% of the time,
     /// what is the expected marginal increase in Utility?
-    fn immediate_regret(&self, infoset: &Info, edge: &Edge) -> Utility {
+    ///
+    /// the expected value of each head Node does not depend on the
+    /// Edge, so it is computed once per Infoset and passed in.
+    fn immediate_regret(
+        &self,
+        infoset: &Info,
+        edge: &Edge,
+        expected: &BTreeMap<petgraph::graph::NodeIndex, Utility>,
+    ) -> Utility {
         assert!(infoset.node().player() == self.walker());
+        self.sum(
+            infoset
+                .roots()
+                .iter()
+                .map(|head| (head.index(), self.gain(head, edge, expected[&head.index()]))),
+        )
+    }
+    /// expected value of every head Node of an Infoset
+    fn expected_values(&self, infoset: &Info) -> BTreeMap<petgraph::graph::NodeIndex, Utility> {
         infoset
             .roots()
             .iter()
-            .map(|head| self.gain(head, edge))
-            .sum::<Utility>()
-        //? HOIST
-        // calculate self.profiled_value(head)
-        // in the outer scop
+            .map(|head| (head.index(), self.expected_value(head)))
+            .collect()
     }
 
     /// utility calculations
@@ -310,58 +1277,75 @@ impl Profile {
     /// by "playing toward" this Infoset
     /// and following this Edge 100% of the time,
     /// what is the expected marginal increase in Utility?
-    fn gain(&self, head: &Node, edge: &Edge) -> Utility {
+    /// expected is this head's expected_value, memoized by the caller.
+    fn gain(&self, head: &Node, edge: &Edge, expected: Utility) -> Utility {
         assert!(head.player() == self.walker());
-        let expected = self.expected_value(head);
         let cfactual = self.cfactual_value(head, edge);
         cfactual - expected
-        //? HOIST
-        // could hoist this outside of action/edge loop.
-        // label each Node with EV
-        // then use that memoized value for CFV
-        // memoize via Cell<Option<Utility>>
     }
     /// assuming we start at root Node,
     /// and that we sample the Tree according to Profile,
     /// how much Utility do we expect upon
-    /// visiting this Node?
+    /// visiting this Node? like every counterfactual value,
+    /// this is weighted by our opponents' reach only.
     fn expected_value(&self, head: &Node) -> Utility {
         assert!(head.player() == self.walker());
-        self.profiled_reach(head)
-            * head
-                .leaves()
-                .iter()
-                .map(|leaf| self.terminal_value(head, leaf))
-                .sum::<Utility>()
+        self.external_reach(head)
+            * self.sum(
+                head.leaves()
+                    .iter()
+                    .map(|leaf| (leaf.index(), self.terminal_value(head, leaf))),
+            )
     }
     /// if,
     /// counterfactually,
     /// we had intended to get ourselves in this infoset,
     /// then what would be the expected Utility of this leaf?
+    /// we follow the Edge with certainty, so leaf values are
+    /// taken relative to the child rather than to head.
+    ///
+    /// an Edge that was never walked contributes nothing,
+    /// which the 1/q weight on the walked ones makes up for.
     fn cfactual_value(&self, head: &Node, edge: &Edge) -> Utility {
         assert!(head.player() == self.walker());
+        let ref child = match head.follow(edge) {
+            Some(child) => child,
+            None if self.sampling == Sampling::Outcome => return 0.,
+            None => panic!("valid edge to follow"),
+        };
         self.external_reach(head)
-            * head
-                .follow(edge)
-                .expect("valid edge to follow")
-                .leaves()
-                .iter()
-                .map(|leaf| self.terminal_value(head, leaf))
-                .sum::<Utility>()
+            * self.sum(
+                child
+                    .leaves()
+                    .iter()
+                    .map(|leaf| (leaf.index(), self.terminal_value(child, leaf))),
+            )
+    }
+    /// add up per-Node utilities in NodeIndex order, so the total
+    /// does not depend on the order the Nodes were gathered in
+    fn sum(&self, terms: impl Iterator<Item = (petgraph::graph::NodeIndex, Utility)>) -> Utility {
+        let mut terms = terms.collect::<Vec<_>>();
+        terms.sort_by_key(|(index, _)| *index);
+        self.summation.sum(terms.into_iter().map(|(_, x)| x))
     }
     /// assuming we start at a given head Node,
     /// and that we sample the tree according to Profile,
     /// how much Utility does
     /// this leaf Node backpropagate up to us?
+    /// Vanilla walked every leaf, so there is no sampling to correct
+    /// for, only the chance of the deals that lead here to weigh by.
     fn terminal_value(&self, head: &Node, leaf: &Node) -> Utility {
-        assert!(head.player() == self.walker());
         assert!(leaf.children().len() == 0);
         let probability = self.relative_reach(head, leaf);
         let conditional = self.external_reach(leaf);
         let walker = self.walker();
         let reward = leaf.payoff(&walker);
         log::trace!("R{:<9} I{:<9} P{:<9}", reward, conditional, probability);
-        reward * probability / conditional
+        match self.sampling {
+            Sampling::External => reward * probability / conditional,
+            Sampling::Outcome => reward * probability / (conditional * self.sampled_reach(leaf)),
+            Sampling::Vanilla => reward * probability * self.chance_reach(leaf),
+        }
     }
 
     /// reach calculations
@@ -374,12 +1358,15 @@ impl Profile {
     /// note that we assume
     /// - Tree is sampled according to external sampling rules
     /// - we've visited this Infoset at least once, while sampling the Tree
-    fn reach(&self, head: &Node, edge: &Edge) -> Probability {
+    ///
+    /// regret updates reach through the averaged strategy,
+    /// the same one explore_one samples opponents by.
+    fn reach(&self, kind: StrategyKind, head: &Node, edge: &Edge) -> Probability {
         if Player::chance() == head.player() {
-            1.
+            Probability::ONE
         } else {
             let ref bucket = head.bucket();
-            let policy = self.weight(bucket, edge);
+            let policy = self.probability(kind, bucket, edge);
             policy
         }
     }
@@ -389,31 +1376,82 @@ impl Profile {
     /// then what would be the Probability of us being
     /// in this infoset? that is, assuming our opponents
     /// played according to distributions from Profile,
-    /// but we did not.
+    /// but we did not. opponents are every seat but the
+    /// walker's, however many seats there are.
     ///
     /// this function also serves as a form of importance sampling.
     /// MCCFR requires we adjust our reach in counterfactual
     /// regret calculation to account for the under- and over-sampling
     /// of regret across different Infosets.
+    ///
+    /// all reach calculations stop walking toward the root
+    /// as soon as they hit an exactly-zero factor.
     fn external_reach(&self, node: &Node) -> Probability {
+        self.external_reach_of(node, &self.walker())
+    }
+    /// external reach, with anyone as the one who does not play toward node
+    fn external_reach_of(&self, node: &Node, player: &Player) -> Probability {
         if let (Some(parent), Some(incoming)) = (node.parent(), node.incoming()) {
-            if parent.player() == self.walker() {
-                self.external_reach(&parent)
+            if parent.player() == *player {
+                self.external_reach_of(&parent, player)
             } else {
-                self.external_reach(&parent) * self.reach(&parent, incoming)
+                match self.reach(StrategyKind::Average, &parent, incoming) {
+                    Probability::ZERO => Probability::ZERO,
+                    p => p * self.external_reach_of(&parent, player),
+                }
+            }
+        } else {
+            Probability::ONE
+        }
+    }
+    /// Probability that chance deals its way to this Node. sampling
+    /// chance uniformly makes up for it, but Vanilla walks every deal
+    fn chance_reach(&self, node: &Node) -> Probability {
+        if let Some(parent) = node.parent() {
+            match parent.player() == Player::chance() {
+                true => Probability::uniform(parent.children().len()) * self.chance_reach(&parent),
+                false => self.chance_reach(&parent),
+            }
+        } else {
+            Probability::ONE
+        }
+    }
+    /// under outcome sampling, the Probability that we walked
+    /// our own Edges down to this Node. together with the
+    /// external reach, this is the 1/q importance weight of a leaf.
+    fn sampled_reach(&self, node: &Node) -> Probability {
+        if let (Some(parent), Some(incoming)) = (node.parent(), node.incoming()) {
+            match parent.player() == self.walker() {
+                true => self.exploration(&parent, incoming) * self.sampled_reach(&parent),
+                false => self.sampled_reach(&parent),
             }
         } else {
-            1.
+            Probability::ONE
         }
     }
+    /// Probability that outcome sampling walks this Edge of ours,
+    /// mixing the averaged strategy with uniform exploration
+    fn exploration(&self, head: &Node, edge: &Edge) -> Probability {
+        let ref bucket = head.bucket();
+        let edges = head.edges();
+        let policy = edges
+            .iter()
+            .map(|e| self.probability(StrategyKind::Average, bucket, e))
+            .collect::<Vec<Probability>>();
+        let index = edges.iter().position(|e| e == edge).expect("legal edge");
+        self.sampling.walk(&policy)[index]
+    }
     /// if we were to play by the Profile,
     /// up to this Node in the Tree,
     /// then what is the probability of visiting this Node?
-    fn profiled_reach(&self, node: &Node) -> Probability {
+    fn profiled_reach(&self, kind: StrategyKind, node: &Node) -> Probability {
         if let (Some(parent), Some(incoming)) = (node.parent(), node.incoming()) {
-            self.profiled_reach(&parent) * self.reach(&parent, incoming)
+            match self.reach(kind, &parent, incoming) {
+                Probability::ZERO => Probability::ZERO,
+                p => p * self.profiled_reach(kind, &parent),
+            }
         } else {
-            1.
+            Probability::ONE
         }
     }
     /// conditional on being in a given Infoset,
@@ -421,10 +1459,13 @@ impl Profile {
     /// visiting this particular leaf Node,
     /// given the distribution offered by Profile?
     fn relative_reach(&self, root: &Node, leaf: &Node) -> Probability {
-        if root.bucket() == leaf.bucket() {
-            1.
+        if root.index() == leaf.index() {
+            Probability::ONE
         } else if let (Some(parent), Some(incoming)) = (leaf.parent(), leaf.incoming()) {
-            self.relative_reach(root, &parent) * self.reach(&parent, incoming)
+            match self.reach(StrategyKind::Average, &parent, incoming) {
+                Probability::ZERO => Probability::ZERO,
+                p => p * self.relative_reach(root, &parent),
+            }
         } else {
             unreachable!("tail must have parent")
         }
@@ -435,6 +1476,15 @@ impl Arbitrary for Profile {
     fn random() -> Self {
         Self {
             iterations: 0,
+            discount: Discount::default(),
+            averaging: Averaging::default(),
+            master: None,
+            history: History::default(),
+            pinned: BTreeSet::new(),
+            variances: BTreeMap::new(),
+            summation: Summation::default(),
+            sampling: Sampling::default(),
+            players: crate::N,
             strategies: (0..100)
                 .map(|_| (Bucket::random(), Strategy::random()))
                 .collect(),
@@ -485,21 +1535,1632 @@ mod tests {
     /// and delete the file
     fn persistence() {
         let save = Profile::random();
-        let load = Profile::load(Street::random());
+        let load = Profile::load_or_panic(Street::random());
         assert!(std::iter::empty()
             .chain(save.strategies.iter().zip(load.strategies.iter()))
             .chain(load.strategies.iter().zip(save.strategies.iter()))
             .all(|((s1, l1), (s2, l2))| s1 == s2 && l1 == l2));
     }
-}
 
-#[cfg(feature = "native")]
-impl crate::save::upload::Table for Profile {
-    fn name() -> String {
-        "blueprint".to_string()
+    proptest::proptest! {
+        #[test]
+        fn pgcopy_round_trip(
+            rows in proptest::collection::btree_map(
+                (proptest::prelude::any::<u64>(), 0..Street::Turn.k(), proptest::prelude::any::<u64>()),
+                proptest::collection::btree_map(
+                    proptest::sample::select(vec![Edge::Draw, Edge::Fold, Edge::Check, Edge::Call, Edge::Shove]),
+                    (-1e3 as Utility..1e3, 0f32..1.),
+                    1..4,
+                ),
+                0..32,
+            )
+        ) {
+            use crate::clustering::abstraction::Abstraction;
+            use crate::mccfr::path::Path;
+            let ref path = std::env::temp_dir()
+                .join("robopoker-proptest.blueprint")
+                .to_string_lossy()
+                .to_string();
+            let strategies = rows
+                .into_iter()
+                .map(|((past, k, future), memories)| {
+                    let bucket = Bucket::from((
+                        Path::from(past),
+                        Abstraction::from((Street::Turn, k)),
+                        Path::from(future),
+                    ));
+                    let mut strategy = Strategy::default();
+                    for (edge, memory) in memories {
+                        strategy.entry(edge).or_insert(Memory::from(memory));
+                    }
+                    (bucket, strategy)
+                })
+                .collect::<BTreeMap<Bucket, Strategy>>();
+            let save = Profile {
+                strategies,
+                ..Profile::default()
+            };
+            save.write(path);
+            let load = Profile::read(path).unwrap();
+            proptest::prop_assert!(save.strategies == load.strategies);
+        }
     }
-    fn columns() -> &'static [tokio_postgres::types::Type] {
-        &[
+
+    #[test]
+    fn policy_table_shares_bucket_rows() {
+        use crate::cards::isomorphism::Isomorphism;
+        use crate::clustering::abstraction::Abstraction;
+        use crate::gameplay::game::Game;
+        use crate::mccfr::data::Data;
+        let abstraction = Abstraction::from((Street::Pref, 0));
+        let games = (0..).map(|_| Game::root()).take(2).collect::<Vec<Game>>();
+        let encoder = Encoder::from(
+            games
+                .iter()
+                .map(|g| (Isomorphism::from(g.sweat()), abstraction))
+                .collect::<BTreeMap<_, _>>(),
+        );
+        let trees = games
+            .iter()
+            .map(|g| {
+                let mut tree = Tree::empty(Player(Turn::Choice(0)));
+                tree.plant(Data::from((g.clone(), abstraction)));
+                tree
+            })
+            .collect::<Vec<Tree>>();
+        let ref bucket = trees[0].at(0.into()).bucket().clone();
+        let mut profile = Profile::default();
+        for (i, edge) in Vec::<Edge>::from(bucket.2).into_iter().enumerate() {
+            profile
+                .strategies
+                .entry(bucket.clone())
+                .or_insert_with(Strategy::default)
+                .entry(edge)
+                .or_insert(Memory::from((0., 1. + i as f32)));
+        }
+        let tables = trees
+            .iter()
+            .map(|tree| profile.policy_table(&encoder, tree))
+            .collect::<Vec<_>>();
+        let x = tables[0].get(&games[0].sweat());
+        let y = tables[1].get(&games[1].sweat());
+        assert!(x.is_some());
+        assert!(x == y);
+    }
+
+    #[test]
+    fn zero_reach_short_circuits() {
+        use crate::clustering::abstraction::Abstraction;
+        use crate::gameplay::game::Game;
+        use crate::mccfr::data::Data;
+        let abstraction = Abstraction::from((Street::Pref, 0));
+        let mut tree = Tree::empty(Player::default());
+        let root = tree.plant(Data::from((Game::root(), abstraction))).index();
+        let (edge, game) = tree.at(root).branches().remove(0);
+        let data = Data::from((game, abstraction));
+        let child = tree.fork(Branch(data, edge, root)).index();
+        let (edge, game) = tree.at(child).branches().remove(0);
+        let data = Data::from((game, abstraction));
+        let leaf = tree.fork(Branch(data, edge, child)).index();
+        // only the middle Bucket is witnessed. visiting the root
+        // would panic on the missing Bucket, so a zero result
+        // proves we never walked past the zero-policy Edge.
+        let ref bucket = tree.at(child).bucket().clone();
+        let mut profile = Profile::default();
+        for (i, e) in Vec::<Edge>::from(bucket.2).into_iter().enumerate() {
+            let policy = if e == edge { 0. } else { 1. + i as f32 };
+            profile
+                .strategies
+                .entry(bucket.clone())
+                .or_insert_with(Strategy::default)
+                .entry(e)
+                .or_insert(Memory::from((0., policy)));
+        }
+        assert!(profile.profiled_reach(StrategyKind::Average, &tree.at(leaf)) == 0.);
+    }
+
+    #[test]
+    fn bucket_reach_sums_to_one() {
+        use crate::clustering::abstraction::Abstraction;
+        use crate::gameplay::game::Game;
+        use crate::mccfr::data::Data;
+        let abstraction = Abstraction::from((Street::Pref, 0));
+        let mut tree = Tree::empty(Player::default());
+        let root = tree.plant(Data::from((Game::root(), abstraction))).index();
+        for (edge, game) in tree.at(root).branches() {
+            tree.fork(Branch(Data::from((game, abstraction)), edge, root));
+        }
+        let ref bucket = tree.at(root).bucket().clone();
+        let mut profile = Profile::default();
+        for (i, e) in Vec::<Edge>::from(bucket.2).into_iter().enumerate() {
+            profile
+                .strategies
+                .entry(bucket.clone())
+                .or_insert_with(Strategy::default)
+                .entry(e)
+                .or_insert(Memory::from((0., i as f32)));
+        }
+        let children = tree
+            .at(root)
+            .children()
+            .iter()
+            .map(|child| child.bucket().clone())
+            .collect::<Vec<Bucket>>();
+        let total = children
+            .iter()
+            .map(|child| profile.bucket_reach(StrategyKind::Average, &tree, child))
+            .sum::<Probability>();
+        assert!(profile.bucket_reach(StrategyKind::Average, &tree, bucket) == 1.);
+        assert!((f32::from(total) - 1.).abs() < 1e-6);
+        let first = Vec::<Edge>::from(bucket.2)[0];
+        let ref never = tree.at(root).follow(&first).unwrap().bucket().clone();
+        assert!(profile.bucket_reach(StrategyKind::Average, &tree, never) == 0.);
+    }
+
+    /// check or call down to the first River decision, then expand
+    /// every River line to showdown or fold, witnessing each decision
+    fn river(blueprint: &mut Profile) -> (Tree, petgraph::graph::NodeIndex) {
+        use crate::clustering::abstraction::Abstraction;
+        use crate::gameplay::game::Game;
+        use crate::mccfr::data::Data;
+        use petgraph::graph::NodeIndex;
+        let abstraction = Abstraction::from((Street::Rive, 0));
+        let mut tree = Tree::empty(Player::default());
+        let branches = |tree: &Tree, index: NodeIndex| {
+            tree.at(index)
+                .branches()
+                .into_iter()
+                .map(|(e, g)| Branch(Data::from((g, abstraction)), e, index))
+                .collect::<Vec<Branch>>()
+        };
+        let mut head = tree.plant(Data::from((Game::root(), abstraction))).index();
+        while tree.at(head).data().game().street() != Street::Rive
+            || tree.at(head).player() == Player::chance()
+        {
+            let branches = branches(&tree, head);
+            if tree.at(head).player() != Player::chance() {
+                blueprint.witness(&tree.at(head), &branches);
+            }
+            let branch = branches
+                .into_iter()
+                .find(|b| matches!(b.edge(), Edge::Check | Edge::Call | Edge::Draw))
+                .unwrap();
+            head = tree.fork(branch).index();
+        }
+        let root = head;
+        let mut todo = vec![root];
+        while let Some(index) = todo.pop() {
+            let branches = branches(&tree, index);
+            if !branches.is_empty() {
+                blueprint.witness(&tree.at(index), &branches);
+            }
+            for branch in branches {
+                todo.push(tree.fork(branch).index());
+            }
+        }
+        (tree, root)
+    }
+
+    #[test]
+    fn resolve_subgame_reduces_exploitability() {
+        let mut blueprint = Profile::default();
+        let (tree, root) = river(&mut blueprint);
+        let ref root = tree.at(root);
+        let resolved = blueprint.resolve_subgame(&tree, root, 64);
+        let before = blueprint.exploitability(StrategyKind::Average, root);
+        let after = resolved.exploitability(StrategyKind::Average, root);
+        let decisions = root
+            .subtree()
+            .iter()
+            .filter(|node| matches!(node.player(), Player(Turn::Choice(_))))
+            .map(|node| node.bucket().clone())
+            .collect::<std::collections::BTreeSet<Bucket>>();
+        assert!(resolved.size() == decisions.len());
+        assert!(after < before / 2., "{} !< {}", after, before);
+    }
+
+    #[test]
+    fn exploitability_converges_toward_zero() {
+        let mut blueprint = Profile::default();
+        let (tree, root) = river(&mut blueprint);
+        let ref root = tree.at(root);
+        let exploitability = [0, 16, 256]
+            .map(|epochs| blueprint.resolve_subgame(&tree, root, epochs))
+            .map(|profile| profile.exploitability(StrategyKind::Average, root));
+        assert!(
+            exploitability.windows(2).all(|w| w[1] < w[0]),
+            "{:?}",
+            exploitability
+        );
+        assert!(
+            exploitability[2] < exploitability[0] / 10.,
+            "{:?}",
+            exploitability
+        );
+    }
+
+    /// the poker analogue of always playing Rock: an opponent who
+    /// folds whenever they can and checks otherwise. betting into
+    /// them wins the pot outright, so checking is never better.
+    #[test]
+    fn best_response_exploits_a_folding_opponent() {
+        let mut profile = Profile::default();
+        let (tree, root) = river(&mut profile);
+        let ref root = tree.at(root);
+        let hero = root.player();
+        for node in root.subtree() {
+            if matches!(node.player(), Player(Turn::Choice(_))) && node.player() != hero {
+                let passive = match node.edges().contains(&Edge::Fold) {
+                    true => Edge::Fold,
+                    false => Edge::Check,
+                };
+                let strategy = profile.strategies.get_mut(node.bucket()).unwrap();
+                for edge in strategy.keys().copied().collect::<Vec<Edge>>() {
+                    let play = (edge == passive) as usize as f32;
+                    strategy.get_mut(&edge).unwrap().set_policy(play);
+                }
+            }
+        }
+        let response = profile.best_response(&tree, hero);
+        for policy in response.values() {
+            assert!(policy.values().filter(|p| **p == Probability::ONE).count() == 1);
+            assert!(policy.values().copied().sum::<Probability>() == Probability::ONE);
+        }
+        let ref choice = response[root.bucket()];
+        let best = choice
+            .iter()
+            .find(|(_, p)| **p == Probability::ONE)
+            .unwrap()
+            .0;
+        let value = |edge: &Edge| {
+            let ref child = root.follow(edge).unwrap();
+            profile.best_response_value(StrategyKind::Average, child, &hero)
+        };
+        let check = value(&Edge::Check);
+        let shove = value(&Edge::Shove);
+        assert!(shove >= check, "{} !>= {}", shove, check);
+        assert!(
+            value(best) >= shove - 1e-3,
+            "{:?} {} !>= {}",
+            best,
+            value(best),
+            shove
+        );
+    }
+
+    /// exact counterfactual regret of every walker (Bucket, Edge) below
+    /// root, by full enumeration of the Tree, alongside the opponents'
+    /// reach of its Bucket. reach is measured from root.
+    fn exact_regret(
+        profile: &Profile,
+        root: &Node,
+    ) -> BTreeMap<(Bucket, Edge), (Utility, Utility)> {
+        fn value(profile: &Profile, node: &Node, walker: &Player) -> Utility {
+            let children = node.children();
+            if children.is_empty() {
+                node.payoff(walker)
+            } else if node.player() == Player::chance() {
+                children
+                    .iter()
+                    .map(|child| value(profile, child, walker))
+                    .sum::<Utility>()
+                    / children.len() as Utility
+            } else {
+                children
+                    .iter()
+                    .map(|child| (child, child.incoming().unwrap()))
+                    .map(|(child, e)| {
+                        profile.weight(node.bucket(), e) * value(profile, child, walker)
+                    })
+                    .sum()
+            }
+        }
+        fn descend(
+            profile: &Profile,
+            node: &Node,
+            reach: Probability,
+            regrets: &mut BTreeMap<(Bucket, Edge), (Utility, Utility)>,
+        ) {
+            let ref walker = profile.walker();
+            let children = node.children();
+            for child in children.iter() {
+                let edge = child.incoming().unwrap();
+                if node.player() == *walker {
+                    let gain = value(profile, child, walker) - value(profile, node, walker);
+                    let (regret, reached) = regrets.entry((*node.bucket(), *edge)).or_default();
+                    *regret += reach * gain;
+                    *reached += f32::from(reach);
+                    descend(profile, child, reach, regrets);
+                } else if node.player() == Player::chance() {
+                    let draw = Probability::uniform(children.len());
+                    descend(profile, child, reach * draw, regrets);
+                } else {
+                    let p = profile.weight(node.bucket(), edge);
+                    descend(profile, child, reach * p, regrets);
+                }
+            }
+        }
+        let mut regrets = BTreeMap::new();
+        descend(profile, root, Probability::ONE, &mut regrets);
+        regrets
+    }
+
+    /// sampling below root, exactly as Blueprint::sample does it:
+    /// walker Edges by the Profile's Sampling, one opponent Edge
+    /// by Profile, one chance Edge.
+    /// the path above root is copied as-is so the Buckets line up.
+    fn sample_below(profile: &Profile, tree: &Tree, root: &Node) -> Tree {
+        use crate::mccfr::data::Data;
+        let copy = |node: &Node| {
+            let game = node.data().game().clone();
+            Data::from((game, node.data().abstraction().clone()))
+        };
+        let mut sampled = Tree::empty(profile.walker());
+        let mut path = vec![*root];
+        while let Some(parent) = path.last().unwrap().parent() {
+            path.push(parent);
+        }
+        let mut head = sampled.plant(copy(path.last().unwrap())).index();
+        for node in path.iter().rev().skip(1) {
+            let edge = *node.incoming().unwrap();
+            head = sampled.fork(Branch(copy(node), edge, head)).index();
+        }
+        let mut todo = vec![(root.index(), head)];
+        while let Some((original, index)) = todo.pop() {
+            let node = tree.at(original);
+            let branches = node
+                .children()
+                .iter()
+                .map(|child| Branch(copy(child), *child.incoming().unwrap(), index))
+                .collect::<Vec<Branch>>();
+            if branches.is_empty() {
+                continue;
+            }
+            let ref head = sampled.at(index);
+            let chosen = match node.player() {
+                p if p == Player::chance() => profile.explore_any(branches, head),
+                p if p == profile.walker() => profile.explore_all(branches, head),
+                _ => profile.explore_one(branches, head),
+            };
+            for branch in chosen {
+                let next = node.follow(branch.edge()).unwrap().index();
+                todo.push((next, sampled.fork(branch).index()));
+            }
+        }
+        sampled
+    }
+
+    #[test]
+    fn sampled_regret_is_unbiased() {
+        regret_is_unbiased(Sampling::External);
+    }
+
+    #[test]
+    fn outcome_sampled_regret_is_unbiased() {
+        regret_is_unbiased(Sampling::Outcome);
+    }
+
+    #[test]
+    fn vanilla_regret_is_exact() {
+        use crate::mccfr::partition::Partition;
+        let mut profile = Profile::default().with_sampling(Sampling::Vanilla);
+        let (tree, root) = river(&mut profile);
+        let ref root = tree.at(root);
+        let ref mut rng = SmallRng::seed_from_u64(0);
+        for strategy in profile.strategies.values_mut() {
+            for edge in strategy.keys().copied().collect::<Vec<Edge>>() {
+                let weight = rng.gen_range(0.1..1.0);
+                strategy.get_mut(&edge).unwrap().set_policy(weight);
+            }
+        }
+        for walker in 0..2 {
+            profile.iterations = walker;
+            let exact = exact_regret(&profile, root);
+            // exact_regret measures reach from root, so
+            // divide out everyone else's reach into root
+            let prefix = f32::from(profile.external_reach(root) * profile.chance_reach(root));
+            let walked = sample_below(&profile, &tree, root);
+            let regrets = Vec::<Info>::from(Partition::from(walked))
+                .into_iter()
+                .filter(|info| info.node().data().game().street() == Street::Rive)
+                .flat_map(|info| {
+                    let bucket = *info.node().bucket();
+                    profile
+                        .regret_vector(&info)
+                        .into_iter()
+                        .map(move |(edge, regret)| ((bucket, edge), regret / prefix))
+                })
+                .collect::<BTreeMap<(Bucket, Edge), Utility>>();
+            assert!(!exact.is_empty());
+            for (key, (exact, _)) in exact {
+                let regret = regrets[&key];
+                assert!(
+                    (regret - exact).abs() <= 1e-3 * (1. + exact.abs()),
+                    "{:?} vanilla {} exact {}",
+                    key,
+                    regret,
+                    exact
+                );
+            }
+        }
+    }
+
+    fn regret_is_unbiased(sampling: Sampling) {
+        use crate::mccfr::partition::Partition;
+        const SAMPLES: usize = 1000;
+        let mut profile = Profile::default().with_sampling(sampling);
+        let (tree, root) = river(&mut profile);
+        let ref root = tree.at(root);
+        let ref mut rng = SmallRng::seed_from_u64(0);
+        for strategy in profile.strategies.values_mut() {
+            for edge in strategy.keys().copied().collect::<Vec<Edge>>() {
+                let weight = rng.gen_range(0.1..1.0);
+                strategy.get_mut(&edge).unwrap().set_policy(weight);
+            }
+        }
+        for walker in 0..2 {
+            profile.iterations = walker;
+            let exact = exact_regret(&profile, root);
+            // the path down to root is fixed rather than walked, so
+            // undo the importance weight outcome sampling puts on it
+            let prefix = f32::from(profile.sampled_reach(root));
+            let mut moments = BTreeMap::<(Bucket, Edge), (Utility, Utility, usize)>::new();
+            for _ in 0..SAMPLES {
+                profile.iterations += 2;
+                let sampled = sample_below(&profile, &tree, root);
+                let regrets = Vec::<Info>::from(Partition::from(sampled))
+                    .into_iter()
+                    .filter(|info| info.node().data().game().street() == Street::Rive)
+                    .flat_map(|info| {
+                        let bucket = *info.node().bucket();
+                        profile
+                            .regret_vector(&info)
+                            .into_iter()
+                            .map(move |(edge, regret)| ((bucket, edge), regret * prefix))
+                    })
+                    .collect::<BTreeMap<(Bucket, Edge), Utility>>();
+                for key in exact.keys() {
+                    let x = regrets.get(key).copied().unwrap_or_default();
+                    let (sum, squares, visits) = moments.entry(*key).or_default();
+                    *sum += x;
+                    *squares += x * x;
+                    *visits += regrets.contains_key(key) as usize;
+                }
+            }
+            // rarely reached Buckets are too heavy-tailed for a normal
+            // approximation, so only judge those sampled often enough.
+            // outcome sampling also has to walk our own way there
+            let reached = exact
+                .iter()
+                .filter(|(_, (_, reach))| *reach * SAMPLES as Utility >= 100.)
+                .filter(|(key, _)| moments[key].2 >= 100)
+                .map(|(key, (exact, _))| (key, exact))
+                .collect::<Vec<_>>();
+            assert!(reached.len() > 8);
+            for (key, exact) in reached {
+                let (sum, squares, _) = moments[key];
+                let mean = sum / SAMPLES as Utility;
+                let variance = (squares / SAMPLES as Utility - mean * mean).max(0.);
+                let error = (variance / SAMPLES as Utility).sqrt();
+                let tolerance = 5. * error + 1e-3 * (1. + exact.abs());
+                assert!(
+                    (mean - exact).abs() <= tolerance,
+                    "{:?} sampled {} exact {} tolerance {}",
+                    key,
+                    mean,
+                    exact,
+                    tolerance
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn memoized_regret_matches_recomputed() {
+        use crate::mccfr::partition::Partition;
+        let mut profile = Profile::default();
+        let (tree, _) = river(&mut profile);
+        let ref mut rng = SmallRng::seed_from_u64(0);
+        for strategy in profile.strategies.values_mut() {
+            for edge in strategy.keys().copied().collect::<Vec<Edge>>() {
+                let weight = rng.gen_range(0.1..1.0);
+                strategy.get_mut(&edge).unwrap().set_policy(weight);
+            }
+        }
+        let infos = Vec::<Info>::from(Partition::from(tree))
+            .into_iter()
+            .filter(|info| info.node().data().game().street() == Street::Rive)
+            .collect::<Vec<Info>>();
+        assert!(!infos.is_empty());
+        for info in infos.iter() {
+            let memoized = profile.regret_vector(info);
+            let recomputed = info
+                .node()
+                .edges()
+                .into_iter()
+                .map(|edge| {
+                    let regret = profile.sum(info.roots().iter().map(|head| {
+                        let expected = profile.expected_value(head);
+                        (head.index(), profile.cfactual_value(head, &edge) - expected)
+                    }));
+                    (edge, regret.max(crate::REGRET_MIN).min(crate::REGRET_MAX))
+                })
+                .collect::<BTreeMap<Edge, Utility>>();
+            assert!(memoized == recomputed, "{:?} != {:?}", memoized, recomputed);
+        }
+    }
+
+    #[test]
+    fn pinned_buckets_hold_while_others_train() {
+        use crate::mccfr::partition::Partition;
+        let mut profile = Profile::default();
+        let (tree, root) = river(&mut profile);
+        let ref root = tree.at(root);
+        let ref pinned = root.bucket().clone();
+        let edges = root.edges();
+        let policy = edges
+            .iter()
+            .map(|e| match e == &edges[0] {
+                true => (*e, Probability::from(0.5)),
+                false => (*e, Probability::from(0.5 / (edges.len() - 1) as f32)),
+            })
+            .collect::<BTreeMap<Edge, Probability>>();
+        profile.pin(*pinned, policy.clone());
+        let before = profile.exploitability(StrategyKind::Average, root);
+        for _ in 0..256 {
+            profile.next();
+            let sampled = sample_below(&profile, &tree, root);
+            for info in Vec::<Info>::from(Partition::from(sampled))
+                .into_iter()
+                .filter(|info| info.node().data().game().street() == Street::Rive)
+            {
+                let bucket = info.node().bucket().clone();
+                let counterfactual = profile.counterfactual(info);
+                profile.add_regret(&bucket, counterfactual.regret());
+                profile.add_policy(&bucket, counterfactual.policy());
+            }
+        }
+        let after = profile.exploitability(StrategyKind::Average, root);
+        for (edge, p) in policy {
+            let weight = profile.weight(pinned, &edge);
+            let current = profile.probability(StrategyKind::Current, pinned, &edge);
+            assert!((f32::from(weight) - f32::from(p)).abs() < Probability::TOLERANCE);
+            assert!((f32::from(current) - f32::from(p)).abs() < Probability::TOLERANCE);
+        }
+        assert!(after < before, "{} !< {}", after, before);
+    }
+
+    #[test]
+    fn pinned_zero_edge_is_never_played() {
+        use crate::mccfr::partition::Partition;
+        let mut profile = Profile::default();
+        let (tree, root) = river(&mut profile);
+        let ref pinned = tree.at(root).bucket().clone();
+        let edges = tree.at(root).edges();
+        let policy = edges
+            .iter()
+            .map(|e| match e == &edges[0] {
+                true => (*e, Probability::from(0.)),
+                false => (*e, Probability::from(1. / (edges.len() - 1) as f32)),
+            })
+            .collect::<BTreeMap<Edge, Probability>>();
+        profile.pin(*pinned, policy);
+        if tree.at(root).player() != profile.walker() {
+            profile.next();
+        }
+        let sampled = sample_below(&profile, &tree, &tree.at(root));
+        let info = Vec::<Info>::from(Partition::from(sampled))
+            .into_iter()
+            .find(|info| info.node().bucket() == pinned)
+            .expect("pinned bucket sampled");
+        let ref zero = edges[0];
+        assert!(profile.policy_vector(&info)[zero] == Probability::from(0.));
+        assert!(profile.probability(StrategyKind::Current, pinned, zero) == Probability::from(0.));
+        assert!(profile.distribution(StrategyKind::Current, pinned)[zero] == Probability::from(0.));
+    }
+
+    #[test]
+    fn rare_buckets_have_noisier_regrets() {
+        let ref mut rng = rand::thread_rng();
+        let mut profile = Profile::random();
+        let buckets = profile.strategies.keys().copied().collect::<Vec<Bucket>>();
+        let (rare, often) = (buckets[0], buckets[1]);
+        let sample = |profile: &Profile, bucket: &Bucket, rng: &mut rand::rngs::ThreadRng| {
+            Regret::from(
+                profile.strategies[bucket]
+                    .keys()
+                    .map(|edge| (*edge, rng.gen_range(-1.0..1.0)))
+                    .collect::<BTreeMap<Edge, Utility>>(),
+            )
+        };
+        for epoch in 0..64 {
+            if epoch % 4 == 0 {
+                let ref regret = sample(&profile, &rare, rng);
+                profile.add_regret(&rare, regret);
+            }
+            for _ in 0..16 {
+                let ref regret = sample(&profile, &often, rng);
+                profile.add_regret(&often, regret);
+            }
+            profile.next();
+        }
+        let noise = |bucket: &Bucket| {
+            profile.strategies[bucket]
+                .keys()
+                .map(|edge| profile.regret_variance(bucket, edge))
+                .fold(Utility::MIN, Utility::max)
+        };
+        let unseen = Bucket::random();
+        assert!(
+            noise(&rare) > noise(&often),
+            "{} !> {}",
+            noise(&rare),
+            noise(&often)
+        );
+        assert!(profile.regret_variance(&unseen, &Edge::Fold) == Utility::INFINITY);
+    }
+
+    #[test]
+    fn priming_biases_toward_observed_edges() {
+        let mut profile = Profile::default();
+        let legal = |b: &Bucket| {
+            Vec::<Edge>::from(b.2)
+                .into_iter()
+                .collect::<BTreeSet<Edge>>()
+        };
+        let bucket = std::iter::repeat_with(Bucket::random)
+            .find(|b| legal(b).len() > 1)
+            .unwrap();
+        let edges = legal(&bucket).into_iter().collect::<Vec<Edge>>();
+        profile.prime_from_histories(std::iter::repeat((bucket, edges[0])).take(100));
+        let average = profile.distribution(StrategyKind::Average, &bucket);
+        assert!(average.keys().eq(edges.iter()));
+        assert!(average[&edges[0]] == Probability::from(1.));
+        assert!(edges[1..]
+            .iter()
+            .all(|e| average[e] == Probability::from(0.)));
+        assert!(profile.size() == 1);
+    }
+
+    #[test]
+    fn utilities_are_reproducible() {
+        use crate::mccfr::partition::Partition;
+        let mut profile = Profile::default();
+        let (tree, root) = river(&mut profile);
+        let sampled = sample_below(&profile, &tree, &tree.at(root));
+        let infos = Vec::<Info>::from(Partition::from(sampled))
+            .into_iter()
+            .filter(|info| info.node().data().game().street() == Street::Rive)
+            .collect::<Vec<Info>>();
+        assert!(!infos.is_empty());
+        let regrets = |profile: &Profile| {
+            infos
+                .iter()
+                .map(|info| profile.regret_vector(info))
+                .collect::<Vec<BTreeMap<Edge, Utility>>>()
+        };
+        let naive = regrets(&profile);
+        assert!(naive == regrets(&profile));
+        let profile = profile.with_summation(Summation::Kahan);
+        let kahan = regrets(&profile);
+        assert!(kahan == regrets(&profile));
+        for (naive, kahan) in naive.iter().zip(kahan.iter()) {
+            for (edge, r) in naive {
+                assert!((r - kahan[edge]).abs() <= 1e-3 * r.abs().max(1.));
+            }
+        }
+    }
+
+    /// river Infosets of one dealt Tree, witnessed into profile,
+    /// so that training on them is deterministic
+    #[cfg(feature = "native")]
+    fn dealt(profile: &mut Profile) -> Vec<Info> {
+        use crate::mccfr::partition::Partition;
+        let (tree, _) = river(profile);
+        Vec::<Info>::from(Partition::from(tree))
+            .into_iter()
+            .filter(|info| info.node().data().game().street() == Street::Rive)
+            .collect()
+    }
+
+    /// train on the same Infosets every epoch. their
+    /// walker only updates on alternate epochs
+    #[cfg(feature = "native")]
+    fn train(profile: &mut Profile, infos: &[Info], epochs: usize) {
+        for _ in 0..epochs {
+            let counterfactuals = infos
+                .iter()
+                .filter(|_| profile.walker() == Player::default())
+                .cloned()
+                .map(|info| profile.counterfactual(info))
+                .collect::<Vec<Counterfactual>>();
+            profile.apply(counterfactuals);
+            profile.next();
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn parallel_updates_match_serial() {
+        let mut serial = Profile::default();
+        let ref infos = dealt(&mut serial);
+        let mut parallel = Profile {
+            strategies: serial.strategies.clone(),
+            ..Profile::default()
+        };
+        for _ in 0..8 {
+            let batch = infos
+                .iter()
+                .filter(|_| serial.walker() == Player::default())
+                .cloned()
+                .collect::<Vec<Info>>();
+            let expected = batch
+                .iter()
+                .cloned()
+                .map(|info| serial.counterfactual(info))
+                .collect::<Vec<Counterfactual>>();
+            let computed = parallel.counterfactuals(batch);
+            assert!(expected.len() == computed.len());
+            for (x, y) in expected.iter().zip(computed.iter()) {
+                assert!(x.info().node().bucket() == y.info().node().bucket());
+                assert!(x.regret().inner() == y.regret().inner());
+                assert!(x.policy().inner() == y.policy().inner());
+            }
+            serial.apply(expected);
+            parallel.apply(computed);
+            serial.next();
+            parallel.next();
+        }
+        assert!(serial.strategies == parallel.strategies);
+    }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn resumed_training_matches_uninterrupted() {
+        let ref path = std::env::temp_dir()
+            .join("robopoker-resume.blueprint")
+            .to_string_lossy()
+            .to_string();
+        let mut straight = Profile::default();
+        let ref infos = dealt(&mut straight);
+        let mut halted = Profile {
+            strategies: straight.strategies.clone(),
+            ..Profile::default()
+        };
+        train(&mut straight, infos, 8);
+        train(&mut halted, infos, 3);
+        halted.write(path);
+        halted.write_metadata(path);
+        let mut resumed = Profile::resume(path).unwrap();
+        assert!(resumed.epochs() == 3);
+        train(&mut resumed, infos, 5);
+        assert!(resumed.epochs() == straight.epochs());
+        assert!(resumed.strategies == straight.strategies);
+        std::fs::remove_file(path).ok();
+        std::fs::remove_file(crate::mccfr::metadata::Metadata::path(path)).ok();
+    }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn save_load_save_is_identical() {
+        use crate::mccfr::metadata::Metadata;
+        let path = |name: &str| {
+            std::env::temp_dir()
+                .join(name)
+                .to_string_lossy()
+                .to_string()
+        };
+        let ref a = path("robopoker-cycle-a.blueprint");
+        let ref b = path("robopoker-cycle-b.blueprint");
+        let mut profile = Profile::default();
+        let ref infos = dealt(&mut profile);
+        train(&mut profile, infos, 3);
+        profile.write(a);
+        profile.write_metadata(a);
+        let loaded = Profile::resume(a).unwrap();
+        assert!(loaded.epochs() == profile.epochs());
+        assert!(loaded.discount == profile.discount);
+        loaded.write(b);
+        loaded.write_metadata(b);
+        assert!(std::fs::read(a).unwrap() == std::fs::read(b).unwrap());
+        assert!(Metadata::load(&Metadata::path(a)) == Metadata::load(&Metadata::path(b)));
+        for path in [a, b] {
+            std::fs::remove_file(path).ok();
+            std::fs::remove_file(Metadata::path(path)).ok();
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn experiments_save_side_by_side() {
+        let dirs = ["a", "b"].map(|name| {
+            let dir = std::env::temp_dir().join(format!("robopoker-experiment-{}", name));
+            std::fs::create_dir_all(&dir).unwrap();
+            dir
+        });
+        let mut a = Profile::default();
+        let mut b = Profile::default();
+        let ref infos = dealt(&mut a);
+        dealt(&mut b);
+        train(&mut a, infos, 2);
+        a.save_to(&dirs[0]);
+        b.save_to(&dirs[1]);
+        assert!(Profile::load_from(&dirs[0]).unwrap().strategies == a.strategies);
+        assert!(Profile::load_from(&dirs[1]).unwrap().strategies == b.strategies);
+        assert!(Profile::load_from(&dirs[0]).unwrap().epochs() == 2);
+        for dir in dirs {
+            std::fs::remove_dir_all(dir).ok();
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn normalized_export_sums_to_one() {
+        let ref path = std::env::temp_dir()
+            .join("robopoker-normalized.blueprint")
+            .to_string_lossy()
+            .to_string();
+        let mut profile = Profile::default();
+        let ref infos = dealt(&mut profile);
+        train(&mut profile, infos, 3);
+        let mut buckets = profile.strategies.keys().cloned().collect::<Vec<Bucket>>();
+        let ref zero = buckets.pop().unwrap();
+        for (i, bucket) in buckets.iter().enumerate() {
+            let strategy = profile.strategies.get_mut(bucket).unwrap();
+            for edge in strategy.keys().copied().collect::<Vec<Edge>>() {
+                let memory = strategy.get_mut(&edge).unwrap();
+                memory.set_policy(memory.policy() * (i + 3) as f32);
+            }
+        }
+        let strategy = profile.strategies.get_mut(zero).unwrap();
+        for edge in strategy.keys().copied().collect::<Vec<Edge>>() {
+            strategy.get_mut(&edge).unwrap().set_policy(0.);
+        }
+        profile.write_normalized(path);
+        let loaded = Profile::read(path).unwrap();
+        assert!(loaded.size() == profile.size());
+        for (bucket, strategy) in loaded.strategies.iter() {
+            let sum = strategy.values().map(Memory::policy).sum::<f32>();
+            assert!((sum - 1.).abs() < 1e-6, "{}", sum);
+            assert!(strategy.average() == profile.strategies[bucket].normalized().average());
+        }
+        let n = loaded.strategies[zero].values().count();
+        assert!(loaded.strategies[zero]
+            .values()
+            .all(|memory| memory.policy() == 1. / n as f32));
+        std::fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn average_policy_normalizes_advice() {
+        use crate::clustering::abstraction::Abstraction;
+        use crate::mccfr::path::Path;
+        let present = Abstraction::from((Street::Rive, 0));
+        let choices = Path::from(vec![Edge::Fold, Edge::Call, Edge::Check]);
+        let bucket = |i: u64| Bucket::from((Path::from(i), present, choices));
+        let mut profile = Profile::default();
+        let strategy = profile.strategies.entry(bucket(0)).or_default();
+        for (edge, advice) in [(Edge::Fold, 1.), (Edge::Call, 3.), (Edge::Check, 4.)] {
+            strategy.entry(edge).or_insert(Memory::from((0., advice)));
+        }
+        let strategy = profile.strategies.entry(bucket(1)).or_default();
+        for edge in [Edge::Fold, Edge::Call] {
+            strategy.entry(edge).or_insert(Memory::from((0., 0.)));
+        }
+        let seen = profile.average_policy(&bucket(0));
+        assert!(seen[&Edge::Fold] == Probability::from(0.125));
+        assert!(seen[&Edge::Call] == Probability::from(0.375));
+        assert!(seen[&Edge::Check] == Probability::from(0.5));
+        let degenerate = profile.average_policy(&bucket(1));
+        assert!(degenerate.len() == 2);
+        assert!(degenerate.values().all(|p| *p == Probability::uniform(2)));
+        let unseen = profile.average_policy(&bucket(2));
+        assert!(unseen.len() == 3);
+        assert!(unseen.values().all(|p| *p == Probability::uniform(3)));
+    }
+    #[test]
+    fn purified_drops_rare_edges() {
+        use crate::clustering::abstraction::Abstraction;
+        use crate::mccfr::path::Path;
+        let present = Abstraction::from((Street::Rive, 0));
+        let edges = [Edge::Fold, Edge::Call, Edge::Check, Edge::Draw];
+        let bucket = Bucket::from((Path::from(0u64), present, Path::from(edges.to_vec())));
+        let mut profile = Profile::default();
+        let strategy = profile.strategies.entry(bucket).or_default();
+        for (edge, advice) in edges.into_iter().zip([0.7, 0.2, 0.08, 0.02]) {
+            strategy.entry(edge).or_insert(Memory::from((0., advice)));
+        }
+        let purified = profile
+            .purified(Probability::from(0.1))
+            .average_policy(&bucket);
+        for (edge, expected) in edges.into_iter().zip([7. / 9., 2. / 9., 0., 0.]) {
+            assert!((f32::from(purified[&edge]) - expected).abs() < 1e-6);
+        }
+        let purest = profile
+            .purified(Probability::from(0.8))
+            .average_policy(&bucket);
+        assert!(purest[&Edge::Fold] == Probability::from(1.));
+        assert!(edges[1..]
+            .iter()
+            .all(|e| purest[e] == Probability::from(0.)));
+    }
+
+    #[test]
+    fn sample_action_follows_average_policy() {
+        use crate::clustering::abstraction::Abstraction;
+        use crate::mccfr::path::Path;
+        let present = Abstraction::from((Street::Rive, 0));
+        let choices = Path::from(vec![Edge::Fold, Edge::Call, Edge::Check]);
+        let bucket = |i: u64| Bucket::from((Path::from(i), present, choices));
+        let mut profile = Profile::default();
+        let strategy = profile.strategies.entry(bucket(0)).or_default();
+        for (edge, advice) in [(Edge::Fold, 1.), (Edge::Call, 3.), (Edge::Check, 0.)] {
+            strategy.entry(edge).or_insert(Memory::from((0., advice)));
+        }
+        let ref mut rng = SmallRng::seed_from_u64(0);
+        let n = 20_000;
+        let frequencies = |profile: &Profile, bucket: &Bucket, rng: &mut SmallRng| {
+            let mut counts = BTreeMap::<Edge, usize>::new();
+            for _ in 0..n {
+                *counts
+                    .entry(profile.sample_action(bucket, rng))
+                    .or_default() += 1;
+            }
+            counts
+                .into_iter()
+                .map(|(edge, count)| (edge, count as f32 / n as f32))
+                .collect::<BTreeMap<Edge, f32>>()
+        };
+        for bucket in [bucket(0), bucket(1)] {
+            let expected = profile.average_policy(&bucket);
+            let observed = frequencies(&profile, &bucket, rng);
+            for (edge, p) in expected {
+                let f = observed.get(&edge).copied().unwrap_or(0.);
+                assert!((f - f32::from(p)).abs() < 0.02, "{:?} {} {}", edge, f, p);
+            }
+        }
+        assert!(!frequencies(&profile, &bucket(0), rng).contains_key(&Edge::Check));
+    }
+    /// a toy game of three players who each either Call or Fold.
+    /// calling always pays one, less half for each other caller,
+    /// so Call is dominant for everyone. a walker that only ever
+    /// alternated between two seats would never teach the third.
+    #[test]
+    fn three_players_take_turns() {
+        use crate::clustering::abstraction::Abstraction;
+        use crate::mccfr::path::Path;
+        let mut profile = Profile::default().with_players(3);
+        let present = Abstraction::from((Street::Rive, 0));
+        let buckets =
+            [0u64, 1, 2].map(|i| Bucket::from((Path::from(i), present, Path::from(0u64))));
+        for bucket in buckets.iter() {
+            let strategy = profile.strategies.entry(*bucket).or_default();
+            for edge in [Edge::Fold, Edge::Call] {
+                strategy.entry(edge).or_insert(Memory::from((0., 0.5)));
+            }
+        }
+        for i in 0..300 {
+            let walker = profile.walker();
+            let seat = match walker.0 {
+                Turn::Choice(seat) => seat,
+                _ => unreachable!(),
+            };
+            assert!(seat == i % 3);
+            let ref bucket = buckets[seat];
+            let others = (0..3)
+                .filter(|j| *j != seat)
+                .map(|j| profile.probability(StrategyKind::Current, &buckets[j], &Edge::Call))
+                .map(f32::from)
+                .sum::<Utility>();
+            let value = |edge: &Edge| (*edge == Edge::Call) as usize as Utility - 0.5 * others;
+            let current = profile.current(bucket);
+            let expected = current.iter().map(|(e, p)| *p * value(e)).sum::<Utility>();
+            let regret = current
+                .keys()
+                .map(|e| (*e, value(e) - expected))
+                .collect::<BTreeMap<Edge, Utility>>();
+            profile.add_regret(bucket, &Regret::from(regret));
+            profile.add_policy(bucket, &Policy::from(current));
+            profile.next();
+        }
+        assert!(profile.seats().len() == 3);
+        for bucket in buckets.iter() {
+            let call = profile.weight(bucket, &Edge::Call);
+            assert!(call > Probability::from(0.9), "{}", call);
+        }
+    }
+
+    #[test]
+    fn linear_averaging_weights_by_epoch() {
+        let bucket = Bucket::random();
+        let average = |averaging: Averaging| {
+            let mut profile = Profile::default().with_averaging(averaging);
+            let strategy = profile.strategies.entry(bucket.clone()).or_default();
+            strategy.entry(Edge::Fold).or_default();
+            strategy.entry(Edge::Call).or_default();
+            // fold with probability 1/k in the k-th epoch
+            for k in 1..=4 {
+                let fold = Probability::from(1. / k as f32);
+                let call = Probability::from(1. - 1. / k as f32);
+                let policy = Policy::from(BTreeMap::from([(Edge::Fold, fold), (Edge::Call, call)]));
+                profile.add_policy(&bucket, &policy);
+                profile.next();
+            }
+            f32::from(profile.weight(&bucket, &Edge::Fold))
+        };
+        let uniform = (1. + 1. / 2. + 1. / 3. + 1. / 4.) / 4.;
+        let linear = (1. + 1. + 1. + 1.) / (1. + 2. + 3. + 4.);
+        assert!((average(Averaging::Uniform) - uniform).abs() < 1e-6);
+        assert!((average(Averaging::Linear) - linear).abs() < 1e-6);
+        assert!(average(Averaging::Discounted) < linear);
+    }
+
+    #[test]
+    fn few_epochs_cover_little() {
+        use crate::cards::isomorphisms::IsomorphismIterator;
+        use crate::clustering::abstraction::Abstraction;
+        let encoder = Encoder::from(
+            IsomorphismIterator::from(Street::Pref)
+                .enumerate()
+                .map(|(i, iso)| (iso, Abstraction::from((Street::Pref, i))))
+                .collect::<BTreeMap<_, _>>(),
+        );
+        // each epoch deals a fresh preflop root and witnesses it
+        let coverage = |epochs: usize| {
+            let mut profile = Profile::default();
+            for _ in 0..epochs {
+                let mut tree = Tree::empty(Player::default());
+                let root = tree.plant(encoder.seed());
+                profile.witness(&root, &encoder.branches(&root));
+            }
+            profile.coverage(&encoder)
+        };
+        let few = coverage(4);
+        let many = coverage(4096);
+        assert!(few.uncovered(Street::Pref) > 0.9);
+        assert!(many.uncovered(Street::Pref) < 0.1);
+        assert!(few.reachable[&Street::Pref].len() == Street::Pref.n_isomorphisms());
+        assert!(few.untrained[&Street::Pref] == few.witnessed[&Street::Pref]);
+        assert!(few.uncovered(Street::Flop) == 0.);
+    }
+
+    #[test]
+    fn preflop_grid_places_suited_above_diagonal() {
+        use crate::cards::isomorphism::Isomorphism;
+        use crate::cards::isomorphisms::IsomorphismIterator;
+        use crate::clustering::abstraction::Abstraction;
+        let encoder = Encoder::from(
+            IsomorphismIterator::from(Street::Pref)
+                .enumerate()
+                .map(|(i, iso)| (iso, Abstraction::from((Street::Pref, i))))
+                .collect::<BTreeMap<_, _>>(),
+        );
+        let mut profile = Profile::default();
+        let mut roots = BTreeMap::new();
+        for _ in 0..8192 {
+            let mut tree = Tree::empty(Player::default());
+            let root = tree.plant(encoder.seed());
+            profile.witness(&root, &encoder.branches(&root));
+            roots.insert(root.data().game().sweat(), root.bucket().clone());
+        }
+        // push suited Ace-King toward its first Edge
+        let suited = Observation::new(
+            [
+                Card::from((Rank::Ace, Suit::D)),
+                Card::from((Rank::King, Suit::D)),
+            ],
+            &[],
+        )
+        .unwrap();
+        let bucket = roots
+            .iter()
+            .find(|(obs, _)| Isomorphism::from(**obs) == Isomorphism::from(suited))
+            .map(|(_, bucket)| *bucket)
+            .unwrap();
+        let edge = Vec::<Edge>::from(bucket.2)[0];
+        profile.prime_from_histories(std::iter::repeat((bucket, edge)).take(10));
+        let grid = profile.preflop_grid(&encoder);
+        assert!(grid.iter().flatten().all(|cell| !cell.is_empty()));
+        assert!(grid[0][1][&edge] == Probability::from(1.));
+        assert!(grid[1][0][&edge] < Probability::from(1.));
+    }
+
+    #[test]
+    #[should_panic(expected = "pinned strategy must sum to one")]
+    fn pinned_strategy_is_a_distribution() {
+        let bucket = Bucket::random();
+        let edges = Vec::<Edge>::from(bucket.2);
+        let half = edges
+            .iter()
+            .map(|e| (*e, Probability::from(0.5 / edges.len() as f32)))
+            .collect();
+        Profile::default().pin(bucket, half);
+    }
+
+    #[test]
+    fn exploitability_decomposes_by_street() {
+        use crate::clustering::abstraction::Abstraction;
+        use crate::gameplay::game::Game;
+        use crate::mccfr::data::Data;
+        use petgraph::graph::NodeIndex;
+        let mut blueprint = Profile::default();
+        let mut tree = Tree::empty(Player::default());
+        let branches = |tree: &Tree, index: NodeIndex| {
+            tree.at(index)
+                .branches()
+                .into_iter()
+                .map(|(e, g)| {
+                    let abstraction = Abstraction::from((g.street(), 0));
+                    Branch(Data::from((g, abstraction)), e, index)
+                })
+                .collect::<Vec<Branch>>()
+        };
+        // check or call down to the first Turn decision
+        let abstraction = Abstraction::from((Street::Pref, 0));
+        let mut head = tree.plant(Data::from((Game::root(), abstraction))).index();
+        while tree.at(head).data().game().street() != Street::Turn
+            || tree.at(head).player() == Player::chance()
+        {
+            let branches = branches(&tree, head);
+            if tree.at(head).player() != Player::chance() {
+                blueprint.witness(&tree.at(head), &branches);
+            }
+            let branch = branches
+                .into_iter()
+                .find(|b| matches!(b.edge(), Edge::Check | Edge::Call | Edge::Draw))
+                .unwrap();
+            head = tree.fork(branch).index();
+        }
+        // then expand every Turn and River line to showdown or fold
+        let root = head;
+        let mut todo = vec![root];
+        while let Some(index) = todo.pop() {
+            let branches = branches(&tree, index);
+            if !branches.is_empty() && tree.at(index).player() != Player::chance() {
+                blueprint.witness(&tree.at(index), &branches);
+            }
+            for branch in branches {
+                todo.push(tree.fork(branch).index());
+            }
+        }
+        let ref root = tree.at(root);
+        // play the Turn and River well, then take the worst Turn action
+        let mut profile = blueprint.resolve_subgame(&tree, root, 64);
+        let good = profile.exploitability_by_street(StrategyKind::Average, root);
+        let worst = root
+            .subtree()
+            .into_iter()
+            .filter(|node| node.data().game().street() == Street::Turn)
+            .filter(|node| matches!(node.player(), Player(Turn::Choice(_))))
+            .map(|node| {
+                let child = node
+                    .children()
+                    .into_iter()
+                    .map(|child| {
+                        let value = profile.best_response_value(
+                            StrategyKind::Average,
+                            &child,
+                            &node.player(),
+                        );
+                        (value, child)
+                    })
+                    .min_by(|(a, _), (b, _)| a.partial_cmp(b).unwrap())
+                    .map(|(_, child)| *child.incoming().unwrap())
+                    .unwrap();
+                (node.bucket().clone(), child)
+            })
+            .collect::<Vec<(Bucket, Edge)>>();
+        for (bucket, worst) in worst {
+            let strategy = profile.strategies.get_mut(&bucket).unwrap();
+            for edge in strategy.keys().copied().collect::<Vec<Edge>>() {
+                let play = (edge == worst) as usize as f32;
+                strategy.get_mut(&edge).unwrap().set_policy(play);
+            }
+        }
+        let bad = profile.exploitability_by_street(StrategyKind::Average, root);
+        let total = bad.values().sum::<Utility>();
+        assert!(
+            (total - profile.exploitability(StrategyKind::Average, root)).abs()
+                < 1e-3 * total.abs().max(1.)
+        );
+        assert!(bad[&Street::Turn] > good[&Street::Turn]);
+        assert!(bad[&Street::Turn] > bad[&Street::Rive]);
+        assert!(bad[&Street::Turn] > total / 2.);
+    }
+
+    #[test]
+    fn probe_is_reproducible() {
+        use crate::cards::isomorphisms::IsomorphismIterator;
+        use crate::clustering::abstraction::Abstraction;
+        use crate::gameplay::game::Game;
+        let ref encoder = Encoder::from(
+            IsomorphismIterator::from(Street::Pref)
+                .enumerate()
+                .map(|(i, iso)| (iso, Abstraction::from((Street::Pref, i))))
+                .collect::<BTreeMap<_, _>>(),
+        );
+        let points = ["As Kd", "7c 2h", "Qs Qh"]
+            .into_iter()
+            .map(|hole| ProbePoint {
+                hero: Game::root().turn(),
+                seen: Observation::try_from(hole).unwrap(),
+                history: vec![],
+            })
+            .collect::<Vec<ProbePoint>>();
+        // stand in for training by pinning the first spot, so that
+        // it leans toward its first Edge by a given weight
+        let train = |profile: &mut Profile, lean: f32| {
+            let bucket = encoder.bucket(&Recall::from(&points[0]));
+            let edges = Vec::<Edge>::from(bucket.2);
+            let rest = (1. - lean) / (edges.len() - 1) as f32;
+            let policy = edges
+                .iter()
+                .enumerate()
+                .map(|(i, e)| (*e, Probability::from(if i == 0 { lean } else { rest })))
+                .collect::<BTreeMap<_, _>>();
+            profile.pin(bucket, policy);
+        };
+        let mut profile = Profile::default();
+        train(&mut profile, 0.5);
+        let once = profile.probe(encoder, &points);
+        let twice = profile.probe(encoder, &points);
+        assert!(once == twice);
+        assert!(once[0].visited);
+        assert!(once[1..].iter().all(|p| !p.visited));
+        assert!(once[1..].iter().all(|p| p
+            .policy
+            .values()
+            .all(|w| *w == Probability::uniform(p.policy.len()))));
+        // golden file round trip
+        let ref path = std::env::temp_dir()
+            .join("robopoker-probes")
+            .to_string_lossy()
+            .to_string();
+        Probe::save(path, &once);
+        let golden = Probe::load(path);
+        assert!(golden == once);
+        assert!(Probe::diff(&golden, &twice, Probability::from(Probability::TOLERANCE)).is_empty());
+        // retraining the probed spot shows up in the diff
+        train(&mut profile, 0.9);
+        let after = profile.probe(encoder, &points);
+        let drift = Probe::diff(&golden, &after, Probability::from(Probability::TOLERANCE));
+        assert!(drift.len() == 1);
+        assert!(drift[0].0.point == points[0]);
+        std::fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn repl_survives_malformed_input() {
+        use crate::cards::isomorphism::Isomorphism;
+        let ref obs = Observation::try_from("AsKs~QsJsTs").unwrap();
+        let profile = Profile::random();
+        let bucket = profile.strategies.keys().next().cloned().unwrap();
+        let encoder = Encoder::from(BTreeMap::from([(Isomorphism::from(*obs), bucket.1)]));
+        let mut output = Vec::new();
+        profile.repl(&encoder, "garbage\nAsKs/QsJsTs\n".as_bytes(), &mut output);
+        let output = String::from_utf8(output).unwrap();
+        let mut lines = output.lines();
+        assert!(lines.next().unwrap().starts_with("error"));
+        assert!(lines.next().unwrap() == bucket.to_string());
+        assert!(lines.all(|line| line.starts_with(" ├─") || line.contains(">>")));
+    }
+
+    #[test]
+    fn master_seed_is_uniform_and_reproducible() {
+        use crate::clustering::abstraction::Abstraction;
+        use crate::gameplay::game::Game;
+        use crate::mccfr::data::Data;
+        const BINS: usize = 16;
+        const EPOCHS: usize = 4096;
+        let mut tree = Tree::empty(Player::default());
+        let data = Data::from((Game::root(), Abstraction::from((Street::Pref, 0))));
+        let ref node = tree.plant(data).clone();
+        let mut profile = Profile::default().with_master_seed(0xC0FFEE);
+        let mut counts = [0usize; BINS];
+        for t in 0..EPOCHS {
+            profile.iterations = t;
+            let x = profile.rng(node).gen_range(0..BINS);
+            let y = profile.rng(node).gen_range(0..BINS);
+            assert!(x == y);
+            counts[x] += 1;
+        }
+        let expected = (EPOCHS / BINS) as f32;
+        let chisquare = counts
+            .iter()
+            .map(|&n| (n as f32 - expected).powi(2) / expected)
+            .sum::<f32>();
+        assert!(chisquare < 37.7); // df = 15, p = 0.001
+    }
+
+    #[test]
+    fn concurrent_accumulation_commits() {
+        let mut profile = Profile::random();
+        let mut sequential = Profile {
+            strategies: profile.strategies.clone(),
+            ..Profile::default()
+        };
+        let accumulators = profile.accumulators(sequential.strategies.keys());
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| {
+                    for memories in accumulators.values() {
+                        for atomic in memories.values() {
+                            atomic.add_regret(1.);
+                        }
+                    }
+                });
+            }
+        });
+        profile.commit(&accumulators);
+        for strategy in sequential.strategies.values_mut() {
+            for edge in strategy.keys().cloned().collect::<Vec<_>>() {
+                for _ in 0..4 {
+                    strategy.get_mut(&edge).unwrap().add_regret(1., 1.);
+                }
+            }
+        }
+        assert!(profile.strategies == sequential.strategies);
+    }
+
+    #[test]
+    fn concurrent_epoch_decays_once() {
+        let mut profile = Profile::random();
+        let before = profile.strategies.clone();
+        let accumulators = profile.accumulators(before.keys());
+        profile.decay(&accumulators);
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| {
+                    for (bucket, strategy) in before.iter() {
+                        let regret = strategy
+                            .keys()
+                            .map(|e| (*e, 1.))
+                            .collect::<BTreeMap<_, _>>();
+                        let policy = strategy
+                            .keys()
+                            .map(|e| (*e, Probability::ONE))
+                            .collect::<BTreeMap<_, _>>();
+                        let ref regret = Regret::from(regret);
+                        let ref policy = Policy::from(policy);
+                        profile.accumulate(&accumulators, bucket, regret, policy);
+                    }
+                });
+            }
+        });
+        profile.commit(&accumulators);
+        let t = profile.epochs();
+        let discount = Discount::default();
+        for (bucket, strategy) in before.iter() {
+            for (edge, memory) in strategy.iter() {
+                let regret = memory.regret() * discount.regret(t, memory.regret()) + 4.;
+                let policy = memory.policy() * discount.policy(t) + 4.;
+                let after = profile.strategies[bucket].get(edge).unwrap();
+                assert!((after.regret() - regret).abs() <= 1e-3 * regret.abs().max(1.));
+                assert!((after.policy() - policy).abs() <= 1e-3 * policy.abs().max(1.));
+            }
+        }
+    }
+
+    #[test]
+    fn audit_detects_corruption() {
+        let mut profile = Profile::random();
+        for strategy in profile.strategies.values_mut() {
+            for edge in strategy.keys().cloned().collect::<Vec<_>>() {
+                strategy.get_mut(&edge).unwrap().set_policy(1.);
+            }
+        }
+        assert!(profile.audit().is_empty());
+        let ref bucket = profile.strategies.keys().next().cloned().unwrap();
+        let strategy = profile.strategies.get_mut(bucket).unwrap();
+        let edge = strategy.keys().next().cloned().unwrap();
+        strategy.get_mut(&edge).unwrap().set_regret(f32::NAN);
+        let audit = profile.audit();
+        assert!(audit.len() == 1);
+        assert!(audit.nonfinite == vec![*bucket]);
+    }
+
+    #[test]
+    fn history_records_tracked_buckets() {
+        const EPOCHS: usize = 20;
+        const INTERVAL: usize = 4;
+        let mut profile = Profile::random().with_history_interval(INTERVAL);
+        let seen = profile.strategies.keys().next().cloned().unwrap();
+        let unseen = std::iter::repeat_with(Bucket::random)
+            .find(|b| !profile.strategies.contains_key(b))
+            .unwrap();
+        profile.track_buckets(vec![seen, unseen]);
+        for epoch in 1..=EPOCHS {
+            let strategy = profile.strategies.get_mut(&seen).unwrap();
+            let edge = strategy.keys().next().cloned().unwrap();
+            strategy
+                .get_mut(&edge)
+                .unwrap()
+                .set_regret(epoch as Utility);
+            profile.next();
+        }
+        let history = profile.history();
+        assert!(history.tracked().count() == 2);
+        for bucket in [seen, unseen] {
+            let series = history.series(&bucket);
+            assert!(series.len() == EPOCHS / INTERVAL);
+            assert!(series
+                .iter()
+                .map(|s| s.epoch)
+                .eq((1..=EPOCHS / INTERVAL).map(|i| i * INTERVAL)));
+        }
+        assert!(history
+            .series(&seen)
+            .iter()
+            .all(|s| s.strategy.iter().next().unwrap().1.regret() == s.epoch as Utility));
+        assert!(history
+            .series(&unseen)
+            .iter()
+            .all(|s| s.strategy.iter().count() == 0));
+    }
+}
+
+#[cfg(feature = "native")]
+impl Profile {
+    /// parse the strategies of a PGCOPY blueprint file
+    pub(crate) fn read(path: &str) -> Result<Self, crate::save::upload::LoadError> {
+        use super::shard::Shard;
+        let mut shard = Shard::open(path)?;
+        let mut strategies = BTreeMap::new();
+        while let Some((bucket, edge, memory)) = shard.record()? {
+            strategies
+                .entry(bucket)
+                .or_insert_with(Strategy::default)
+                .entry(edge)
+                .or_insert(memory);
+        }
+        Ok(Self {
+            strategies,
+            ..Self::default()
+        })
+    }
+    /// read a blueprint for serving. rather than panicking mid-parse,
+    /// reject files whose framing is off (bad header, unsupported version,
+    /// missing trailer, partial rows), then reject any that fail audit
+    pub fn validated(path: &str) -> Result<Self, String> {
+        let profile = Self::read(path).map_err(|e| format!("{}: {}", path, e))?;
+        let audit = profile.audit();
+        match audit.is_empty() {
+            true => Ok(profile),
+            false => Err(format!("{} failed audit\n{}", path, audit)),
+        }
+    }
+    /// every witnessed Bucket, in order
+    pub(crate) fn buckets(&self) -> impl Iterator<Item = &Bucket> {
+        self.strategies.keys()
+    }
+    /// copy of the strategies at these Buckets only
+    pub(crate) fn subset<'a>(&self, buckets: impl IntoIterator<Item = &'a Bucket>) -> Self {
+        Self {
+            strategies: buckets
+                .into_iter()
+                .filter_map(|bucket| self.strategies.get_key_value(bucket))
+                .map(|(bucket, strategy)| (bucket.clone(), strategy.clone()))
+                .collect(),
+            ..Self::default()
+        }
+    }
+    /// replace whole strategies with those of a partial Profile
+    pub(crate) fn overlay(&mut self, delta: Self) {
+        self.strategies.extend(delta.strategies);
+    }
+    /// read a blueprint to keep training from. accumulators on disk
+    /// are already discounted through the epoch they were written at,
+    /// so the epoch count and weighting schedules come back from the
+    /// Metadata sidecar too. the next update then decays and adds
+    /// exactly as it would have had training never stopped.
+    pub(crate) fn resume(path: &str) -> Result<Self, crate::save::upload::LoadError> {
+        let mut profile = Self::read(path)?;
+        match Self::check_metadata(path) {
+            Some(metadata) => {
+                profile.iterations = metadata.epochs;
+                profile.discount = metadata.discount;
+                profile.averaging = metadata.averaging;
+            }
+            None => log::warn!("resuming blueprint from epoch zero"),
+        }
+        Ok(profile)
+    }
+    /// warn if the blueprint at path has no Metadata sidecar,
+    /// or if it was trained against artifacts that have changed
+    pub(crate) fn check_metadata(path: &str) -> Option<crate::mccfr::metadata::Metadata> {
+        use crate::mccfr::metadata::Metadata;
+        match Metadata::load(&Metadata::path(path)) {
+            None => {
+                log::warn!("blueprint has no metadata record");
+                None
+            }
+            Some(metadata) => {
+                for artifact in metadata.missing() {
+                    log::warn!("blueprint trained against missing artifact {}", artifact);
+                }
+                Some(metadata)
+            }
+        }
+    }
+    /// write the Metadata sidecar of the blueprint at path,
+    /// against the abstraction artifacts in the same directory
+    pub(crate) fn write_metadata(&self, path: &str) {
+        use crate::mccfr::metadata::Metadata;
+        let dir = std::path::Path::new(path)
+            .parent()
+            .unwrap_or(std::path::Path::new("."));
+        Metadata {
+            averaging: self.averaging,
+            ..Metadata::from((self.iterations, self.discount, dir))
+        }
+        .save(&Metadata::path(path));
+    }
+    /// write the strategies with every Bucket's policy accumulators
+    /// normalized, so that consumers read the average strategy as is.
+    /// normalizing discards how much weight past epochs carry, so
+    /// this is for export, and not for resuming training from
+    pub(crate) fn write_normalized(&self, path: &str) {
+        Self {
+            strategies: self
+                .strategies
+                .iter()
+                .map(|(bucket, strategy)| (bucket.clone(), strategy.normalized()))
+                .collect(),
+            ..Self::default()
+        }
+        .write(path)
+    }
+    /// save for export under a given directory,
+    /// with normalized policy accumulators
+    pub fn save_normalized_to(&self, dir: &std::path::Path) {
+        use crate::save::upload::Table;
+        let ref path = Self::path_in(dir, Street::random());
+        log::info!("{:<32}{:<32}", "saving      blueprint", path);
+        self.write_normalized(path);
+        self.write_metadata(path);
+    }
+    /// read from disk under a given directory, to keep training from
+    pub fn load_from(dir: &std::path::Path) -> Result<Self, crate::save::upload::LoadError> {
+        use crate::save::upload::Table;
+        let ref path = Self::path_in(dir, Street::random());
+        log::info!("{:<32}{:<32}", "loading     blueprint", path);
+        let profile = Self::resume(path)?;
+        let audit = profile.audit();
+        if !audit.is_empty() {
+            log::warn!("blueprint failed audit\n{}", audit);
+        }
+        Ok(profile)
+    }
+    /// write to disk under a given directory, so that
+    /// several Profiles can be trained side by side
+    pub fn save_to(&self, dir: &std::path::Path) {
+        use crate::save::upload::Table;
+        let ref path = Self::path_in(dir, Street::random());
+        log::info!("{:<32}{:<32}", "saving      blueprint", path);
+        self.write(path);
+        self.write_metadata(path);
+    }
+    /// write the strategies as a PGCOPY blueprint file
+    pub(crate) fn write(&self, path: &str) {
+        use super::shard::Shard;
+        Shard::write(
+            path,
+            self.strategies.iter().flat_map(|(bucket, strategy)| {
+                strategy
+                    .iter()
+                    .map(|(edge, memory)| (bucket.clone(), edge.clone(), memory.clone()))
+            }),
+        )
+    }
+}
+
+#[cfg(feature = "native")]
+impl crate::save::upload::Table for Profile {
+    fn name() -> String {
+        "blueprint".to_string()
+    }
+    fn columns() -> &'static [tokio_postgres::types::Type] {
+        &[
             tokio_postgres::types::Type::INT8,
             tokio_postgres::types::Type::INT8,
             tokio_postgres::types::Type::INT8,
@@ -511,15 +3172,8 @@ impl crate::save::upload::Table for Profile {
     fn sources() -> Vec<String> {
         vec![Self::path(Street::random())]
     }
-    fn path(_: Street) -> String {
-        format!(
-            "{}/pgcopy/{}",
-            std::env::current_dir()
-                .unwrap_or_default()
-                .to_string_lossy()
-                .into_owned(),
-            Self::name()
-        )
+    fn path_in(dir: &std::path::Path, _: Street) -> String {
+        format!("{}/{}", dir.to_string_lossy(), Self::name())
     }
     fn grow(_: Street) -> Self {
         unreachable!("must be learned in MCCFR minimization")
@@ -560,84 +3214,10 @@ impl crate::save::upload::Table for Profile {
         "
         .to_string()
     }
-    fn load(_: Street) -> Self {
-        let ref path = Self::path(Street::random());
-        log::info!("{:<32}{:<32}", "loading     blueprint", path);
-        use crate::clustering::abstraction::Abstraction;
-        use crate::mccfr::path::Path;
-        use byteorder::ReadBytesExt;
-        use byteorder::BE;
-        use std::fs::File;
-        use std::io::BufReader;
-        use std::io::Read;
-        use std::io::Seek;
-        use std::io::SeekFrom;
-        let file = File::open(path).expect("open file");
-        let mut strategies = BTreeMap::new();
-        let mut reader = BufReader::new(file);
-        let ref mut buffer = [0u8; 2];
-        reader.seek(SeekFrom::Start(19)).expect("seek past header");
-        while reader.read_exact(buffer).is_ok() {
-            match u16::from_be_bytes(buffer.clone()) {
-                6 => {
-                    reader.read_u32::<BE>().expect("past path length");
-                    let history = Path::from(reader.read_u64::<BE>().expect("history"));
-                    reader.read_u32::<BE>().expect("abstraction length");
-                    let present = Abstraction::from(reader.read_u64::<BE>().expect("abstraction"));
-                    reader.read_u32::<BE>().expect("future path length");
-                    let choices = Path::from(reader.read_u64::<BE>().expect("choices"));
-                    reader.read_u32::<BE>().expect("edge length");
-                    let edge = Edge::from(reader.read_u64::<BE>().expect("read edge"));
-                    reader.read_u32::<BE>().expect("regret length");
-                    let regret = reader.read_f32::<BE>().expect("read regret");
-                    reader.read_u32::<BE>().expect("policy length");
-                    let policy = reader.read_f32::<BE>().expect("read policy");
-                    let bucket = Bucket::from((history, present, choices));
-                    let memory = strategies
-                        .entry(bucket)
-                        .or_insert_with(Strategy::default)
-                        .entry(edge)
-                        .or_insert_with(Memory::default);
-                    memory.set_regret(regret);
-                    memory.set_policy(policy);
-                    continue;
-                }
-                0xFFFF => break,
-                n => panic!("unexpected number of fields: {}", n),
-            }
-        }
-        Self {
-            strategies,
-            iterations: 0,
-        }
+    fn load(_: Street) -> Result<Self, crate::save::upload::LoadError> {
+        Self::load_from(&crate::save::upload::directory())
     }
     fn save(&self) {
-        const N_FIELDS: u16 = 6;
-        let ref path = Self::path(Street::random());
-        let ref mut file = File::create(path).expect(&format!("touch {}", path));
-        use byteorder::WriteBytesExt;
-        use byteorder::BE;
-        use std::fs::File;
-        use std::io::Write;
-        log::info!("{:<32}{:<32}", "saving      blueprint", path);
-        file.write_all(Self::header()).expect("header");
-        for (bucket, strategy) in self.strategies.iter() {
-            for (edge, memory) in strategy.iter() {
-                file.write_u16::<BE>(N_FIELDS).unwrap();
-                file.write_u32::<BE>(size_of::<u64>() as u32).unwrap();
-                file.write_u64::<BE>(u64::from(bucket.0)).unwrap();
-                file.write_u32::<BE>(size_of::<u64>() as u32).unwrap();
-                file.write_u64::<BE>(u64::from(bucket.1)).unwrap();
-                file.write_u32::<BE>(size_of::<u64>() as u32).unwrap();
-                file.write_u64::<BE>(u64::from(bucket.2)).unwrap();
-                file.write_u32::<BE>(size_of::<u64>() as u32).unwrap();
-                file.write_u64::<BE>(u64::from(edge.clone())).unwrap();
-                file.write_u32::<BE>(size_of::<f32>() as u32).unwrap();
-                file.write_f32::<BE>(memory.regret()).unwrap();
-                file.write_u32::<BE>(size_of::<f32>() as u32).unwrap();
-                file.write_f32::<BE>(memory.policy()).unwrap();
-            }
-        }
-        file.write_u16::<BE>(Self::footer()).expect("trailer");
+        self.save_to(&crate::save::upload::directory())
     }
 }
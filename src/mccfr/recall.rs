@@ -2,6 +2,7 @@ use crate::cards::card::Card;
 use crate::cards::hole::Hole;
 use crate::cards::observation::Observation;
 use crate::gameplay::action::Action;
+use crate::gameplay::blinds::Blinds;
 use crate::gameplay::game::Game;
 use crate::gameplay::ply::Turn;
 
@@ -13,33 +14,53 @@ use crate::gameplay::ply::Turn;
 ///
 /// note that this struct implicitly assumes:
 /// - default stacks
-/// - default blinds
 /// - default dealer position
 /// - unordered private and community cards
+///
+/// blinds and antes are default unless set with_blinds.
 #[derive(Debug, Clone)]
 pub struct Recall {
     hero: Turn,
     seen: Observation, // could be replaced by Hole + Board + BetHistory(Vec<Action>)
     path: Vec<Action>,
+    blinds: Blinds,
 }
 
 impl From<(Turn, Observation, Vec<Action>)> for Recall {
     fn from((hero, seen, path): (Turn, Observation, Vec<Action>)) -> Self {
-        Self { hero, seen, path }
+        Self {
+            hero,
+            seen,
+            path,
+            blinds: Blinds::default(),
+        }
     }
 }
 
 impl Recall {
     pub fn new(seen: Observation, hero: Turn) -> Self {
+        let blinds = Blinds::default();
         Self {
             seen,
             hero,
-            path: Game::blinds(),
+            path: Game::from(blinds).blinds(),
+            blinds,
         }
     }
+    /// replay the history under these blinds and antes
+    pub fn with_blinds(mut self, blinds: Blinds) -> Self {
+        let n = self.path.iter().take_while(|a| a.is_blind()).count();
+        self.path
+            .splice(..n, Game::from(blinds).blinds().into_iter().take(n));
+        self.blinds = blinds;
+        self
+    }
 
     pub fn root(&self) -> Game {
-        Game::root().wipe(Hole::from(self.seen))
+        Game::from(self.blinds)
+            .deal()
+            .post()
+            .wipe(Hole::from(self.seen))
     }
 
     pub fn head(&self) -> Game {
@@ -99,12 +120,33 @@ impl Recall {
     /// we depend on the pot size as of the Game state where
     /// the Action is applied, and always compare the size of the
     /// Action::Raise(_) to the pot to yield an [Odds] value.
+    ///
+    /// off-grid raises are translated pseudo-harmonically, see
+    /// [Game::translate]. the draw is seeded by the history itself
+    /// so that repeated lookups within a hand agree with each other.
     fn pseudoharmonics(&self) -> Path {
-        todo!(
-            "use pseudo-harmonic
-mapping to
-                    convert history:
-        Recall -> Vec<(Game, Action)> -> Vec<Edge> -> Path"
+        use rand::rngs::SmallRng;
+        use rand::SeedableRng;
+        use std::hash::Hash;
+        use std::hash::Hasher;
+        let ref mut hasher = std::collections::hash_map::DefaultHasher::new();
+        self.seen.hash(hasher);
+        self.path.hash(hasher);
+        let ref mut rng = SmallRng::seed_from_u64(hasher.finish());
+        let mut game = self.root();
+        let mut aggro = 0;
+        let mut edges = Vec::new();
+        for action in self.path.iter().filter(|a| !a.is_blind()) {
+            let ref choices = game.choices(aggro);
+            edges.push(game.translate(action, choices, rng));
+            aggro += action.is_aggro() as usize;
+            game = game.apply(*action);
+        }
+        Path::from(
+            edges
+                .into_iter()
+                .take(crate::MAX_DEPTH_SUBGAME)
+                .collect::<Vec<Edge>>(),
         )
     }
 
@@ -124,4 +166,5 @@ mapping to
 
 use crate::clustering::abstraction::Abstraction;
 use crate::mccfr::bucket::Bucket;
+use crate::mccfr::edge::Edge;
 use crate::mccfr::path::Path;
@@ -0,0 +1,176 @@
+use crate::Probability;
+
+/// how the walker's own decisions are sampled into a Tree.
+/// opponents and chance are sampled one Edge at a time,
+/// except under Vanilla, which samples nothing at all.
+///
+/// External walks every walker Edge, so the only importance weight
+/// is the opponents' reach. Outcome walks a single trajectory, with
+/// walker Edges drawn from the strategy mixed with some uniform
+/// exploration, and leaf values are also divided by the probability
+/// of having walked to them. each Tree is much cheaper, but the
+/// regret estimates are noisier.
+///
+/// Vanilla walks every Edge of every Node, so leaf values are weighted
+/// by their exact reach rather than corrected for being sampled. the
+/// updates have no variance, but a Tree holds the whole game, so this
+/// is only for games small enough to enumerate.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Sampling {
+    #[default]
+    External,
+    Outcome,
+    Vanilla,
+}
+
+impl Sampling {
+    /// probability that each walker Edge is walked,
+    /// given the strategy's distribution over them
+    pub fn walk(&self, policy: &[Probability]) -> Vec<Probability> {
+        match self {
+            Self::External | Self::Vanilla => vec![Probability::ONE; policy.len()],
+            Self::Outcome => {
+                let explore = crate::CFR_OUTCOME_EXPLORATION;
+                let uniform = Probability::uniform(policy.len());
+                policy
+                    .iter()
+                    .map(|p| Probability::from(explore * uniform + (1. - explore) * *p))
+                    .collect()
+            }
+        }
+    }
+    /// Trees to sample per epoch. a Vanilla Tree already
+    /// holds every line of play, so more would only repeat it
+    pub fn batch(&self) -> usize {
+        match self {
+            Self::External | Self::Outcome => crate::CFR_BATCH_SIZE,
+            Self::Vanilla => 1,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mccfr::memory::Memory;
+    use crate::Utility;
+    use rand::distributions::Distribution;
+    use rand::distributions::WeightedIndex;
+    use rand::rngs::SmallRng;
+    use rand::SeedableRng;
+
+    /// Rock Paper Scissors, as the row player's payoff
+    const RPS: [[Utility; 3]; 3] = [[0., -1., 1.], [1., 0., -1.], [-1., 1., 0.]];
+
+    /// self-play of a symmetric zero-sum game of Rock Paper Scissors
+    /// with this payoff matrix, sampling the opponent's action every
+    /// iteration and the hero's as the scheme says, or taking the
+    /// exact expectation over both under Vanilla.
+    /// returns both players' average strategies
+    fn rps(
+        payoff: [[Utility; 3]; 3],
+        sampling: Sampling,
+        seed: u64,
+        iterations: usize,
+    ) -> [[f32; 3]; 2] {
+        let ref mut rng = SmallRng::seed_from_u64(seed);
+        let matching = |memories: &[Memory; 3]| {
+            let positive = memories.clone().map(|m| m.regret().max(0.));
+            let total = positive.iter().sum::<Utility>();
+            match total > 0. {
+                true => positive.map(|r| Probability::from(r / total)),
+                false => [Probability::uniform(3); 3],
+            }
+        };
+        let mut players = [
+            [Memory::from((1., 0.)), Memory::default(), Memory::default()],
+            [Memory::default(), Memory::default(), Memory::default()],
+        ];
+        for _ in 0..iterations {
+            let strategies = [matching(&players[0]), matching(&players[1])];
+            for (hero, memories) in players.iter_mut().enumerate() {
+                let policy = strategies[hero];
+                let villain = WeightedIndex::new(strategies[1 - hero].map(f32::from))
+                    .unwrap()
+                    .sample(rng);
+                let walk = sampling.walk(&policy);
+                let walked = match sampling {
+                    Sampling::External | Sampling::Vanilla => [true; 3],
+                    Sampling::Outcome => {
+                        let a = WeightedIndex::new(walk.iter().map(|q| f32::from(*q)))
+                            .unwrap()
+                            .sample(rng);
+                        [a == 0, a == 1, a == 2]
+                    }
+                };
+                let values = [0, 1, 2].map(|a| match (sampling, walked[a]) {
+                    (Sampling::Vanilla, _) => (0..3)
+                        .map(|b| strategies[1 - hero][b] * payoff[a][b])
+                        .sum::<Utility>(),
+                    (_, true) => payoff[a][villain] / walk[a],
+                    (_, false) => 0.,
+                });
+                let expected = (0..3).map(|a| policy[a] * values[a]).sum::<Utility>();
+                for (a, memory) in memories.iter_mut().enumerate() {
+                    memory.add_regret(1., values[a] - expected);
+                    memory.add_policy(1., f32::from(policy[a]));
+                }
+            }
+        }
+        players.map(|memories| {
+            let total = memories.iter().map(Memory::policy).sum::<f32>();
+            memories.map(|m| m.policy() / total)
+        })
+    }
+
+    #[test]
+    fn outcome_and_external_agree_on_rps() {
+        let external = rps(RPS, Sampling::External, 0, 0x10000);
+        let outcome = rps(RPS, Sampling::Outcome, 0, 0x10000);
+        for (x, y) in external.iter().flatten().zip(outcome.iter().flatten()) {
+            assert!((x - 1. / 3.).abs() < 0.02, "{:?}", external);
+            assert!((y - 1. / 3.).abs() < 0.02, "{:?}", outcome);
+        }
+    }
+
+    /// as close as sampling gets in a sixteenth of the iterations,
+    /// and the same however the opponent would have been sampled
+    #[test]
+    fn vanilla_converges_on_rps_without_variance() {
+        let vanilla = rps(RPS, Sampling::Vanilla, 0, 0x1000);
+        assert!(vanilla == rps(RPS, Sampling::Vanilla, 1, 0x1000));
+        for x in vanilla.iter().flatten() {
+            assert!((x - 1. / 3.).abs() < 0.02, "{:?}", vanilla);
+        }
+    }
+
+    /// when Scissors wins it wins double. Paper must then be played
+    /// as often as Scissors for Rock to break even, and Rock twice as
+    /// often as Scissors for Paper to, so the Nash is (1/2, 1/4, 1/4)
+    #[test]
+    fn biased_rps_converges_to_its_nash() {
+        const BIASED: [[Utility; 3]; 3] = [[0., -1., 1.], [1., 0., -2.], [-1., 2., 0.]];
+        const NASH: [f32; 3] = [0.5, 0.25, 0.25];
+        for sampling in [Sampling::Vanilla, Sampling::External] {
+            let iterations = match sampling {
+                Sampling::Vanilla => 0x1000,
+                _ => 0x10000,
+            };
+            let average = rps(BIASED, sampling, 0, iterations);
+            for strategy in average.iter() {
+                for (x, nash) in strategy.iter().zip(NASH) {
+                    assert!((x - nash).abs() < 0.02, "{:?} {:?}", sampling, average);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn outcome_explores_every_edge() {
+        let policy = [Probability::ONE, Probability::ZERO];
+        let walk = Sampling::Outcome.walk(&policy);
+        assert!(walk.iter().all(|q| *q > 0.));
+        assert!((f32::from(walk.iter().copied().sum::<Probability>()) - 1.).abs() < 1e-6);
+        assert!(Sampling::External.walk(&policy) == vec![Probability::ONE; 2]);
+    }
+}
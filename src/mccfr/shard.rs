@@ -0,0 +1,198 @@
+use super::bucket::Bucket;
+use super::edge::Edge;
+use super::memory::Memory;
+use super::path::Path;
+use super::profile::Profile;
+use crate::clustering::abstraction::Abstraction;
+use crate::save::upload::Checked;
+use crate::save::upload::LoadError;
+use crate::save::upload::Table;
+use byteorder::ReadBytesExt;
+use byteorder::WriteBytesExt;
+use byteorder::BE;
+use std::io::Read;
+use std::io::Write;
+use std::iter::Peekable;
+
+/// one row of a blueprint PGCOPY file
+pub type Record = (Bucket, Edge, Memory);
+
+/// sequential reader over a blueprint PGCOPY file.
+/// Profile::save emits rows in BTreeMap order,
+/// so a Shard yields Records sorted by (Bucket, Edge).
+pub struct Shard(Checked<Box<dyn Read + Send>>, String);
+
+impl From<&str> for Shard {
+    fn from(path: &str) -> Self {
+        Self::open(path).unwrap_or_else(|e| panic!("{}: {}", path, e))
+    }
+}
+
+impl Iterator for Shard {
+    type Item = Record;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.record()
+            .unwrap_or_else(|e| panic!("{}: {}", self.1, e))
+    }
+}
+
+impl Shard {
+    /// open a blueprint file and consume its header
+    pub fn open(path: &str) -> Result<Self, LoadError> {
+        let mut reader = crate::save::upload::open(path)?;
+        crate::save::upload::header(&mut reader)?;
+        Ok(Self(reader, path.to_string()))
+    }
+    /// parse the next Record, or None at the trailer
+    pub fn record(&mut self) -> Result<Option<Record>, LoadError> {
+        use crate::save::upload::row;
+        use crate::save::upload::width;
+        let reader = &mut self.0;
+        match row(reader, 6)? {
+            false => Ok(None),
+            true => {
+                width(reader, 8)?;
+                let history = Path::from(reader.read_u64::<BE>()?);
+                width(reader, 8)?;
+                let present = Abstraction::from(reader.read_u64::<BE>()?);
+                width(reader, 8)?;
+                let choices = Path::from(reader.read_u64::<BE>()?);
+                width(reader, 8)?;
+                let edge = Edge::from(reader.read_u64::<BE>()?);
+                width(reader, 4)?;
+                let regret = reader.read_f32::<BE>()?;
+                width(reader, 4)?;
+                let policy = reader.read_f32::<BE>()?;
+                let bucket = Bucket::from((history, present, choices));
+                Ok(Some((bucket, edge, Memory::from((regret, policy)))))
+            }
+        }
+    }
+    /// write sorted Records in the same layout as Profile::save
+    pub fn write(path: &str, records: impl Iterator<Item = Record>) {
+        const N_FIELDS: u16 = 6;
+        let ref mut file = crate::save::upload::writer(path).expect(&format!("touch {}", path));
+        file.write_all(Profile::header()).expect("header");
+        for (bucket, edge, memory) in records {
+            file.write_u16::<BE>(N_FIELDS).unwrap();
+            file.write_u32::<BE>(size_of::<u64>() as u32).unwrap();
+            file.write_u64::<BE>(u64::from(bucket.0)).unwrap();
+            file.write_u32::<BE>(size_of::<u64>() as u32).unwrap();
+            file.write_u64::<BE>(u64::from(bucket.1)).unwrap();
+            file.write_u32::<BE>(size_of::<u64>() as u32).unwrap();
+            file.write_u64::<BE>(u64::from(bucket.2)).unwrap();
+            file.write_u32::<BE>(size_of::<u64>() as u32).unwrap();
+            file.write_u64::<BE>(u64::from(edge)).unwrap();
+            file.write_u32::<BE>(size_of::<f32>() as u32).unwrap();
+            file.write_f32::<BE>(memory.regret()).unwrap();
+            file.write_u32::<BE>(size_of::<f32>() as u32).unwrap();
+            file.write_f32::<BE>(memory.policy()).unwrap();
+        }
+        crate::save::upload::trailer(file).expect("trailer");
+    }
+
+    /// k-way merge of sorted blueprint shards into a single file.
+    /// only the head Record of each shard is held in memory, so
+    /// the combined size of the shards is not bounded by RAM.
+    /// regrets and policy accumulators are summed on matching keys.
+    pub fn merge(shards: &[&str], output: &str) {
+        log::info!("{:<32}{:<32}", "merging     blueprint", output);
+        let shards = shards
+            .iter()
+            .map(|path| Shard::from(*path).peekable())
+            .collect::<Vec<Peekable<Shard>>>();
+        Self::write(output, Merge(shards));
+    }
+}
+
+/// sorted stream of Records combined across shards
+struct Merge(Vec<Peekable<Shard>>);
+
+impl Iterator for Merge {
+    type Item = Record;
+    fn next(&mut self) -> Option<Self::Item> {
+        let key = self
+            .0
+            .iter_mut()
+            .filter_map(|shard| shard.peek())
+            .map(|(bucket, edge, _)| (*bucket, *edge))
+            .min()?;
+        let mut memory = Memory::default();
+        for shard in self.0.iter_mut() {
+            while let Some((_, _, m)) = shard.next_if(|(b, e, _)| (*b, *e) == key) {
+                memory.set_regret(memory.regret() + m.regret());
+                memory.set_policy(memory.policy() + m.policy());
+            }
+        }
+        Some((key.0, key.1, memory))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Arbitrary;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn merge_sorted_shards() {
+        let dir = std::env::temp_dir().join("robopoker-shards");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = |name: &str| dir.join(name).to_string_lossy().to_string();
+        let (ref a, ref b, ref c) = (path("a"), path("b"), path("c"));
+        let keys = (0..16)
+            .map(|_| (Bucket::random(), Edge::random()))
+            .collect::<Vec<(Bucket, Edge)>>();
+        let x = keys[..12]
+            .iter()
+            .map(|k| (*k, Memory::from((1., 2.))))
+            .collect::<BTreeMap<_, _>>();
+        let y = keys[4..]
+            .iter()
+            .map(|k| (*k, Memory::from((10., 20.))))
+            .collect::<BTreeMap<_, _>>();
+        Shard::write(a, x.iter().map(|((b, e), m)| (*b, *e, m.clone())));
+        Shard::write(b, y.iter().map(|((b, e), m)| (*b, *e, m.clone())));
+        Shard::merge(&[a.as_str(), b.as_str()], c);
+        let merged = Shard::from(c.as_str()).collect::<Vec<Record>>();
+        let mut expected = BTreeMap::<(Bucket, Edge), Memory>::new();
+        for (key, m) in x.iter().chain(y.iter()) {
+            let sum = expected.entry(*key).or_default();
+            sum.set_regret(sum.regret() + m.regret());
+            sum.set_policy(sum.policy() + m.policy());
+        }
+        let expected = expected
+            .into_iter()
+            .map(|((b, e), m)| (b, e, m))
+            .collect::<Vec<Record>>();
+        assert!(merged == expected);
+        std::fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn versioned_headers() {
+        let dir = std::env::temp_dir().join("robopoker-versions");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = |name: &str| dir.join(name).to_string_lossy().to_string();
+        let (ref current, ref legacy, ref future) = (path("v1"), path("v0"), path("v2"));
+        let records = (0..8)
+            .map(|_| ((Bucket::random(), Edge::random()), Memory::from((1., 1.))))
+            .collect::<BTreeMap<_, _>>()
+            .into_iter()
+            .map(|((b, e), m)| (b, e, m))
+            .collect::<Vec<Record>>();
+        Shard::write(current, records.clone().into_iter());
+        let bytes = std::fs::read(current).unwrap();
+        let rows = &bytes[Profile::header().len()..];
+        let v0 = b"PGCOPY\n\xFF\r\n\0\0\0\0\0\0\0\0\0";
+        let v2 = b"PGCOPY\n\xFF\r\n\0\0\0\0\0\0\0\0\x04\0\0\0\x02";
+        std::fs::write(legacy, [&v0[..], rows].concat()).unwrap();
+        std::fs::write(future, [&v2[..], rows].concat()).unwrap();
+        assert!(Shard::from(current.as_str()).collect::<Vec<Record>>() == records);
+        assert!(Shard::from(legacy.as_str()).collect::<Vec<Record>>() == records);
+        assert!(Profile::validated(legacy).is_ok());
+        let err = Profile::validated(future).err().unwrap();
+        assert!(err.contains("unsupported version 2"), "{}", err);
+        std::fs::remove_dir_all(dir).ok();
+    }
+}
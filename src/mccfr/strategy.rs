@@ -3,24 +3,101 @@ use super::memory::Memory;
 use super::policy::Policy;
 use crate::Arbitrary;
 use crate::Probability;
+use crate::Utility;
 use std::collections::BTreeMap;
 
+/// which of the two strategies held in a Strategy to read.
+/// Current is the last iterate, regret-matched from cumulative regrets.
+/// Average is the accumulated policy, which is what converges to equilibrium.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum StrategyKind {
+    Current,
+    #[default]
+    Average,
+}
+
 #[derive(Debug, Default, Clone, PartialEq)]
 pub struct Strategy(BTreeMap<Edge, Memory>);
 
 impl Strategy {
+    /// averaged distribution over every Edge
     pub fn policy(&self) -> Policy {
-        Policy::from(
+        Policy::from(self.average())
+    }
+    pub fn weight(&self, edge: &Edge) -> Probability {
+        let denom = self.0.values().map(|s| s.policy()).sum::<f32>();
+        let numer = self.0.get(edge).expect("edge in infoset").policy();
+        Probability::from(numer / denom)
+    }
+    /// accumulated distribution over every Edge
+    pub fn average(&self) -> BTreeMap<Edge, Probability> {
+        self.0
+            .keys()
+            .map(|edge| (*edge, self.weight(edge)))
+            .collect()
+    }
+    /// regret matching over cumulative regrets per epoch, floored and
+    /// normalized exactly as the policy vector that training accumulates
+    pub fn current(&self, epochs: usize) -> BTreeMap<Edge, Probability> {
+        let regrets = self
+            .0
+            .iter()
+            .map(|(edge, memory)| (*edge, memory.regret() / epochs.max(1) as Utility))
+            .map(|(edge, r)| (edge, r.max(f32::from(crate::POLICY_MIN))))
+            .collect::<BTreeMap<Edge, Utility>>();
+        Policy::normalize(regrets, crate::POLICY_MIN)
+    }
+    /// distribution over every Edge under either strategy
+    pub fn distribution(&self, kind: StrategyKind, epochs: usize) -> BTreeMap<Edge, Probability> {
+        match kind {
+            StrategyKind::Current => self.current(epochs),
+            StrategyKind::Average => self.average(),
+        }
+    }
+    /// the same regrets, with policy accumulators rescaled to sum
+    /// to one. a Strategy that never accumulated becomes uniform
+    pub fn normalized(&self) -> Self {
+        let weights = self
+            .0
+            .iter()
+            .map(|(edge, memory)| (*edge, memory.policy()))
+            .collect::<BTreeMap<Edge, f32>>();
+        let average = Policy::normalize(weights, Probability::ZERO);
+        Self(
             self.0
                 .iter()
-                .map(|(edge, memory)| (edge.clone(), memory.policy()))
-                .collect::<BTreeMap<Edge, Probability>>(),
+                .map(|(edge, memory)| (*edge, (memory.regret(), f32::from(average[edge]))))
+                .map(|(edge, memory)| (edge, Memory::from(memory)))
+                .collect(),
         )
     }
-    pub fn weight(&self, edge: &Edge) -> Probability {
-        let denom = self.0.values().map(|s| s.policy()).sum::<Probability>();
-        let numer = self.0.get(edge).expect("edge in infoset").policy();
-        numer / denom
+    /// the same regrets, with policy accumulators set to the average
+    /// strategy less every Edge below threshold, renormalized. if no
+    /// Edge clears the threshold, the likeliest is played outright
+    pub fn purified(&self, threshold: Probability) -> Self {
+        let average = self.normalized().average();
+        let kept = average
+            .iter()
+            .filter(|(_, p)| f32::from(**p) >= f32::from(threshold))
+            .map(|(edge, p)| (*edge, f32::from(*p)))
+            .collect::<BTreeMap<Edge, f32>>();
+        let total = kept.values().sum::<f32>();
+        let best = average
+            .iter()
+            .max_by(|(_, a), (_, b)| a.partial_cmp(b).expect("normalized"))
+            .map(|(edge, _)| *edge);
+        Self(
+            self.0
+                .iter()
+                .map(|(edge, memory)| match (kept.get(edge), total > 0.) {
+                    (Some(p), true) => (*edge, (memory.regret(), p / total)),
+                    (None, true) => (*edge, (memory.regret(), 0.)),
+                    (_, false) if Some(*edge) == best => (*edge, (memory.regret(), 1.)),
+                    (_, false) => (*edge, (memory.regret(), 0.)),
+                })
+                .map(|(edge, memory)| (edge, Memory::from(memory)))
+                .collect(),
+        )
     }
     pub fn get(&self, edge: &Edge) -> Option<&Memory> {
         self.0.get(edge)
@@ -51,3 +128,88 @@ impl Arbitrary for Strategy {
         Self((0..n).map(|_| (Edge::random(), Memory::random())).collect())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn current_and_average_diverge() {
+        let (a, b) = (Edge::Fold, Edge::Call);
+        let mut strategy = Strategy::default();
+        strategy.entry(a).or_insert(Memory::from((3., 1.)));
+        strategy.entry(b).or_insert(Memory::from((-2., 3.)));
+        let current = strategy.distribution(StrategyKind::Current, 1);
+        let average = strategy.distribution(StrategyKind::Average, 1);
+        assert!(current[&a] > Probability::from(1. - 1e-6));
+        assert!(current[&b] == crate::POLICY_MIN);
+        assert!(average[&a] == Probability::from(0.25));
+        assert!(average[&b] == Probability::from(0.75));
+    }
+
+    #[test]
+    fn current_is_uniform_without_positive_regret() {
+        let (a, b) = (Edge::Fold, Edge::Call);
+        let mut strategy = Strategy::default();
+        strategy.entry(a).or_insert(Memory::from((-1., 1.)));
+        strategy.entry(b).or_insert(Memory::from((0., 1.)));
+        let current = strategy.distribution(StrategyKind::Current, 4);
+        assert!(current[&a] == Probability::from(0.5));
+        assert!(current[&b] == Probability::from(0.5));
+    }
+
+    /// self-play of Rock Paper Scissors, stopped mid-training.
+    /// the last iterate still cycles around the equilibrium,
+    /// while the average has already closed in on it.
+    #[test]
+    fn average_is_less_exploitable_on_rps() {
+        const PAYOFF: [[Utility; 3]; 3] = [[0., -1., 1.], [1., 0., -1.], [-1., 1., 0.]];
+        const EDGES: [Edge; 3] = [Edge::Fold, Edge::Check, Edge::Call];
+        const EPOCHS: usize = 1000;
+        let mixed = |strategy: &Strategy, kind: StrategyKind, t: usize| {
+            let distribution = strategy.distribution(kind, t);
+            EDGES.map(|e| f32::from(distribution[&e]))
+        };
+        let mut players = [Strategy::default(), Strategy::default()];
+        for strategy in players.iter_mut() {
+            for edge in EDGES {
+                strategy.entry(edge).or_insert(Memory::default());
+            }
+        }
+        players[0].get_mut(&EDGES[0]).unwrap().add_regret(1., 1.);
+        for t in 1..=EPOCHS {
+            let strategies = [
+                mixed(&players[0], StrategyKind::Current, t),
+                mixed(&players[1], StrategyKind::Current, t),
+            ];
+            for (hero, strategy) in players.iter_mut().enumerate() {
+                let villain = strategies[1 - hero];
+                let values = PAYOFF.map(|row| (0..3).map(|j| row[j] * villain[j]).sum::<Utility>());
+                let expected = (0..3)
+                    .map(|a| values[a] * strategies[hero][a])
+                    .sum::<Utility>();
+                for (a, edge) in EDGES.iter().enumerate() {
+                    let memory = strategy.get_mut(edge).unwrap();
+                    memory.add_regret(1., values[a] - expected);
+                    memory.add_policy(1., strategies[hero][a]);
+                }
+            }
+        }
+        let exploitability = |kind: StrategyKind| {
+            players
+                .iter()
+                .rev()
+                .map(|villain| mixed(villain, kind, EPOCHS))
+                .map(|villain| {
+                    PAYOFF
+                        .map(|row| (0..3).map(|j| row[j] * villain[j]).sum::<Utility>())
+                        .into_iter()
+                        .fold(Utility::MIN, Utility::max)
+                })
+                .sum::<Utility>()
+        };
+        let current = exploitability(StrategyKind::Current);
+        let average = exploitability(StrategyKind::Average);
+        assert!(average < current, "{} !< {}", average, current);
+    }
+}
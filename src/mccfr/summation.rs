@@ -0,0 +1,45 @@
+use crate::Utility;
+
+/// how utilities are added up over the Nodes of a Tree.
+/// terms are always taken in NodeIndex order, so either way the
+/// result is reproducible. Kahan also carries the low-order bits
+/// that plain f32 addition drops, at a few extra flops per term.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Summation {
+    #[default]
+    Naive,
+    Kahan,
+}
+
+impl Summation {
+    pub fn sum(&self, terms: impl IntoIterator<Item = Utility>) -> Utility {
+        match self {
+            Self::Naive => terms.into_iter().sum::<Utility>(),
+            Self::Kahan => {
+                let mut total = 0.;
+                let mut carry = 0.;
+                for x in terms {
+                    let y = x - carry;
+                    let t = total + y;
+                    carry = (t - total) - y;
+                    total = t;
+                }
+                total
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kahan_keeps_low_order_bits() {
+        let terms = std::iter::repeat(0.1).take(1_000_000);
+        let naive = Summation::Naive.sum(terms.clone());
+        let kahan = Summation::Kahan.sum(terms);
+        assert!((kahan - 100_000.).abs() < 1., "{}", kahan);
+        assert!((naive - 100_000.).abs() > 1., "{}", naive);
+    }
+}
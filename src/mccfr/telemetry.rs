@@ -0,0 +1,21 @@
+use crate::Utility;
+
+/// what one epoch of training left the Profile looking like,
+/// for plotting convergence from outside the crate
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EpochStats {
+    /// the epoch just completed, counting from one
+    pub epoch: usize,
+    /// Buckets witnessed so far
+    pub touched: usize,
+    /// mean absolute cumulative regret over every witnessed Edge
+    pub mean_regret: Utility,
+    /// largest absolute cumulative regret of any witnessed Edge
+    pub max_regret: Utility,
+    /// exploitability of the average strategy, on the epochs
+    /// that early stopping measures it, if it is enabled
+    pub exploitability: Option<Utility>,
+}
+
+/// thread-safe callback that receives EpochStats after every epoch
+pub type Telemetry = std::sync::Arc<dyn Fn(EpochStats) + Send + Sync>;
@@ -1,11 +1,17 @@
+use super::bucket::Bucket;
 use super::data::Data;
+use super::encoder::Encoder;
+use super::info::Info;
 use super::player::Player;
 use crate::mccfr::edge::Edge;
 use crate::mccfr::node::Node;
 use petgraph::graph::DiGraph;
 use petgraph::graph::NodeIndex;
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
 use std::fmt::Formatter;
 use std::fmt::Result;
+use std::sync::Arc;
 
 pub struct Branch(pub Data, pub Edge, pub NodeIndex);
 impl Branch {
@@ -38,6 +44,34 @@ impl Tree {
     pub fn graph(&self) -> &DiGraph<Data, Edge> {
         &self.0
     }
+    /// every decision Node, for either Player, grouped into the Info
+    /// of the Bucket it maps to. chance and terminal Nodes have no
+    /// infoset, so they are left out.
+    pub fn infosets_by_bucket(self: &Arc<Self>) -> BTreeMap<Bucket, Info> {
+        self.decisions()
+            .fold(BTreeMap::new(), |mut infosets, node| {
+                infosets
+                    .entry(node.bucket().clone())
+                    .or_insert_with(|| Info::from(self.clone()))
+                    .add(node.index());
+                infosets
+            })
+    }
+    /// number of distinct Buckets among decision Nodes
+    pub fn bucket_count(&self) -> usize {
+        self.decisions()
+            .map(|node| node.bucket().clone())
+            .collect::<BTreeSet<Bucket>>()
+            .len()
+    }
+    /// Nodes where some Player chooses among legal Edges
+    fn decisions(&self) -> impl Iterator<Item = Node<'_>> {
+        self.0
+            .node_indices()
+            .map(|index| self.at(index))
+            .filter(|node| node.player() != Player::chance())
+            .filter(|node| !node.edges().is_empty())
+    }
 
     /// special insertion logic for the root node
     /// which, without a parent Branch, has slightly different
@@ -69,6 +103,20 @@ impl Tree {
         self.at(leaf)
     }
 
+    /// lazily materialize the child of a Node along an Edge.
+    /// an already attached child is reused, and siblings are
+    /// never built, so walking a path only grows that path.
+    /// panics if the Edge is not legal at this Node.
+    pub fn visit(&mut self, index: NodeIndex, edge: &Edge, encoder: &Encoder) -> Node<'_> {
+        if let Some(child) = self.at(index).follow(edge).map(|c| c.index()) {
+            return self.at(child);
+        }
+        let branch = encoder
+            .branch(&self.at(index), edge)
+            .expect("edge is legal at node");
+        self.fork(branch)
+    }
+
     /// display the Tree in a human-readable format
     /// be careful because it's really big and recursive
     fn display(&self, f: &mut Formatter, index: NodeIndex, prefix: &str) -> Result {
@@ -103,3 +151,67 @@ impl std::fmt::Display for Tree {
         self.display(f, NodeIndex::new(0), "")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards::isomorphisms::IsomorphismIterator;
+    use crate::cards::street::Street;
+    use crate::clustering::abstraction::Abstraction;
+
+    #[test]
+    fn visiting_a_path_only_grows_that_path() {
+        let encoder = Encoder::from(
+            IsomorphismIterator::from(Street::Pref)
+                .enumerate()
+                .map(|(i, iso)| (iso, Abstraction::from((Street::Pref, i))))
+                .collect::<std::collections::BTreeMap<_, _>>(),
+        );
+        let mut tree = Tree::empty(Player::default());
+        let root = tree.plant(encoder.seed()).index();
+        let path = [Edge::Call, Edge::Check];
+        let mut head = root;
+        for edge in path.iter() {
+            assert!(tree.at(head).edges().len() > 1);
+            head = tree.visit(head, edge, &encoder).index();
+        }
+        assert!(tree.all().len() == path.len() + 1);
+        assert!(tree.at(root).outgoing() == vec![&Edge::Call]);
+        assert!(tree.at(root).leaves().iter().map(|n| n.index()).eq([head]));
+        assert!(tree.at(head).history() == path.iter().collect::<Vec<_>>());
+        let again = tree.visit(root, &Edge::Call, &encoder).index();
+        assert!(tree.all().len() == path.len() + 1);
+        assert!(tree.at(again).follow(&Edge::Check).unwrap().index() == head);
+    }
+
+    #[test]
+    fn infosets_group_decisions_by_bucket() {
+        let encoder = Encoder::from(
+            IsomorphismIterator::from(Street::Pref)
+                .enumerate()
+                .map(|(i, iso)| (iso, Abstraction::from((Street::Pref, i))))
+                .collect::<std::collections::BTreeMap<_, _>>(),
+        );
+        let mut tree = Tree::empty(Player::default());
+        let root = tree.plant(encoder.seed()).index();
+        let call = tree.visit(root, &Edge::Call, &encoder).index();
+        let chance = tree.visit(call, &Edge::Check, &encoder).index();
+        for edge in tree.at(root).edges().iter().filter(|e| **e != Edge::Call) {
+            tree.visit(root, edge, &encoder);
+        }
+        assert!(tree.at(chance).player() == Player::chance());
+        let tree = Arc::new(tree);
+        let infosets = tree.infosets_by_bucket();
+        assert!(infosets.len() == tree.bucket_count());
+        for (bucket, info) in infosets.iter() {
+            assert!(info.roots().iter().all(|n| n.bucket() == bucket));
+            assert!(info.roots().iter().all(|n| n.player() != Player::chance()));
+            assert!(info.roots().iter().all(|n| !n.edges().is_empty()));
+        }
+        let grouped = infosets.values().map(|i| i.roots().len()).sum::<usize>();
+        assert!(grouped < tree.all().len());
+        assert!(infosets
+            .values()
+            .all(|i| i.roots().iter().all(|n| n.index() != chance)));
+    }
+}
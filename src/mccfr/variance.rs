@@ -0,0 +1,81 @@
+use crate::Utility;
+
+/// running mean and variance of a stream of sampled regrets,
+/// by Welford's online algorithm. each update folds one sample
+/// into the mean and the sum of squared deviations from it, so
+/// we never subtract two large, nearly equal sums of squares.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Variance {
+    n: u32,
+    mean: Utility,
+    m2: Utility,
+}
+
+impl Variance {
+    pub fn add(&mut self, x: Utility) {
+        self.n += 1;
+        let delta = x - self.mean;
+        self.mean += delta / self.n as Utility;
+        self.m2 += delta * (x - self.mean);
+    }
+    /// number of samples seen
+    pub fn count(&self) -> usize {
+        self.n as usize
+    }
+    pub fn mean(&self) -> Utility {
+        self.mean
+    }
+    /// unbiased sample variance, undefined below two samples
+    pub fn sample(&self) -> Option<Utility> {
+        match self.n {
+            0 | 1 => None,
+            n => Some(self.m2 / (n - 1) as Utility),
+        }
+    }
+    /// variance of the mean regret as an estimate, which is what
+    /// shrinks as a Bucket is visited more. infinite when we have
+    /// too few samples to say anything at all.
+    pub fn estimate(&self) -> Utility {
+        self.sample()
+            .map(|variance| variance / self.n as Utility)
+            .unwrap_or(Utility::INFINITY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+
+    #[test]
+    fn welford_matches_two_pass() {
+        let ref mut rng = rand::thread_rng();
+        let xs = (0..1000)
+            .map(|_| 1e4 + rng.gen_range(-1.0..1.0))
+            .collect::<Vec<Utility>>();
+        let mut variance = Variance::default();
+        xs.iter().for_each(|x| variance.add(*x));
+        let n = xs.len() as f64;
+        let mean = xs.iter().map(|x| *x as f64).sum::<f64>() / n;
+        let exact = xs.iter().map(|x| (*x as f64 - mean).powi(2)).sum::<f64>() / (n - 1.);
+        let online = variance.sample().unwrap() as f64;
+        assert!(variance.count() == xs.len());
+        assert!(
+            (online - exact).abs() < 1e-2 * exact,
+            "{} !~ {}",
+            online,
+            exact
+        );
+    }
+
+    #[test]
+    fn too_few_samples_are_unreliable() {
+        let mut variance = Variance::default();
+        assert!(variance.estimate() == Utility::INFINITY);
+        variance.add(1.);
+        assert!(variance.sample().is_none());
+        variance.add(3.);
+        assert!(variance.sample() == Some(2.));
+        assert!(variance.estimate() == 1.);
+    }
+}
@@ -0,0 +1,194 @@
+use serde::Deserialize;
+use serde::Serialize;
+use std::ops::Add;
+use std::ops::AddAssign;
+use std::ops::Div;
+use std::ops::Mul;
+use std::ops::Sub;
+
+/// a value in [0, 1]. the range is checked on construction
+/// in debug builds, so that an out-of-range probability panics
+/// where it was produced rather than wherever it is consumed.
+///
+/// sums of normalized f32 vectors drift a few ulps past 1,
+/// so the upper bound is only checked up to TOLERANCE.
+///
+/// products and sums of Probabilities are Probabilities.
+/// ratios of them, and their products with anything else,
+/// are plain f32, since those have no range to enforce.
+#[derive(Debug, Default, Clone, Copy, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Probability(f32);
+
+impl Probability {
+    pub const ZERO: Self = Self(0.);
+    pub const ONE: Self = Self(1.);
+    pub const MIN_POSITIVE: Self = Self(f32::MIN_POSITIVE);
+    pub const TOLERANCE: f32 = 1e-4;
+
+    /// wrap an f32, asserting in debug builds that it lies in [0, 1]
+    pub fn new(p: f32) -> Self {
+        debug_assert!(
+            (0. ..=1. + Self::TOLERANCE).contains(&p),
+            "probability out of range: {}",
+            p
+        );
+        Self(p)
+    }
+    /// uniform probability over n outcomes
+    pub fn uniform(n: usize) -> Self {
+        Self::new(1. / n as f32)
+    }
+    /// probability of the complementary event
+    pub fn complement(self) -> Self {
+        Self::new((1. - self.0).max(0.))
+    }
+    pub fn min(self, other: Self) -> Self {
+        Self(self.0.min(other.0))
+    }
+    pub fn max(self, other: Self) -> Self {
+        Self(self.0.max(other.0))
+    }
+}
+
+impl From<f32> for Probability {
+    fn from(p: f32) -> Self {
+        Self::new(p)
+    }
+}
+
+impl From<Probability> for f32 {
+    fn from(p: Probability) -> Self {
+        p.0
+    }
+}
+
+impl PartialEq<f32> for Probability {
+    fn eq(&self, other: &f32) -> bool {
+        self.0 == *other
+    }
+}
+
+impl PartialOrd<f32> for Probability {
+    fn partial_cmp(&self, other: &f32) -> Option<std::cmp::Ordering> {
+        self.0.partial_cmp(other)
+    }
+}
+
+impl std::fmt::Display for Probability {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        std::fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl Add for Probability {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self {
+        Self::new(self.0 + rhs.0)
+    }
+}
+
+impl AddAssign for Probability {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub for Probability {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self {
+        Self::new(self.0 - rhs.0)
+    }
+}
+
+impl Mul for Probability {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self {
+        Self::new(self.0 * rhs.0)
+    }
+}
+
+impl Mul<f32> for Probability {
+    type Output = f32;
+    fn mul(self, rhs: f32) -> f32 {
+        self.0 * rhs
+    }
+}
+
+impl Mul<Probability> for f32 {
+    type Output = f32;
+    fn mul(self, rhs: Probability) -> f32 {
+        self * rhs.0
+    }
+}
+
+impl Div for Probability {
+    type Output = f32;
+    fn div(self, rhs: Self) -> f32 {
+        self.0 / rhs.0
+    }
+}
+
+impl Div<Probability> for f32 {
+    type Output = f32;
+    fn div(self, rhs: Probability) -> f32 {
+        self / rhs.0
+    }
+}
+
+impl std::iter::Sum for Probability {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::ZERO, Add::add)
+    }
+}
+
+impl<'a> std::iter::Sum<&'a Probability> for Probability {
+    fn sum<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+        iter.copied().sum()
+    }
+}
+
+impl std::iter::Product for Probability {
+    fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::ONE, Mul::mul)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arithmetic_stays_in_range() {
+        let p = Probability::from(0.25);
+        let q = Probability::from(0.5);
+        assert!(p * q == Probability::from(0.125));
+        assert!(p + q == Probability::from(0.75));
+        assert!(q - p == p);
+        assert!(q.complement() == q);
+        assert!(q / p == 2.);
+        assert!(p * 8. == 2.);
+        assert!([p, p, q].into_iter().sum::<Probability>() == Probability::ONE);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "probability out of range")]
+    fn above_one_panics() {
+        let _ = Probability::from(1.5);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "probability out of range")]
+    fn negative_panics() {
+        let _ = Probability::from(-0.2);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "probability out of range")]
+    fn sum_past_one_panics() {
+        let _ = Probability::from(0.75) + Probability::from(0.75);
+    }
+}
@@ -1,4 +1,6 @@
 use crate::cards::street::Street;
+use std::path::Path;
+use std::path::PathBuf;
 use tokio_postgres::types::Type;
 
 // blueprint    ~ 154M, (grows with number of CFR iterations)
@@ -8,6 +10,296 @@ use tokio_postgres::types::Type;
 // abstraction  ~ 500,
 // street       ~ 4
 
+/// on-disk encoding of Table artifacts
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SaveFormat {
+    #[default]
+    Pgcopy,
+}
+
+/// default directory containing Table artifacts, {cwd}/pgcopy.
+/// a Pipeline writes into its own directory instead
+pub fn directory() -> PathBuf {
+    std::env::current_dir().unwrap_or_default().join("pgcopy")
+}
+
+/// layout version written into the header extension of every artifact.
+/// bump this whenever the rows of any Table change shape.
+pub const VERSION: u32 = 1;
+
+/// consume a PGCOPY header and return the format version it declares.
+/// files written before versioning have an empty extension area
+/// and are read as version 0, which shares the version 1 row layout.
+pub fn version(reader: &mut impl std::io::Read) -> Result<u32, String> {
+    use byteorder::ReadBytesExt;
+    use byteorder::BE;
+    let ref mut signature = [0u8; 11];
+    reader
+        .read_exact(signature)
+        .map_err(|_| "truncated PGCOPY header".to_string())?;
+    if signature != b"PGCOPY\n\xFF\r\n\0" {
+        return Err("no PGCOPY header".to_string());
+    }
+    let _flags = reader.read_u32::<BE>().map_err(|e| e.to_string())?;
+    let extension = reader.read_u32::<BE>().map_err(|e| e.to_string())?;
+    let version = match extension {
+        0 => 0,
+        n if n >= 4 => {
+            let version = reader.read_u32::<BE>().map_err(|e| e.to_string())?;
+            std::io::copy(
+                &mut std::io::Read::take(&mut *reader, n as u64 - 4),
+                &mut std::io::sink(),
+            )
+            .map_err(|e| e.to_string())?;
+            version
+        }
+        n => return Err(format!("malformed header extension of {} bytes", n)),
+    };
+    match version {
+        v if v <= VERSION => Ok(v),
+        v => Err(format!(
+            "unsupported version {} (this build reads up to {})",
+            v, VERSION
+        )),
+    }
+}
+
+/// why a PGCOPY artifact could not be read
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LoadError {
+    /// the file could not be opened at all
+    Open(String),
+    /// no PGCOPY signature, or a header this build can't read
+    Header(String),
+    /// a row declared a number of fields this table doesn't have
+    Fields(u16),
+    /// a field declared a width this table doesn't use
+    Width(u32),
+    /// the file ended partway through a row
+    Truncated,
+    /// the file ended without the 0xFFFF trailer
+    Trailer,
+    /// raw keys that appear in more than one row
+    Duplicates(Vec<i64>),
+    /// the CRC32 of the file disagrees with its stamp: (computed, stamped)
+    Checksum(u32, u32),
+}
+
+impl std::fmt::Display for LoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Open(e) => write!(f, "cannot open {}", e),
+            Self::Header(e) => write!(f, "{}", e),
+            Self::Fields(n) => write!(f, "unexpected number of fields: {}", n),
+            Self::Width(n) => write!(f, "unexpected field width: {}", n),
+            Self::Truncated => write!(f, "truncated row"),
+            Self::Trailer => write!(f, "no PGCOPY trailer"),
+            Self::Duplicates(keys) => write!(f, "{} duplicate keys", keys.len()),
+            Self::Checksum(computed, stamped) => write!(
+                f,
+                "PGCOPY checksum mismatch: computed {:08x}, stamped {:08x}",
+                computed, stamped
+            ),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+/// any read that comes up short inside a row
+impl From<std::io::Error> for LoadError {
+    fn from(_: std::io::Error) -> Self {
+        Self::Truncated
+    }
+}
+
+/// open an artifact for parsing, as reader does,
+/// expecting the CRC32 in its stamp if it has one
+pub fn open(path: &str) -> Result<Checked<Box<dyn std::io::Read + Send>>, LoadError> {
+    reader(path)
+        .map(Checked::from)
+        .map(|reader| reader.with_crc(crc_stamped(path)))
+        .map_err(|e| LoadError::Open(format!("{}: {}", path, e)))
+}
+
+/// consume a PGCOPY header, leaving the reader at the first row
+pub fn header(reader: &mut impl std::io::Read) -> Result<u32, LoadError> {
+    version(reader).map_err(LoadError::Header)
+}
+
+/// consume the field count that opens the next row.
+/// false at the trailer, where every well-formed artifact ends,
+/// once the CRC32 of the whole file agrees with its stamp
+pub fn row(reader: &mut Checked<impl std::io::Read>, fields: u16) -> Result<bool, LoadError> {
+    use std::io::Read;
+    let buffer = &mut [0u8; 2];
+    reader.read_exact(buffer).map_err(|_| LoadError::Trailer)?;
+    match u16::from_be_bytes(*buffer) {
+        0xFFFF => match checksum(reader) {
+            Some((computed, stamped)) if computed != stamped => {
+                Err(LoadError::Checksum(computed, stamped))
+            }
+            _ => Ok(false),
+        },
+        n if n == fields => Ok(true),
+        n => Err(LoadError::Fields(n)),
+    }
+}
+
+/// CRC32 of everything read so far, next to the one stamped beside
+/// the artifact. None if there is no stamp to check against, as for
+/// artifacts stamped before checksums or bytes parsed from memory
+pub fn checksum(reader: &Checked<impl std::io::Read>) -> Option<(u32, u32)> {
+    reader.expected.map(|stamped| (reader.crc(), stamped))
+}
+
+/// write the 0xFFFF trailer that ends every artifact. nothing
+/// follows it, so files COPY as-is. their CRC32 goes in the stamp
+pub fn trailer(writer: &mut Checked<impl std::io::Write>) -> std::io::Result<()> {
+    use byteorder::WriteBytesExt;
+    use byteorder::BE;
+    writer.write_u16::<BE>(0xFFFF)
+}
+
+/// a reader or writer that keeps a running CRC32 of every byte
+/// through it, so checksumming an artifact takes no second pass
+pub struct Checked<T> {
+    inner: T,
+    crc: crc32fast::Hasher,
+    expected: Option<u32>,
+}
+
+impl<T> From<T> for Checked<T> {
+    fn from(inner: T) -> Self {
+        Self {
+            inner,
+            crc: crc32fast::Hasher::new(),
+            expected: None,
+        }
+    }
+}
+
+impl<T> Checked<T> {
+    /// CRC32 of every byte so far
+    pub fn crc(&self) -> u32 {
+        self.crc.clone().finalize()
+    }
+    /// the CRC32 that every byte should come to, if known
+    pub fn with_crc(mut self, expected: Option<u32>) -> Self {
+        self.expected = expected;
+        self
+    }
+}
+
+impl<R: std::io::Read> std::io::Read for Checked<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.crc.update(&buf[..n]);
+        Ok(n)
+    }
+}
+
+impl<W: std::io::Write> std::io::Write for Checked<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.crc.update(&buf[..n]);
+        Ok(n)
+    }
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// consume the width that prefixes a field, which must be as expected
+pub fn width(reader: &mut impl std::io::Read, expected: u32) -> Result<(), LoadError> {
+    use byteorder::ReadBytesExt;
+    use byteorder::BE;
+    match reader.read_u32::<BE>()? {
+        n if n == expected => Ok(()),
+        n => Err(LoadError::Width(n)),
+    }
+}
+
+/// the zstd-compressed twin of an artifact
+fn compressed(path: &str) -> String {
+    format!("{}.zst", path)
+}
+
+/// open an artifact for buffered reading. paths ending in .zst are
+/// decompressed on the fly, and a missing artifact falls back to its
+/// compressed twin, so the PGCOPY parsing on top never knows the difference
+pub fn reader(path: &str) -> std::io::Result<Box<dyn std::io::Read + Send>> {
+    use std::io::BufReader;
+    let ref twin = compressed(path);
+    match (
+        path.ends_with(".zst"),
+        Path::new(path).exists() || !Path::new(twin).exists(),
+    ) {
+        (true, _) => Ok(Box::new(zstd::Decoder::new(std::fs::File::open(path)?)?)),
+        (false, true) => Ok(Box::new(BufReader::new(std::fs::File::open(path)?))),
+        (false, false) => Ok(Box::new(zstd::Decoder::new(std::fs::File::open(twin)?)?)),
+    }
+}
+
+/// create an artifact for buffered writing. paths ending in .zst
+/// are compressed on the fly, and the frame is finished on drop.
+/// the CRC32 is over the uncompressed bytes, so it reads back the same
+pub fn writer(path: &str) -> std::io::Result<Checked<Box<dyn std::io::Write + Send>>> {
+    use std::io::BufWriter;
+    let file = std::fs::File::create(path)?;
+    let writer: Box<dyn std::io::Write + Send> = match path.ends_with(".zst") {
+        true => Box::new(BufWriter::new(zstd::Encoder::new(file, 0)?.auto_finish())),
+        false => Box::new(BufWriter::new(file)),
+    };
+    Ok(Checked::from(writer))
+}
+
+/// sidecar holding the content hash of an artifact,
+/// then the CRC32 of its uncompressed bytes
+fn stamp_path(path: &str) -> String {
+    format!("{}.digest", path)
+}
+
+/// hash an artifact once, right after it is written,
+/// and store the digest and checksum next to it
+pub fn stamp(path: &str) {
+    use crate::clustering::cache::Digest;
+    let digest = Digest::file(path).expect(&format!("hash {}", path));
+    let crc = reader(path)
+        .map(Checked::from)
+        .and_then(|mut r| std::io::copy(&mut r, &mut std::io::sink()).map(|_| r.crc()))
+        .unwrap_or_else(|e| panic!("checksum {}: {}", path, e));
+    std::fs::write(stamp_path(path), format!("{:016x}\n{:08x}\n", digest, crc))
+        .expect(&format!("write {}", stamp_path(path)));
+}
+
+/// the digest stored by stamp, if the artifact and its stamp both exist.
+/// artifacts written before stamping are hashed and stamped on first use
+pub fn stamped(path: &str) -> Option<u64> {
+    if !Path::new(path).exists() {
+        return None;
+    }
+    match std::fs::read_to_string(stamp_path(path)) {
+        Ok(hex) => hex
+            .lines()
+            .next()
+            .and_then(|line| u64::from_str_radix(line.trim(), 16).ok()),
+        Err(_) => {
+            stamp(path);
+            stamped(path)
+        }
+    }
+}
+
+/// the CRC32 stored by stamp, if the artifact was stamped with one
+fn crc_stamped(path: &str) -> Option<u32> {
+    std::fs::read_to_string(stamp_path(path))
+        .ok()?
+        .lines()
+        .nth(1)
+        .and_then(|line| u32::from_str_radix(line.trim(), 16).ok())
+}
+
 /// things that can be written to and read from disk, and uploaded into Postgres.
 /// may or may not be dependent on other entities being written/in memory.
 /// dependencies for methods returning Self are up to the implementor.
@@ -26,11 +318,22 @@ pub trait Table {
     fn sources() -> Vec<String>;
     /// build from scratch
     fn grow(street: Street) -> Self;
-    /// read from disk
-    fn load(street: Street) -> Self;
+    /// read from disk, or say why the file can't be read
+    fn load(street: Street) -> Result<Self, LoadError>
+    where
+        Self: Sized;
     /// write to disk
     fn save(&self);
 
+    /// read from disk, for callers that can't go on without it.
+    /// panics naming the file and what is wrong with it
+    fn load_or_panic(street: Street) -> Self
+    where
+        Self: Sized,
+    {
+        Self::load(street).unwrap_or_else(|e| panic!("{}: {}", Self::path(street), e))
+    }
+
     /// query to nuke table in Postgres
     fn truncates() -> String {
         format!(
@@ -41,27 +344,71 @@ pub trait Table {
     }
     /// path to file on disk
     fn path(street: Street) -> String {
-        format!(
-            "{}/pgcopy/{}.{}",
-            std::env::current_dir()
-                .unwrap_or_default()
-                .to_string_lossy()
-                .into_owned(),
-            Self::name(),
-            street
-        )
+        Self::path_in(&directory(), street)
+    }
+    /// path to file on disk under a given directory
+    fn path_in(dir: &Path, street: Street) -> String {
+        format!("{}/{}.{}", dir.to_string_lossy(), Self::name(), street)
     }
     /// check if file exists on disk
     fn done(street: Street) -> bool {
-        std::fs::metadata(Self::path(street)).is_ok()
+        Self::done_in(&directory(), street)
     }
-    /// Postgres signature header + 8 null bytes for flags and extension
+    /// check if file, or its compressed twin, exists on disk under a given directory
+    fn done_in(dir: &Path, street: Street) -> bool {
+        let ref path = Self::path_in(dir, street);
+        std::fs::metadata(path).is_ok() || std::fs::metadata(compressed(path)).is_ok()
+    }
+    /// Postgres signature header, 4 null bytes for flags, and a 4 byte
+    /// extension area holding our format VERSION. Postgres skips extension
+    /// data it doesn't recognize, so the files still COPY as-is.
     /// header for binary copy: https://www.postgresql.org/docs/current/static/sql-copy.html
     fn header() -> &'static [u8] {
-        b"PGCOPY\n\xFF\r\n\0\0\0\0\0\0\0\0\0"
+        b"PGCOPY\n\xFF\r\n\0\0\0\0\0\0\0\0\x04\0\0\0\x01"
     }
-    /// Postgres signature footer to signal end of binary file
+    /// Postgres signature footer to signal end of binary file.
+    /// nothing follows it, see trailer
     fn footer() -> u16 {
         0xFFFF
     }
+    /// bytes per PGCOPY row: a field count, then a length
+    /// and a fixed-width value for every column
+    fn row() -> usize {
+        2 + Self::columns()
+            .iter()
+            .map(|column| match *column {
+                Type::INT2 => 2,
+                Type::INT4 | Type::FLOAT4 => 4,
+                Type::INT8 | Type::FLOAT8 => 8,
+                ref other => panic!("{} has no fixed width", other),
+            })
+            .map(|width| 4 + width)
+            .sum::<usize>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn header_declares_current_version() {
+        let mut header = crate::clustering::metric::Metric::header();
+        assert!(version(&mut header) == Ok(VERSION));
+        assert!(header.is_empty());
+    }
+
+    #[test]
+    fn unversioned_header_is_version_zero() {
+        let mut legacy = &b"PGCOPY\n\xFF\r\n\0\0\0\0\0\0\0\0\0\xFF\xFF"[..];
+        assert!(version(&mut legacy) == Ok(0));
+        assert!(legacy == b"\xFF\xFF");
+    }
+
+    #[test]
+    fn future_version_is_unsupported() {
+        let mut future = &b"PGCOPY\n\xFF\r\n\0\0\0\0\0\0\0\0\x04\0\0\0\x02"[..];
+        let err = version(&mut future).unwrap_err();
+        assert!(err.contains("unsupported version 2"), "{}", err);
+    }
 }
@@ -8,10 +8,6 @@ use crate::mccfr::encoder::Encoder;
 use crate::mccfr::profile::Profile;
 use byteorder::ReadBytesExt;
 use byteorder::BE;
-use std::fs::File;
-use std::io::BufReader;
-use std::io::Read;
-use std::io::Seek;
 use std::sync::Arc;
 use tokio_postgres::binary_copy::BinaryCopyInWriter;
 use tokio_postgres::types::ToSql;
@@ -87,28 +83,19 @@ impl Writer {
         let sink = self.0.copy_in(&T::copy()).await?;
         let writer = BinaryCopyInWriter::new(sink, T::columns());
         futures::pin_mut!(writer);
-        let ref mut fields = [0u8; 2];
-        for ref mut reader in T::sources()
-            .iter()
-            .map(|s| File::open(s).expect("file not found"))
-            .map(|f| BufReader::new(f))
-        {
-            reader.seek(std::io::SeekFrom::Start(19)).unwrap();
-            while let Ok(()) = reader.read_exact(fields) {
-                match u16::from_be_bytes(*fields) {
-                    0xFFFF => break,
-                    length => {
-                        assert!(T::columns().len() == length as usize);
-                        let row = (0..length).map(|_| {
-                            match reader.read_u32::<BE>().expect("field size (bytes)") {
-                                4 => Field::F32(reader.read_f32::<BE>().unwrap()),
-                                8 => Field::I64(reader.read_i64::<BE>().unwrap()),
-                                x => panic!("unsupported type: {}", x),
-                            }
-                        });
-                        writer.as_mut().write_raw(row).await?;
+        let fields = T::columns().len() as u16;
+        for ref path in T::sources() {
+            let ref mut reader = super::upload::open(path).unwrap_or_else(|e| panic!("{}", e));
+            super::upload::header(reader).unwrap_or_else(|e| panic!("{}: {}", path, e));
+            while super::upload::row(reader, fields).unwrap_or_else(|e| panic!("{}: {}", path, e)) {
+                let row = (0..fields).map(|_| {
+                    match reader.read_u32::<BE>().expect("field size (bytes)") {
+                        4 => Field::F32(reader.read_f32::<BE>().unwrap()),
+                        8 => Field::I64(reader.read_i64::<BE>().unwrap()),
+                        x => panic!("unsupported type: {}", x),
                     }
-                }
+                });
+                writer.as_mut().write_raw(row).await?;
             }
         }
         writer.finish().await?;
@@ -1,11 +1,10 @@
 use super::support::Support;
-use crate::Probability;
 
 /// generalization of any probability distribution over
 /// arbitrary Support.
 pub trait Density {
     type S: Support;
 
-    fn density(&self, x: &Self::S) -> Probability;
+    fn density(&self, x: &Self::S) -> f32;
     fn support(&self) -> impl Iterator<Item = &Self::S>;
 }